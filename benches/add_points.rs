@@ -280,5 +280,41 @@ fn bench_add_to_populated_fixed_u16<A: Unsigned, T: Content, const K: usize, IDX
     );
 }
 
-criterion_group!(benches, add_to_empty, add_to_populated);
+pub fn add_many_to_empty(c: &mut Criterion) {
+    const QTY_TO_ADD: usize = 1_000_000;
+
+    let mut group = c.benchmark_group("Add 1M points to Empty Tree, f64 K=3");
+    group.throughput(Throughput::Elements(QTY_TO_ADD as u64));
+
+    let points_to_add: Vec<([f64; 3], u32)> = (0..QTY_TO_ADD)
+        .map(|_| rand::random::<([f64; 3], u32)>())
+        .collect();
+
+    group.bench_function("add", |b| {
+        b.iter_batched(
+            KdTree::<f64, u32, 3, BUCKET_SIZE, u32>::new,
+            |mut kdtree| {
+                points_to_add
+                    .iter()
+                    .for_each(|point| kdtree.add(&point.0, point.1));
+                black_box(kdtree)
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("add_many", |b| {
+        b.iter_batched(
+            KdTree::<f64, u32, 3, BUCKET_SIZE, u32>::new,
+            |mut kdtree| {
+                kdtree.add_many(&points_to_add);
+                black_box(kdtree)
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, add_to_empty, add_to_populated, add_many_to_empty);
 criterion_main!(benches);
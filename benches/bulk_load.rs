@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kiddo::float::bulk_loader::BulkLoader;
+use kiddo::float::kdtree::KdTree;
+
+const TREE_SIZE: usize = 1_000_000;
+const BUCKET_SIZE: usize = 32;
+
+fn bench_add_vs_bulk_load(c: &mut Criterion) {
+    let points: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+        .map(|item| (rand::random::<[f64; 3]>(), item as u32))
+        .collect();
+
+    let mut group = c.benchmark_group("Load tree, f64 K=3, 1M points");
+    group.throughput(Throughput::Elements(TREE_SIZE as u64));
+    group.sample_size(10);
+
+    group.bench_function("repeated add", |b| {
+        b.iter(|| {
+            let mut tree: KdTree<f64, u32, 3, BUCKET_SIZE, u32> = KdTree::with_capacity(TREE_SIZE);
+            points
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+            black_box(tree)
+        })
+    });
+    group.bench_function("append then finalize", |b| {
+        b.iter(|| {
+            let mut loader: BulkLoader<f64, u32, 3, BUCKET_SIZE, u32> =
+                BulkLoader::with_capacity(TREE_SIZE);
+            points
+                .iter()
+                .for_each(|(point, item)| loader.append(point, *item));
+            black_box(loader.finalize())
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_add_vs_bulk_load);
+criterion_main!(benches);
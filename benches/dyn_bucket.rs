@@ -0,0 +1,76 @@
+// compares the speed of KdTree and DynBucketKdTree; their binary sizes are compared by
+// the bucket_size_const and bucket_size_dyn examples
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kiddo::float::distance::squared_euclidean;
+use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+use kiddo::float::kdtree::KdTree;
+
+const TREE_SIZE: usize = 100_000;
+const QUERY_POINTS_PER_LOOP: usize = 1000;
+const BUCKET_SIZE: usize = 32;
+
+fn bench_kdtree_vs_dyn_bucket_kdtree(c: &mut Criterion) {
+    let points: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+        .map(|item| (rand::random::<[f64; 3]>(), item as u32))
+        .collect();
+    let query_points: Vec<[f64; 3]> = (0..QUERY_POINTS_PER_LOOP)
+        .map(|_| rand::random::<[f64; 3]>())
+        .collect();
+
+    let mut group = c.benchmark_group("Add points, f64 K=3, 100k points");
+    group.throughput(Throughput::Elements(TREE_SIZE as u64));
+
+    group.bench_function("KdTree", |b| {
+        b.iter(|| {
+            let mut tree: KdTree<f64, u32, 3, BUCKET_SIZE, u32> = KdTree::with_capacity(TREE_SIZE);
+            points
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+            black_box(tree)
+        })
+    });
+    group.bench_function("DynBucketKdTree", |b| {
+        b.iter(|| {
+            let mut tree: DynBucketKdTree<f64, u32, 3> =
+                DynBucketKdTree::with_capacity(BUCKET_SIZE, TREE_SIZE);
+            points
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+            black_box(tree)
+        })
+    });
+
+    group.finish();
+
+    let mut tree: KdTree<f64, u32, 3, BUCKET_SIZE, u32> = KdTree::with_capacity(TREE_SIZE);
+    let mut dyn_tree: DynBucketKdTree<f64, u32, 3> =
+        DynBucketKdTree::with_capacity(BUCKET_SIZE, TREE_SIZE);
+    points.iter().for_each(|(point, item)| {
+        tree.add(point, *item);
+        dyn_tree.add(point, *item);
+    });
+
+    let mut group = c.benchmark_group("Query Nearest 1, f64 K=3, 100k points");
+    group.throughput(Throughput::Elements(QUERY_POINTS_PER_LOOP as u64));
+
+    group.bench_function("KdTree", |b| {
+        b.iter(|| {
+            query_points.iter().for_each(|query| {
+                black_box(tree.nearest_one(query, &squared_euclidean));
+            })
+        })
+    });
+    group.bench_function("DynBucketKdTree", |b| {
+        b.iter(|| {
+            query_points.iter().for_each(|query| {
+                black_box(dyn_tree.nearest_one(query, &squared_euclidean));
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_kdtree_vs_dyn_bucket_kdtree);
+criterion_main!(benches);
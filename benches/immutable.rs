@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kiddo::float::distance::squared_euclidean;
+use kiddo::float::kdtree::KdTree;
+
+const TREE_SIZE: usize = 100_000;
+const QUERY_POINTS_PER_LOOP: usize = 1000;
+
+fn bench_nearest_one_mutable_vs_immutable(c: &mut Criterion) {
+    let points: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+        .map(|item| (rand::random::<[f64; 3]>(), item as u32))
+        .collect();
+    let query_points: Vec<[f64; 3]> = (0..QUERY_POINTS_PER_LOOP)
+        .map(|_| rand::random::<[f64; 3]>())
+        .collect();
+
+    let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+    points
+        .iter()
+        .for_each(|(point, item)| tree.add(point, *item));
+    let immutable = tree.clone().into_immutable();
+
+    let mut group = c.benchmark_group("Nearest One, f64 K=3, 100k points");
+    group.throughput(Throughput::Elements(QUERY_POINTS_PER_LOOP as u64));
+
+    group.bench_function("KdTree", |b| {
+        b.iter(|| {
+            query_points.iter().for_each(|point| {
+                black_box(tree.nearest_one(point, &squared_euclidean));
+            })
+        })
+    });
+    group.bench_function("ImmutableKdTree", |b| {
+        b.iter(|| {
+            query_points.iter().for_each(|point| {
+                black_box(immutable.nearest_one(point, &squared_euclidean));
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_nearest_one_mutable_vs_immutable);
+criterion_main!(benches);
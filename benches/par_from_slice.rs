@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kiddo::float::kdtree::KdTree;
+
+const TREE_SIZE: usize = 10_000_000;
+const BUCKET_SIZE: usize = 32;
+
+fn bench_from_slice_vs_par_from_slice(c: &mut Criterion) {
+    let points: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+        .map(|item| (rand::random::<[f64; 3]>(), item as u32))
+        .collect();
+
+    let mut group = c.benchmark_group("Build balanced tree, f64 K=3, 10M points");
+    group.throughput(Throughput::Elements(TREE_SIZE as u64));
+    group.sample_size(10);
+
+    group.bench_function("from_slice", |b| {
+        b.iter(|| black_box(KdTree::<f64, u32, 3, BUCKET_SIZE, u32>::from_slice(&points)))
+    });
+    group.bench_function(
+        format!("par_from_slice ({} threads)", rayon::current_num_threads()),
+        |b| {
+            b.iter(|| {
+                black_box(KdTree::<f64, u32, 3, BUCKET_SIZE, u32>::par_from_slice(
+                    &points,
+                ))
+            })
+        },
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_from_slice_vs_par_from_slice);
+criterion_main!(benches);
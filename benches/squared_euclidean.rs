@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kiddo::float::distance::{squared_euclidean, squared_euclidean_simd};
+use rand::distributions::Standard;
+use rand::Rng;
+use rand_distr::Distribution;
+
+const POINT_PAIRS_PER_LOOP: usize = 1000;
+
+fn bench_squared_euclidean<const K: usize>(c: &mut Criterion)
+where
+    Standard: Distribution<[f32; K]>,
+{
+    let mut rng = rand::thread_rng();
+    let pairs: Vec<([f32; K], [f32; K])> = (0..POINT_PAIRS_PER_LOOP)
+        .map(|_| (rng.gen(), rng.gen()))
+        .collect();
+
+    let mut group = c.benchmark_group(format!("Squared Euclidean f32 K={K}"));
+    group.throughput(Throughput::Elements(POINT_PAIRS_PER_LOOP as u64));
+
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            pairs
+                .iter()
+                .map(|(a, b)| squared_euclidean(black_box(a), black_box(b)))
+                .sum::<f32>()
+        })
+    });
+    group.bench_function("simd", |b| {
+        b.iter(|| {
+            pairs
+                .iter()
+                .map(|(a, b)| squared_euclidean_simd(black_box(a), black_box(b)))
+                .sum::<f32>()
+        })
+    });
+
+    group.finish();
+}
+
+pub fn squared_euclidean_benches(c: &mut Criterion) {
+    bench_squared_euclidean::<3>(c);
+    bench_squared_euclidean::<4>(c);
+}
+
+criterion_group!(benches, squared_euclidean_benches);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kiddo::float::kdtree::KdTree;
+use rand::Rng;
+
+// run with and without `--features bbox-cache` to compare the two
+const TREE_SIZE: usize = 1_000_000;
+const QUERY_BOXES_PER_LOOP: usize = 1000;
+const NUM_CLUSTERS: usize = 20;
+const BOX_SIZE: f64 = 0.05;
+
+fn query_boxes() -> Vec<([f64; 3], [f64; 3])> {
+    (0..QUERY_BOXES_PER_LOOP)
+        .map(|_| {
+            let min = rand::random::<[f64; 3]>().map(|x| x * (1.0 - BOX_SIZE));
+            (min, min.map(|x| x + BOX_SIZE))
+        })
+        .collect()
+}
+
+fn bench_query(c: &mut Criterion, name: &str, points: &[([f64; 3], u32)]) {
+    let tree: KdTree<f64, u32, 3, 32, u32> = KdTree::from_slice(points);
+    let query_boxes = query_boxes();
+
+    let mut group = c.benchmark_group(format!("Query: within_bbox, {}", name));
+    group.throughput(Throughput::Elements(QUERY_BOXES_PER_LOOP as u64));
+
+    group.bench_function("from_slice", |b| {
+        b.iter(|| {
+            query_boxes.iter().for_each(|(min, max)| {
+                black_box(tree.within_bbox(min, max));
+            })
+        })
+    });
+
+    let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::with_capacity(points.len());
+    points
+        .iter()
+        .for_each(|(point, item)| tree.add(point, *item));
+
+    group.bench_function("add", |b| {
+        b.iter(|| {
+            query_boxes.iter().for_each(|(min, max)| {
+                black_box(tree.within_bbox(min, max));
+            })
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_within_bbox(c: &mut Criterion) {
+    let uniform: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+        .map(|item| (rand::random::<[f64; 3]>(), item as u32))
+        .collect();
+    bench_query(c, "uniform points", &uniform);
+
+    // points in small, widely spaced clusters, leaving most of the space empty
+    let mut rng = rand::thread_rng();
+    let centres: Vec<[f64; 3]> = (0..NUM_CLUSTERS).map(|_| rand::random()).collect();
+    let clustered: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+        .map(|item| {
+            let centre = centres[item % NUM_CLUSTERS];
+            let point = centre.map(|x| x + rng.gen_range(-0.01..0.01));
+            (point, item as u32)
+        })
+        .collect();
+    bench_query(c, "clustered points", &clustered);
+}
+
+criterion_group!(benches, bench_within_bbox);
+criterion_main!(benches);
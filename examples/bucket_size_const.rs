@@ -0,0 +1,33 @@
+/// Kiddo example 4: binary size of compile-time bucket sizes
+///
+/// Builds and queries a `KdTree` with each of six bucket sizes. As the bucket size `B`
+/// is a const generic parameter, the tree's code is compiled once for each of them.
+/// Compare the size of this example's release binary against that of the
+/// `bucket_size_dyn` example, which does the same with a single `DynBucketKdTree`.
+use kiddo::float::distance::squared_euclidean;
+use kiddo::float::kdtree::KdTree;
+
+const NUM_POINTS: u32 = 10_000;
+
+macro_rules! build_and_query {
+    ($bucket_size:literal) => {{
+        let mut tree: KdTree<f64, u32, 3, $bucket_size, u32> = KdTree::new();
+        for item in 0..NUM_POINTS {
+            tree.add(&rand::random(), item);
+        }
+        let nearest = tree.nearest_one(&[0.5, 0.5, 0.5], &squared_euclidean);
+        println!(
+            "B = {:>3}: nearest to the centre is {:?}",
+            $bucket_size, nearest
+        );
+    }};
+}
+
+fn main() {
+    build_and_query!(4);
+    build_and_query!(8);
+    build_and_query!(16);
+    build_and_query!(32);
+    build_and_query!(64);
+    build_and_query!(128);
+}
@@ -0,0 +1,24 @@
+/// Kiddo example 5: binary size of run-time bucket sizes
+///
+/// Builds and queries a `DynBucketKdTree` with each of six bucket sizes. As the bucket
+/// size is chosen at run time, the tree's code is only compiled once. Compare the size
+/// of this example's release binary against that of the `bucket_size_const` example,
+/// which does the same with a `KdTree` for each bucket size.
+use kiddo::float::distance::squared_euclidean;
+use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+
+const NUM_POINTS: u32 = 10_000;
+
+fn main() {
+    for bucket_size in [4, 8, 16, 32, 64, 128] {
+        let mut tree: DynBucketKdTree<f64, u32, 3> = DynBucketKdTree::new(bucket_size);
+        for item in 0..NUM_POINTS {
+            tree.add(&rand::random(), item);
+        }
+        let nearest = tree.nearest_one(&[0.5, 0.5, 0.5], &squared_euclidean);
+        println!(
+            "B = {:>3}: nearest to the centre is {:?}",
+            bucket_size, nearest
+        );
+    }
+}
@@ -8,7 +8,10 @@ use std::fmt::Formatter;
 use std::fs::File;
 
 use csv::Reader;
-use kiddo::float::{distance::squared_euclidean, kdtree::KdTree};
+use kiddo::float::{
+    distance::{squared_euclidean, Haversine},
+    kdtree::KdTree,
+};
 use serde::Deserialize;
 
 #[allow(dead_code)]
@@ -44,6 +47,10 @@ impl CityCsvRecord {
     pub fn as_xyz(&self) -> [f32; 3] {
         degrees_lat_lng_to_unit_sphere(self.lat, self.lng)
     }
+
+    pub fn as_lat_lng_radians(&self) -> [f32; 2] {
+        [self.lat.to_radians(), self.lng.to_radians()]
+    }
 }
 
 /// converts Earth surface co-ordinates in degrees of latitude and longitude to 3D cartesian coordinates on a unit sphere
@@ -195,6 +202,29 @@ fn main() -> Result<(), Box<dyn Error>> {
         best_3
     );
 
+    // ### Optionally, measure great-circle distances directly with `Haversine`
+    // Rather than converting to 3D positions, we can store the latitude and longitude
+    // of each city (in radians) in a 2D tree and query it using the `Haversine` metric,
+    // which measures the actual distance along the surface of the Earth. This is
+    // slower than squared euclidean distance, but the distances that come back are
+    // angles that only need multiplying by the Earth's radius to get kilometres.
+    // Run the example with the `--haversine` argument to try this out.
+    if std::env::args().any(|arg| arg == "--haversine") {
+        let mut lat_lng_kdtree: KdTree<f32, usize, 2, 32, u16> =
+            KdTree::with_capacity(cities.len());
+        cities.iter().enumerate().for_each(|(idx, city)| {
+            lat_lng_kdtree.add(&city.as_lat_lng_radians(), idx);
+        });
+
+        let query = [52.5f32.to_radians(), (-1.9f32).to_radians()];
+        let (dist, nearest_idx) = lat_lng_kdtree.nearest_one(&query, &Haversine);
+        println!(
+            "\nNearest city to 52.5N, 1.9W by great-circle distance: {} ({:.1}km)",
+            &cities[nearest_idx],
+            dist * EARTH_RADIUS_IN_KM
+        );
+    }
+
     Ok(())
 }
 
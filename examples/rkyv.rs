@@ -91,6 +91,21 @@ fn main() -> Result<(), Box<dyn Error>> {
     let nearest = &cities[nearest_idx as usize];
     println!("\nNearest city to 52.5N, 1.9W: {:?}", nearest);
 
+    // Rather than deserializing, we can memory-map the file and query the
+    // archived tree directly. This is near-instant, even for very large trees.
+    let start = Instant::now();
+    let file = File::open("./examples/geonames-tree.rkyv")?;
+    let mmap = unsafe { memmap::Mmap::map(&file)? };
+    let archived_tree = unsafe { rkyv::archived_root::<KdTree<f32, 3>>(&mmap) };
+    println!(
+        "Memory-mapped rkyv file as an archived kd-tree ({})",
+        ElapsedDuration::new(start.elapsed())
+    );
+
+    let (_, nearest_idx) = archived_tree.nearest_one(&query, &squared_euclidean);
+    let nearest = &cities[nearest_idx];
+    println!("\nNearest city to 52.5N, 1.9W: {:?}", nearest);
+
     Ok(())
 }
 
@@ -1,10 +1,10 @@
 //! Some experimental distance metrics work
 #![allow(missing_docs)]
 
-use num_traits::Float;
+use num_traits::Num;
 
 #[cfg(any(target_arch = "x86_64"))]
-use std::arch::x86_64::*;
+use core::arch::x86_64::*;
 
 #[cfg(any(target_arch = "x86_64"))]
 union SimdToArray {
@@ -12,18 +12,18 @@ union SimdToArray {
     simd: __m128,
 }
 
-pub fn squared_euclidean<T: Float, const K: usize>(a: &[T; K], b: &[T; K]) -> T {
+pub fn squared_euclidean<T: Num + Copy, const K: usize>(a: &[T; K], b: &[T; K]) -> T {
     a.iter()
         .zip(b.iter())
         .map(|(x, y)| ((*x) - (*y)) * ((*x) - (*y)))
-        .fold(T::zero(), ::std::ops::Add::add)
+        .fold(T::zero(), ::core::ops::Add::add)
 }
 
 pub fn dot_product<const K: usize>(a: &[f32; K], b: &[f32; K]) -> f32 {
     a.iter()
         .zip(b.iter())
         .map(|(x, y)| ((*x) * (*y)))
-        .fold(0f32, ::std::ops::Sub::sub)
+        .fold(0f32, ::core::ops::Sub::sub)
 }
 
 #[cfg(any(target_arch = "x86_64"))]
@@ -0,0 +1,102 @@
+//! Errors that can be returned by fallible tree operations.
+
+use core::fmt;
+
+/// Returned when attempting to add a point to a float tree when one or more of the
+/// point's coordinates are NaN.
+///
+/// NaN coordinates cannot be ordered, so storing one would leave the tree unable
+/// to find it, or anything that ends up in the same part of the tree, again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NanError;
+
+impl fmt::Display for NanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "point has a NaN coordinate")
+    }
+}
+
+impl core::error::Error for NanError {}
+
+/// Returned when attempting to add a point to a tree that has no room for the new leaf and
+/// stem nodes that adding it would require.
+///
+/// Nodes are referred to by indices of type `IDX`, so a tree cannot have more nodes than
+/// `IDX` can index. With a small `IDX`, such as `u16`, a large tree can run out of indices;
+/// use a larger `IDX` if this happens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityExceeded;
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tree has too many nodes to be indexed by its IDX type")
+    }
+}
+
+impl core::error::Error for CapacityExceeded {}
+
+/// Returned when a point cannot be added to a float tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddError {
+    /// One or more of the point's coordinates are NaN. See [`NanError`].
+    Nan(NanError),
+    /// The tree is too large for its `IDX` type. See [`CapacityExceeded`].
+    CapacityExceeded(CapacityExceeded),
+}
+
+impl From<NanError> for AddError {
+    fn from(err: NanError) -> Self {
+        AddError::Nan(err)
+    }
+}
+
+impl From<CapacityExceeded> for AddError {
+    fn from(err: CapacityExceeded) -> Self {
+        AddError::CapacityExceeded(err)
+    }
+}
+
+impl fmt::Display for AddError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddError::Nan(err) => err.fmt(f),
+            AddError::CapacityExceeded(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for AddError {}
+
+/// Returned when bytes passed to `from_bytes` cannot be deserialized into a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromBytesError {
+    /// The bytes do not start with the magic number, so are not a serialized tree.
+    BadMagic,
+    /// The bytes are in a version of the format that this version of kiddo cannot read.
+    UnsupportedVersion(u8),
+    /// The tree was serialized with a different `K`, `B`, or type of `A`, `T` or `IDX`.
+    LayoutMismatch,
+    /// The bytes end before the end of the tree.
+    Truncated,
+    /// The bytes do not describe a valid tree, for example because a node index is out of
+    /// range.
+    Corrupt,
+}
+
+impl fmt::Display for FromBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromBytesError::BadMagic => write!(f, "bytes are not a serialized tree"),
+            FromBytesError::UnsupportedVersion(version) => {
+                write!(f, "unsupported serialization format version {version}")
+            }
+            FromBytesError::LayoutMismatch => {
+                write!(f, "tree was serialized with different type parameters")
+            }
+            FromBytesError::Truncated => write!(f, "bytes end before the end of the tree"),
+            FromBytesError::Corrupt => write!(f, "bytes do not describe a valid tree"),
+        }
+    }
+}
+
+impl core::error::Error for FromBytesError {}
@@ -1,8 +1,12 @@
+use crate::error::CapacityExceeded;
 use crate::fixed::kdtree::{Axis, KdTree, LeafNode, StemNode};
 use crate::mirror_select_nth_unstable_by::mirror_select_nth_unstable_by;
+use crate::split_ties::tie_goes_left;
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+use alloc::vec;
 use az::{Az, Cast};
-use std::ops::Rem;
+use core::ops::Rem;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -14,6 +18,15 @@ where
     /// The first argument specifies co-ordinates of the point where the item is located.
     /// The second argument is an integer identifier / index for the item being stored.
     ///
+    /// When a leaf is full, it is split in two, with half of its points moving to a new
+    /// leaf. This happens even if the points all have the same co-ordinate along the axis
+    /// being split. Points that are at the same position cannot be separated by any
+    /// split, but they are still shared between the two leaves, and points added at that
+    /// position later are spread over the leaves below it, so adding any number of them
+    /// is fine and the tree only grows with the logarithm of their number. Queries near
+    /// them still have to look at all of them, so if many items share a position,
+    /// consider a larger bucket size `B`.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -38,12 +51,17 @@ where
             let mut stem_node;
             let mut parent_idx = <IDX as Index>::max();
             let mut is_left_child: bool = false;
+            let tie_seed = self.stems.len();
+            let mut depth = 0;
 
             while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
                 parent_idx = stem_idx;
-                stem_node = self.stems.get_unchecked_mut(stem_idx.az::<usize>());
+                stem_node = self.stems.index_unchecked_mut(stem_idx.az::<usize>());
 
-                stem_idx = if *query.get_unchecked(split_dim) <= stem_node.split_val {
+                let query_val = *query.index_unchecked(split_dim);
+                stem_idx = if query_val < stem_node.split_val
+                    || (query_val == stem_node.split_val && tie_goes_left(tie_seed, depth))
+                {
                     is_left_child = true;
                     stem_node.left
                 } else {
@@ -52,36 +70,70 @@ where
                 };
 
                 split_dim = (split_dim + 1).rem(K);
+                depth += 1;
             }
 
             let mut leaf_idx = stem_idx - IDX::leaf_offset();
-            let mut leaf_node = self.leaves.get_unchecked_mut(leaf_idx.az::<usize>());
+            let mut leaf_node = self.leaves.index_unchecked_mut(leaf_idx.az::<usize>());
 
             if leaf_node.size == B.az::<IDX>() {
                 stem_idx = self.split(leaf_idx, split_dim, parent_idx, is_left_child);
-                let node = self.stems.get_unchecked_mut(stem_idx.az::<usize>());
+                let node = self.stems.index_unchecked_mut(stem_idx.az::<usize>());
 
-                leaf_idx = (if *query.get_unchecked(split_dim) < node.split_val {
+                leaf_idx = (if *query.index_unchecked(split_dim) < node.split_val {
                     node.left
                 } else {
                     node.right
                 } - IDX::leaf_offset());
 
-                leaf_node = self.leaves.get_unchecked_mut(leaf_idx.az::<usize>());
+                leaf_node = self.leaves.index_unchecked_mut(leaf_idx.az::<usize>());
             }
 
             *leaf_node
                 .content_points
-                .get_unchecked_mut(leaf_node.size.az::<usize>()) = *query;
+                .index_unchecked_mut(leaf_node.size.az::<usize>()) = *query;
             *leaf_node
                 .content_items
-                .get_unchecked_mut(leaf_node.size.az::<usize>()) = item;
+                .index_unchecked_mut(leaf_node.size.az::<usize>()) = item;
 
             leaf_node.size = leaf_node.size + IDX::one();
         }
         self.size = self.size + T::one();
     }
 
+    /// Adds an item to the tree, unless the tree has run out of space.
+    ///
+    /// Behaves the same as [`add`](KdTree::add), but leaves the tree unchanged and returns
+    /// [`CapacityExceeded`] if adding the point would require splitting a leaf, but the
+    /// tree already has as many stems or leaves as `IDX` can index. `add` does not check
+    /// for this in release builds, and would corrupt the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.try_add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100), Ok(()));
+    ///
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn try_add(&mut self, query: &[A; K], item: T) -> Result<(), CapacityExceeded> {
+        if !self.has_capacity_to_split() && self.add_would_split(query) {
+            return Err(CapacityExceeded);
+        }
+
+        self.add(query, item);
+
+        Ok(())
+    }
+
     /// Removes an item from the tree.
     ///
     /// The first argument specifies co-ordinates of the point where the item is located.
@@ -110,32 +162,158 @@ where
     /// ```
     #[inline]
     pub fn remove(&mut self, query: &[A; K], item: T) -> usize {
-        let mut stem_idx = self.root_index;
-        let mut split_dim = 0;
         let mut removed: usize = 0;
 
-        while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
-            let Some(stem_node) = self.stems.get_mut(stem_idx.az::<usize>()) else {
-                return removed;
-            };
+        // points equal to a split value can end up on either side of it when
+        // a leaf gets split, so both sides need checking in that case.
+        let mut to_visit = vec![(self.root_index, 0)];
+        while let Some((mut stem_idx, mut split_dim)) = to_visit.pop() {
+            while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
+                let Some(stem_node) = self.stems.get(stem_idx.az::<usize>()) else {
+                    return removed;
+                };
+                let next_split_dim = (split_dim + 1).rem(K);
 
-            stem_idx = if query[split_dim] <= stem_node.split_val {
-                stem_node.left
-            } else {
-                stem_node.right
-            };
+                if query[split_dim] == stem_node.split_val {
+                    to_visit.push((stem_node.right, next_split_dim));
+                }
 
-            split_dim = (split_dim + 1).rem(K);
+                stem_idx = if query[split_dim] <= stem_node.split_val {
+                    stem_node.left
+                } else {
+                    stem_node.right
+                };
+
+                split_dim = next_split_dim;
+            }
+
+            let leaf_idx = stem_idx - IDX::leaf_offset();
+
+            if let Some(leaf_node) = self.leaves.get_mut(leaf_idx.az::<usize>()) {
+                let mut p_index = 0;
+                while p_index < leaf_node.size.az::<usize>() {
+                    if &leaf_node.content_points[p_index] == query
+                        && leaf_node.content_items[p_index] == item
+                    {
+                        leaf_node.content_points[p_index] =
+                            leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
+                        leaf_node.content_items[p_index] =
+                            leaf_node.content_items[leaf_node.size.az::<usize>() - 1];
+
+                        self.size -= T::one();
+                        removed += 1;
+                        leaf_node.size = leaf_node.size - IDX::one();
+                    } else {
+                        p_index += 1;
+                    }
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Changes the item stored at `query` from `old` to `new`, without moving it.
+    ///
+    /// This is cheaper than removing `old` and adding `new`, as the shape of the tree is
+    /// not changed. It is useful when the items are indices into another collection whose
+    /// entries have moved, for example after compacting it. If `old` is stored at `query`
+    /// more than once, only one of its entries is changed. Returns `false`, leaving the
+    /// tree unchanged, if `old` is not stored at `query`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// let point = [FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)];
+    /// tree.add(&point, 100);
+    /// tree.add(&point, 101);
+    ///
+    /// assert!(tree.replace_item(&point, 100, 200));
+    /// assert!(tree.contains(&point, 200));
+    /// assert!(tree.contains(&point, 101));
+    /// assert!(!tree.replace_item(&point, 100, 200));
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn replace_item(&mut self, query: &[A; K], old: T, new: T) -> bool {
+        // points equal to a split value can end up on either side of it when
+        // a leaf gets split, so both sides need checking in that case.
+        let mut to_visit = vec![(self.root_index, 0)];
+        while let Some((mut node_idx, mut split_dim)) = to_visit.pop() {
+            while KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+                let stem_node = &self.stems[node_idx.az::<usize>()];
+                let next_split_dim = (split_dim + 1).rem(K);
+
+                if query[split_dim] == stem_node.split_val {
+                    to_visit.push((stem_node.right, next_split_dim));
+                }
+
+                node_idx = if query[split_dim] <= stem_node.split_val {
+                    stem_node.left
+                } else {
+                    stem_node.right
+                };
+
+                split_dim = next_split_dim;
+            }
+
+            let leaf_node = &mut self.leaves[(node_idx - IDX::leaf_offset()).az::<usize>()];
+            let size = leaf_node.size.az::<usize>();
+
+            if let Some((_, stored_item)) = leaf_node.content_points[..size]
+                .iter()
+                .zip(leaf_node.content_items[..size].iter_mut())
+                .find(|(point, stored_item)| *point == query && **stored_item == old)
+            {
+                *stored_item = new;
+                return true;
+            }
         }
 
-        let leaf_idx = stem_idx - IDX::leaf_offset();
+        false
+    }
+
+    /// Removes every entry for an item from the tree, wherever it is located.
+    ///
+    /// Unlike [`remove`](KdTree::remove), the co-ordinates of the item do not need to
+    /// be known. This visits every leaf in the tree, and so is O(n) rather than
+    /// O(log n). Returns the number of entries removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 100);
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 200);
+    /// assert_eq!(tree.size(), 3);
+    ///
+    /// assert_eq!(tree.remove_by_item(100), 2);
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_by_item(&mut self, item: T) -> usize {
+        let mut removed: usize = 0;
 
-        if let Some(mut leaf_node) = self.leaves.get_mut(leaf_idx.az::<usize>()) {
+        for leaf_node in self.leaves.iter_mut() {
             let mut p_index = 0;
             while p_index < leaf_node.size.az::<usize>() {
-                if &leaf_node.content_points[p_index] == query
-                    && leaf_node.content_items[p_index] == item
-                {
+                if leaf_node.content_items[p_index] == item {
                     leaf_node.content_points[p_index] =
                         leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
                     leaf_node.content_items[p_index] =
@@ -153,6 +331,118 @@ where
         removed
     }
 
+    /// Retains only the entries for which `pred` returns `true`, removing all others.
+    ///
+    /// `pred` is called once for every entry in the tree, with its point and item. This
+    /// visits every leaf in the tree, and so is O(n), but is much faster than removing
+    /// lots of entries one at a time. The shape of the tree is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    /// tree.add(&[FXD::from_num(3), FXD::from_num(4), FXD::from_num(7)], 102);
+    ///
+    /// tree.retain(|_, item| item % 2 == 0);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn retain<P>(&mut self, pred: P)
+    where
+        P: Fn(&[A; K], T) -> bool,
+    {
+        for leaf_node in self.leaves.iter_mut() {
+            let mut p_index = 0;
+            while p_index < leaf_node.size.az::<usize>() {
+                if !pred(
+                    &leaf_node.content_points[p_index],
+                    leaf_node.content_items[p_index],
+                ) {
+                    // the last entry is swapped into this slot, so p_index is not advanced
+                    // in order for that entry to be checked too
+                    leaf_node.content_points[p_index] =
+                        leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
+                    leaf_node.content_items[p_index] =
+                        leaf_node.content_items[leaf_node.size.az::<usize>() - 1];
+
+                    self.size -= T::one();
+                    leaf_node.size = leaf_node.size - IDX::one();
+                } else {
+                    p_index += 1;
+                }
+            }
+        }
+    }
+
+    /// Moves everything stored in `other` into this tree.
+    ///
+    /// Each entry in `other` is added to this tree in turn with [`add`](KdTree::add), so
+    /// this takes O(m log n) time when merging a tree of size m into one of size n.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    ///
+    /// let mut other: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    /// other.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    ///
+    /// tree.merge(other);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn merge(&mut self, other: KdTree<A, T, K, B, IDX>) {
+        other
+            .iter()
+            .for_each(|(point, &item)| self.add(point, item));
+    }
+
+    fn add_would_split(&self, query: &[A; K]) -> bool {
+        let mut node_idx = self.root_index;
+        let mut split_dim = 0;
+        let tie_seed = self.stems.len();
+        let mut depth = 0;
+
+        while KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+            let stem_node = &self.stems[node_idx.az::<usize>()];
+            let query_val = query[split_dim];
+            node_idx = if query_val < stem_node.split_val
+                || (query_val == stem_node.split_val && tie_goes_left(tie_seed, depth))
+            {
+                stem_node.left
+            } else {
+                stem_node.right
+            };
+            split_dim = (split_dim + 1).rem(K);
+            depth += 1;
+        }
+
+        self.leaves[(node_idx - IDX::leaf_offset()).az::<usize>()].size == B.az::<IDX>()
+    }
+
+    fn has_capacity_to_split(&self) -> bool {
+        self.stems.len() < IDX::leaf_offset().az::<usize>()
+            && self.leaves.len() <= (<IDX as Index>::max() - IDX::leaf_offset()).az::<usize>()
+    }
+
     unsafe fn split(
         &mut self,
         leaf_idx: IDX,
@@ -160,7 +450,16 @@ where
         parent_idx: IDX,
         was_parents_left: bool,
     ) -> IDX {
-        let orig = self.leaves.get_unchecked_mut(leaf_idx.az::<usize>());
+        debug_assert!(
+            self.has_capacity_to_split(),
+            "tree has too many nodes to be indexed by its IDX type"
+        );
+
+        let orig = self.leaves.index_unchecked_mut(leaf_idx.az::<usize>());
+
+        // always split into equal halves, even if every point has the same value along
+        // split_dim and so ends up on both sides of split_val, so that splitting
+        // makes room in the leaf whatever the data
         let pivot_idx: IDX = (B / 2).az::<IDX>();
 
         mirror_select_nth_unstable_by(
@@ -168,79 +467,79 @@ where
             &mut orig.content_items,
             pivot_idx.az::<usize>(),
             |a, b| unsafe {
-                a.get_unchecked(split_dim)
-                    .partial_cmp(b.get_unchecked(split_dim))
+                a.index_unchecked(split_dim)
+                    .partial_cmp(b.index_unchecked(split_dim))
                     .expect("Leaf node sort failed.")
             },
         );
 
         let split_val = *orig
             .content_points
-            .get_unchecked(pivot_idx.az::<usize>())
-            .get_unchecked(split_dim);
+            .index_unchecked(pivot_idx.az::<usize>())
+            .index_unchecked(split_dim);
 
         let mut left = LeafNode::new();
         let mut right = LeafNode::new();
 
         if B.rem(2) == 1 {
             left.content_points
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
+                .index_unchecked_mut(..(pivot_idx.az::<usize>()))
                 .copy_from_slice(
                     orig.content_points
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
+                        .index_unchecked(..(pivot_idx.az::<usize>())),
                 );
             left.content_items
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
+                .index_unchecked_mut(..(pivot_idx.az::<usize>()))
                 .copy_from_slice(
                     orig.content_items
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
+                        .index_unchecked(..(pivot_idx.az::<usize>())),
                 );
             left.size = pivot_idx;
 
             right
                 .content_points
-                .get_unchecked_mut(..((pivot_idx + IDX::one()).az::<usize>()))
+                .index_unchecked_mut(..((pivot_idx + IDX::one()).az::<usize>()))
                 .copy_from_slice(
                     orig.content_points
-                        .get_unchecked((pivot_idx.az::<usize>())..),
+                        .index_unchecked((pivot_idx.az::<usize>())..),
                 );
             right
                 .content_items
-                .get_unchecked_mut(..((pivot_idx + IDX::one()).az::<usize>()))
+                .index_unchecked_mut(..((pivot_idx + IDX::one()).az::<usize>()))
                 .copy_from_slice(
                     orig.content_items
-                        .get_unchecked((pivot_idx.az::<usize>())..),
+                        .index_unchecked((pivot_idx.az::<usize>())..),
                 );
 
             right.size = (B.az::<IDX>()) - pivot_idx;
         } else {
             left.content_points
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
+                .index_unchecked_mut(..(pivot_idx.az::<usize>()))
                 .copy_from_slice(
                     orig.content_points
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
+                        .index_unchecked(..(pivot_idx.az::<usize>())),
                 );
             left.content_items
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
+                .index_unchecked_mut(..(pivot_idx.az::<usize>()))
                 .copy_from_slice(
                     orig.content_items
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
+                        .index_unchecked(..(pivot_idx.az::<usize>())),
                 );
             left.size = pivot_idx;
 
             right
                 .content_points
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
+                .index_unchecked_mut(..(pivot_idx.az::<usize>()))
                 .copy_from_slice(
                     orig.content_points
-                        .get_unchecked((pivot_idx.az::<usize>())..),
+                        .index_unchecked((pivot_idx.az::<usize>())..),
                 );
             right
                 .content_items
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
+                .index_unchecked_mut(..(pivot_idx.az::<usize>()))
                 .copy_from_slice(
                     orig.content_items
-                        .get_unchecked((pivot_idx.az::<usize>())..),
+                        .index_unchecked((pivot_idx.az::<usize>())..),
                 );
 
             right.size = (B.az::<IDX>()) - pivot_idx;
@@ -257,7 +556,7 @@ where
         let new_stem_index: IDX = (self.stems.len().az::<IDX>()) - IDX::one();
 
         if parent_idx != <IDX as Index>::max() {
-            let parent_node = self.stems.get_unchecked_mut(parent_idx.az::<usize>());
+            let parent_node = self.stems.index_unchecked_mut(parent_idx.az::<usize>());
             if was_parents_left {
                 parent_node.left = new_stem_index;
             } else {
@@ -277,6 +576,7 @@ mod tests {
     use fixed::FixedU16;
 
     use crate::fixed::kdtree::KdTree;
+    use rand::Rng;
 
     type FXD = FixedU16<U16>;
 
@@ -296,6 +596,35 @@ mod tests {
         assert_eq!(tree.size(), 1);
     }
 
+    #[test]
+    fn try_add_returns_an_error_once_idx_runs_out_of_nodes() {
+        use crate::error::CapacityExceeded;
+        use crate::fixed::distance::squared_euclidean;
+
+        let mut tree: KdTree<FXD, u32, 2, 4, u8> = KdTree::new();
+        let mut rng = rand::thread_rng();
+
+        let mut added = Vec::new();
+        let err = loop {
+            let point = [n(rng.gen_range(0.0..1.0)), n(rng.gen_range(0.0..1.0))];
+            let item = added.len() as u32;
+            match tree.try_add(&point, item) {
+                Ok(()) => added.push((point, item)),
+                Err(err) => break err,
+            }
+        };
+
+        assert_eq!(err, CapacityExceeded);
+        assert_eq!(tree.size(), added.len() as u32);
+        assert_eq!(tree.stem_count(), 127);
+
+        // everything that was added can still be found
+        for (point, item) in &added {
+            assert_eq!(tree.nearest_one(point, &squared_euclidean).0, n(0.0));
+            assert!(tree.contains(point, *item));
+        }
+    }
+
     #[test]
     fn can_add_enough_items_to_cause_a_split() {
         let mut tree: KdTree<FXD, u32, 4, 4, u32> = KdTree::new();
@@ -360,4 +689,207 @@ mod tests {
         assert_eq!(removed, 1);
         assert_eq!(tree.size(), 15);
     }
+
+    #[test]
+    fn removes_every_copy_of_a_repeated_entry() {
+        let mut tree: KdTree<FXD, u32, 2, 32, u32> = KdTree::new();
+        let point = [n(0.5f32), n(0.5f32)];
+
+        tree.add(&[n(0.1f32), n(0.2f32)], 1);
+        for _ in 0..3 {
+            tree.add(&point, 2);
+        }
+        tree.add(&[n(0.9f32), n(0.8f32)], 3);
+
+        assert_eq!(tree.remove(&point, 2), 3);
+        assert_eq!(tree.size(), 2);
+        assert!(!tree.contains(&point, 2));
+        assert_eq!(tree.remove(&point, 2), 0);
+    }
+
+    #[test]
+    fn removes_copies_of_an_entry_that_were_split_across_leaves() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+        let point = [n(0.5f32), n(0.5f32)];
+
+        let mut rng = rand::thread_rng();
+        for item in 0..100 {
+            tree.add(
+                &[n(rng.gen_range(0.0..0.99)), n(rng.gen_range(0.0..0.99))],
+                item,
+            );
+            tree.add(&point, 1000);
+        }
+
+        assert_eq!(tree.remove(&point, 1000), 100);
+        assert_eq!(tree.size(), 100);
+        assert!(!tree.contains(&point, 1000));
+    }
+
+    #[test]
+    fn replaces_items_that_share_a_point_one_at_a_time() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+        let point = [n(0.5f32), n(0.5f32)];
+
+        let mut rng = rand::thread_rng();
+        for item in 0..100 {
+            tree.add(
+                &[n(rng.gen_range(0.0..0.99)), n(rng.gen_range(0.0..0.99))],
+                item,
+            );
+            tree.add(&point, 1000 + item);
+        }
+
+        for item in 0..100 {
+            assert!(tree.replace_item(&point, 1000 + item, 2000 + item));
+            assert!(!tree.replace_item(&point, 1000 + item, 2000 + item));
+        }
+        assert!(!tree.replace_item(&point, 0, 1));
+        assert_eq!(tree.size(), 200);
+
+        let mut items = tree.get(&point);
+        items.sort();
+        assert_eq!(items, (2000..2100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn can_remove_an_item_by_item_alone() {
+        let mut tree: KdTree<FXD, u32, 4, 4, u32> = KdTree::new();
+
+        let mut rng = rand::thread_rng();
+        for i in 0..100 {
+            let point = [
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+            ];
+
+            tree.add(&point, i % 10);
+        }
+
+        assert_eq!(tree.size(), 100);
+
+        let removed = tree.remove_by_item(3);
+
+        assert_eq!(removed, 10);
+        assert_eq!(tree.size(), 90);
+        assert_eq!(tree.remove_by_item(3), 0);
+        assert_eq!(tree.size(), 90);
+        assert!(tree
+            .leaves
+            .iter()
+            .all(|leaf| leaf.content_items[..leaf.size as usize]
+                .iter()
+                .all(|&item| item != 3)));
+    }
+
+    #[test]
+    fn can_retain_only_matching_items() {
+        let mut tree: KdTree<FXD, u32, 4, 4, u32> = KdTree::new();
+
+        let mut rng = rand::thread_rng();
+        for i in 0..100 {
+            let point = [
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+            ];
+
+            tree.add(&point, i);
+        }
+
+        tree.retain(|_, item| item % 2 == 0);
+
+        assert_eq!(tree.size(), 50);
+
+        let mut items: Vec<u32> = tree.iter().map(|(_, &item)| item).collect();
+        items.sort();
+        assert_eq!(items, (0..100).step_by(2).collect::<Vec<_>>());
+
+        tree.retain(|_, _| false);
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.iter().count(), 0);
+    }
+
+    #[test]
+    fn can_merge_two_trees() {
+        use crate::fixed::distance::manhattan;
+
+        const TREE_SIZE: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([FXD; 2], u32)> = (0..TREE_SIZE * 2)
+            .map(|item| {
+                (
+                    [
+                        n(rng.gen_range(0f32..0.99998f32)),
+                        n(rng.gen_range(0f32..0.99998f32)),
+                    ],
+                    item as u32,
+                )
+            })
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 2, 8, u32> = KdTree::new();
+        let mut other: KdTree<FXD, u32, 2, 8, u32> = KdTree::new();
+        let mut expected: KdTree<FXD, u32, 2, 8, u32> = KdTree::new();
+        for (idx, (point, item)) in content_to_add.iter().enumerate() {
+            if idx < TREE_SIZE {
+                tree.add(point, *item);
+            } else {
+                other.add(point, *item);
+            }
+            expected.add(point, *item);
+        }
+
+        tree.merge(other);
+
+        assert_eq!(tree.size(), (TREE_SIZE * 2) as u32);
+
+        for _ in 0..100 {
+            let query_point = [
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+            ];
+
+            assert_eq!(
+                tree.nearest_one(&query_point, &manhattan).0,
+                expected.nearest_one(&query_point, &manhattan).0
+            );
+        }
+    }
+
+    #[test]
+    fn can_add_many_points_with_identical_coordinates() {
+        use crate::fixed::distance::manhattan;
+
+        const NUM_DUPLICATES: u32 = 100_000;
+        let point: [FXD; 3] = [n(0.1), n(0.2), n(0.3)];
+
+        let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+        for item in 0..NUM_DUPLICATES {
+            tree.add(&point, item);
+        }
+        tree.add(&[n(0.5), n(0.5), n(0.5)], NUM_DUPLICATES);
+
+        assert_eq!(tree.size(), NUM_DUPLICATES + 1);
+        // a balanced tree with half-full leaves would be around 13 levels deep, whereas
+        // a chain of stems would be thousands of levels deep
+        assert!(tree.height() <= 40, "height was {}", tree.height());
+        assert_eq!(tree.nearest_one(&point, &manhattan), (FXD::ZERO, 0));
+        assert_eq!(
+            tree.nearest_one(&[n(0.5), n(0.5), n(0.5)], &manhattan),
+            (FXD::ZERO, NUM_DUPLICATES)
+        );
+
+        let mut found: Vec<u32> = tree
+            .within(&point, n(0.01), &manhattan)
+            .into_iter()
+            .map(|neighbour| neighbour.item)
+            .collect();
+        found.sort();
+        assert_eq!(found, (0..NUM_DUPLICATES).collect::<Vec<_>>());
+    }
 }
@@ -7,10 +7,64 @@
 
 use crate::fixed::kdtree::Axis;
 
-/// Returns the squared euclidean distance between two points. When you only
-/// need to compare distances, rather than having the exact distance between
-/// the points, this metric is beneficial because it avoids the expensive square
-/// root computation.
+/// Implemented by distance metrics that can be used to query the tree.
+///
+/// As well as measuring the distance between two points, a metric needs to be
+/// able to provide a lower bound on the distance from the query point to anything
+/// on the far side of a split plane, so that queries can skip the parts of the tree
+/// that cannot contain anything closer than what has already been found. This bound
+/// is built up one axis at a time, by [`combine`](DistanceMetric::combine)-ing the
+/// [`axis_dist`](DistanceMetric::axis_dist) of each split plane crossed into a
+/// running total. The default implementations saturate rather than overflow.
+///
+/// Any function or closure with the signature `Fn(&[A; K], &[A; K]) -> A`, such as
+/// [`squared_euclidean`], is a `DistanceMetric` that bounds each axis using the
+/// squared distance along it. This is only correct for metrics that behave like squared
+/// euclidean distance. Other metrics, such as [`Chebyshev`], implement this trait
+/// themselves and should be passed to queries instead of their distance function.
+pub trait DistanceMetric<A: Axis, const K: usize> {
+    /// Returns the distance between two points.
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A;
+
+    /// Returns the contribution made to the distance between two points by the difference
+    /// between `a` and `b` along axis `dim` alone.
+    fn axis_dist(&self, a: A, b: A, dim: usize) -> A;
+
+    /// Adds the contribution of one axis, as returned by `axis_dist`, to a running
+    /// distance bound.
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd.saturating_add(axis_dist)
+    }
+
+    /// Removes the contribution of one axis, previously added by `combine`, from a
+    /// running distance bound.
+    #[inline]
+    fn uncombine(&self, rd: A, axis_dist: A) -> A {
+        rd.saturating_sub(axis_dist)
+    }
+}
+
+impl<A: Axis, const K: usize, F> DistanceMetric<A, K> for F
+where
+    F: Fn(&[A; K], &[A; K]) -> A,
+{
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        self(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        let diff = a.dist(b);
+        diff.saturating_mul(diff)
+    }
+}
+
+/// Returns the Manhattan / "taxi cab" distance between two points.
+///
+/// When querying the tree, pass [`Manhattan`] rather than this function so that
+/// the parts of the tree that get skipped are determined correctly.
 ///
 /// # Examples
 ///
@@ -41,6 +95,22 @@ pub fn manhattan<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
         .fold(A::ZERO, |a, b| a.saturating_add(b))
 }
 
+/// Manhattan / "taxi cab" distance metric, for use in queries. See [`manhattan`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Manhattan;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for Manhattan {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        manhattan(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        a.dist(b)
+    }
+}
+
 /// Returns the squared euclidean distance between two points.
 ///
 /// Faster than Euclidean distance due to not needing a square root, but still
@@ -96,3 +166,79 @@ pub fn squared_euclidean<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
             //res
         })
 }
+
+/// Squared euclidean distance metric, for use in queries. See [`squared_euclidean`].
+///
+/// Behaves identically to passing `&squared_euclidean` itself.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SquaredEuclidean;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for SquaredEuclidean {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        squared_euclidean(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        let diff = a.dist(b);
+        diff.saturating_mul(diff)
+    }
+}
+
+/// Returns the Chebyshev / L-infinity distance between two points.
+///
+/// This is the largest absolute difference between the two points along any single axis.
+/// When querying the tree, pass [`Chebyshev`] rather than this function so that
+/// the parts of the tree that get skipped are determined correctly.
+///
+/// # Examples
+///
+/// ```rust
+/// use fixed::types::extra::U0;
+/// use fixed::FixedU16;
+/// use kiddo::fixed::distance::chebyshev;
+/// type FXD = FixedU16<U0>;
+///
+/// let ZERO = FXD::from_num(0);
+/// let ONE = FXD::from_num(1);
+/// let TWO = FXD::from_num(2);
+///
+/// assert_eq!(chebyshev(&[ZERO, ZERO], &[ZERO, ZERO]), ZERO);
+/// assert_eq!(chebyshev(&[ZERO, ZERO], &[ONE, ZERO]), ONE);
+/// assert_eq!(chebyshev(&[ZERO, TWO], &[ONE, ZERO]), TWO);
+/// ```
+pub fn chebyshev<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&a_val, &b_val)| a_val.dist(b_val))
+        .fold(A::ZERO, |a, b| a.max(b))
+}
+
+/// Chebyshev / L-infinity distance metric, for use in queries. See [`chebyshev`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Chebyshev;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for Chebyshev {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        chebyshev(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        a.dist(b)
+    }
+
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd.max(axis_dist)
+    }
+
+    /// The bound for an axis only ever grows as the query descends further from the
+    /// query point, so the largest contribution seen so far is always still valid.
+    #[inline]
+    fn uncombine(&self, rd: A, _axis_dist: A) -> A {
+        rd
+    }
+}
@@ -1,12 +1,15 @@
 //! Fixed point k-d tree, for use when the co-ordinates of the points being stored in the tree
 //! are fixed point or integers. `u8`, `u16`, `u32`, and `u64` based fixed-point / integers are supported
 //! via the Fixed crate, eg `FixedU16<U14>` for a 16-bit fixed point number with 14 bits after the
-//! decimal point.
+//! decimal point. `i32` and `i64` integers can also be used directly, along with the distance
+//! metrics in `kiddo::int::distance`.
 
+use alloc::vec::Vec;
 use az::{Az, Cast};
 use fixed::traits::Fixed;
-use std::cmp::PartialEq;
-use std::fmt::Debug;
+use core::cmp::PartialEq;
+use core::fmt::Debug;
+use core::ops::{Add, Mul, Sub};
 use divrem::DivCeil;
 
 #[cfg(feature = "serialize")]
@@ -17,10 +20,121 @@ use serde::{Deserialize, Serialize};
 
 /// Axis trait represents the traits that must be implemented
 /// by the type that is used as the first generic parameter, `A`,
-/// on `KdTree`. A type from the `Fixed` crate will implement
-/// all of the traits required by Axis. For example `FixedU16<U14>`.
-pub trait Axis: Fixed + Default + Debug + Copy + Sync {}
-impl<T: Fixed + Default + Debug + Copy + Sync> Axis for T {}
+/// on `KdTree`. It is implemented for every type from the `Fixed` crate,
+/// for example `FixedU16<U14>`, and for the signed integers `i32` and `i64`.
+///
+/// Before version 3, `Fixed` was a supertrait of `Axis`. Generic code that relies on the
+/// methods of `Fixed` now needs to ask for them with a bound of `A: Axis + Fixed`.
+///
+/// # Examples
+///
+/// ```rust
+/// use fixed::traits::Fixed;
+/// use fixed::types::extra::U14;
+/// use fixed::FixedU16;
+/// use kiddo::fixed::kdtree::Axis;
+///
+/// fn to_f64<A: Axis + Fixed>(val: A) -> f64 {
+///     val.to_num()
+/// }
+///
+/// assert_eq!(to_f64(FixedU16::<U14>::from_num(1.5)), 1.5);
+/// ```
+pub trait Axis:
+    Ord + Default + Debug + Copy + Sync + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    /// Zero.
+    const ZERO: Self;
+    /// The largest value of the type.
+    const MAX: Self;
+
+    /// Returns the absolute difference between `self` and `other`, saturating at
+    /// [`MAX`](Axis::MAX) if it does not fit in the type.
+    fn dist(self, other: Self) -> Self;
+
+    /// Adds `other` to `self`, saturating at the bounds of the type.
+    fn saturating_add(self, other: Self) -> Self;
+
+    /// Subtracts `other` from `self`, saturating at the bounds of the type.
+    fn saturating_sub(self, other: Self) -> Self;
+
+    /// Multiplies `self` by `other`, saturating at the bounds of the type.
+    fn saturating_mul(self, other: Self) -> Self;
+}
+
+macro_rules! impl_axis_for_fixed {
+    ($($fixed:ident)*) => {
+        $(
+            impl<Frac> Axis for fixed::$fixed<Frac>
+            where
+                Self: Fixed + Sync,
+            {
+                const ZERO: Self = Self::ZERO;
+                const MAX: Self = Self::MAX;
+
+                #[inline]
+                fn dist(self, other: Self) -> Self {
+                    Fixed::dist(self, other)
+                }
+
+                #[inline]
+                fn saturating_add(self, other: Self) -> Self {
+                    Fixed::saturating_add(self, other)
+                }
+
+                #[inline]
+                fn saturating_sub(self, other: Self) -> Self {
+                    Fixed::saturating_sub(self, other)
+                }
+
+                #[inline]
+                fn saturating_mul(self, other: Self) -> Self {
+                    Fixed::saturating_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_axis_for_fixed! {
+    FixedU8 FixedU16 FixedU32 FixedU64 FixedU128
+    FixedI8 FixedI16 FixedI32 FixedI64 FixedI128
+}
+
+macro_rules! impl_axis_for_int {
+    ($($int:ty)*) => {
+        $(
+            impl Axis for $int {
+                const ZERO: Self = 0;
+                const MAX: Self = <$int>::MAX;
+
+                #[inline]
+                fn dist(self, other: Self) -> Self {
+                    Self::try_from(self.abs_diff(other)).unwrap_or(Self::MAX)
+                }
+
+                #[inline]
+                fn saturating_add(self, other: Self) -> Self {
+                    <$int>::saturating_add(self, other)
+                }
+
+                #[inline]
+                fn saturating_sub(self, other: Self) -> Self {
+                    <$int>::saturating_sub(self, other)
+                }
+
+                #[inline]
+                fn saturating_mul(self, other: Self) -> Self {
+                    <$int>::saturating_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_axis_for_int! {
+    i32 i64
+}
 
 /// Rkyv-serializable equivalent of `kiddo::fixed::kdtree::Axis`
 #[cfg(feature = "serialize_rkyv")]
@@ -59,8 +173,47 @@ pub struct KdTreeRK<
 /// are fixed point or integers. `u8`, `u16`, `u32`, and `u64` based fixed-point / integers are supported
 /// via the Fixed crate, eg `FixedU16<U14>` for a 16-bit fixed point number with 14 bits after the
 /// decimal point.
+///
+/// # Sharing between threads
+///
+/// Queries only need a shared reference to the tree, and never modify it, so a tree
+/// whose axis and item types are `Send` and `Sync` is itself `Send` and `Sync`. Once
+/// it has been built, it can be shared between threads, for example in an
+/// [`Arc`](alloc::sync::Arc), and queried from all of them at once without any locking.
+/// Modifying the tree needs a `&mut` reference, so the borrow checker ensures that
+/// no queries are running while it is being changed.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::thread;
+/// use fixed::FixedU16;
+/// use fixed::types::extra::U0;
+/// use kiddo::fixed::kdtree::KdTree;
+/// use kiddo::fixed::distance::squared_euclidean;
+///
+/// type FXD = FixedU16<U0>;
+///
+/// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+/// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+/// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+///
+/// let tree = Arc::new(tree);
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|i| {
+///         let tree = Arc::clone(&tree);
+///         thread::spawn(move || {
+///             tree.nearest_one(&[FXD::from_num(i), FXD::from_num(2), FXD::from_num(5)], &squared_euclidean)
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     assert!(handle.join().unwrap().1 >= 100);
+/// }
+/// ```
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct KdTree<A: Copy + Default, T: Copy + Default, const K: usize, const B: usize, IDX> {
     pub(crate) leaves: Vec<LeafNode<A, T, K, B, IDX>>,
     pub(crate) stems: Vec<StemNode<A, K, IDX>>,
@@ -182,7 +335,13 @@ where
         KdTree::with_capacity(B * 10)
     }
 
-    /// Creates a new fixed-point/integer KdTree and reserves capacity for a specific number of items.
+    /// Creates a new fixed-point/integer KdTree with room for `num_points` points to be
+    /// added without it needing to reallocate.
+    ///
+    /// `num_points` is the number of points that the tree is expected to hold, not the
+    /// number of leaves. Since leaves are split at the median as points are added, each
+    /// one ends up between half full and full, so as with [`reserve`](KdTree::reserve),
+    /// room is made for `2 * num_points / B` leaves and stems.
     ///
     /// # Examples
     ///
@@ -195,16 +354,23 @@ where
     ///
     /// assert_eq!(tree.size(), 0);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bucket size `B` is less than 2, as a leaf holding a single point
+    /// cannot be split in two.
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
-        assert!(capacity <= <IDX as Index>::capacity_with_bucket_size(B));
+    pub fn with_capacity(num_points: usize) -> Self {
+        assert!(B >= 2, "bucket size B must be at least 2");
+        assert!(num_points <= <IDX as Index>::capacity_with_bucket_size(B));
         let mut tree = Self {
             size: T::zero(),
-            stems: Vec::with_capacity(capacity.max(1).ilog2() as usize),
-            leaves: Vec::with_capacity(DivCeil::div_ceil(capacity, B.az::<usize>())),
+            stems: Vec::new(),
+            leaves: Vec::new(),
             root_index: <IDX as Index>::leaf_offset(),
         };
 
+        tree.reserve(num_points);
         tree.leaves.push(LeafNode::new());
 
         tree
@@ -233,17 +399,526 @@ where
         self.size
     }
 
+    /// Returns the current number of elements stored in the tree, as a `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    ///
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize
+    where
+        T: Cast<usize>,
+    {
+        self.size.az::<usize>()
+    }
+
+    /// Returns `true` if there are no elements stored in the tree
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    /// assert!(tree.is_empty());
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// assert!(!tree.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size == T::zero()
+    }
+
+    /// Removes everything from the tree, keeping the memory that has been allocated
+    /// for it so that it can be reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    ///
+    /// tree.clear();
+    ///
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.stems.clear();
+        self.leaves.clear();
+        self.leaves.push(LeafNode::new());
+        self.root_index = <IDX as Index>::leaf_offset();
+        self.size = T::zero();
+    }
+
+    /// Reserves capacity for at least roughly `additional` more items to be added to the
+    /// tree without it needing to reallocate.
+    ///
+    /// When a leaf is split, each half gets about `B / 2` of its items, so up to
+    /// `2 * additional / B` new leaves and stems are reserved. This is an estimate: if many
+    /// of the tree's leaves are already full, or the points are added in an order that
+    /// leaves the new leaves less than half full, more may be needed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.reserve(100_000);
+    ///
+    /// let leaf_size = KdTree::<FXD, u32, 3, 32, u32>::leaf_node_memory_usage();
+    ///
+    /// assert!(tree.memory_usage() >= 100_000 / 32 * leaf_size);
+    /// ```
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let new_leaves = DivCeil::div_ceil(additional * 2, B.max(2));
+        self.leaves.reserve(new_leaves);
+        self.stems.reserve(new_leaves);
+    }
+
+    /// Releases any memory allocated for stems and leaves beyond what the tree is
+    /// currently using.
+    ///
+    /// Leaves that have been emptied by removals are kept, as are stems, so this does not
+    /// help a tree that has had most of its items removed as much as it could. Building a
+    /// new tree from the remaining items fills every leaf again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::with_capacity(100_000);
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    ///
+    /// let before = tree.memory_usage();
+    /// tree.shrink_to_fit();
+    ///
+    /// assert!(tree.memory_usage() < before);
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.stems.shrink_to_fit();
+        self.leaves.shrink_to_fit();
+    }
+
+    /// Returns an iterator over every point stored in the tree, alongside its item.
+    ///
+    /// Every entry is visited exactly once, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    ///
+    /// let mut items: Vec<_> = tree.iter().map(|(_, &item)| item).collect();
+    /// items.sort();
+    ///
+    /// assert_eq!(items, vec![100, 101]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&[A; K], &T)> + '_ {
+        self.leaves.iter().flat_map(|leaf_node| {
+            let size = leaf_node.size.az::<usize>();
+            leaf_node.content_points[..size]
+                .iter()
+                .zip(leaf_node.content_items[..size].iter())
+        })
+    }
+
+    /// Removes every entry from the tree, returning them as an iterator of `(point, item)`
+    /// pairs in no particular order.
+    ///
+    /// The tree is emptied as soon as this is called, even if the iterator is dropped
+    /// before all of the entries have been consumed. As with [`clear`](Self::clear), the
+    /// memory allocated for the tree is kept so that it can be reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::types::extra::U0;
+    /// use fixed::FixedU16;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    ///
+    /// let mut items: Vec<_> = tree.drain().map(|(_, item)| item).collect();
+    /// items.sort();
+    ///
+    /// assert_eq!(items, vec![100, 101]);
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    pub fn drain(&mut self) -> impl Iterator<Item = ([A; K], T)> {
+        let entries: Vec<([A; K], T)> = self.iter().map(|(&point, &item)| (point, item)).collect();
+        self.clear();
+
+        entries.into_iter()
+    }
+
+    /// Returns the smallest axis-aligned box that contains every point in the tree, as
+    /// `(min, max)` corners, or `None` if the tree is empty.
+    ///
+    /// This visits every stored point, and so is O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.bounds(), None);
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(3), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(2), FXD::from_num(6)], 101);
+    ///
+    /// assert_eq!(
+    ///     tree.bounds(),
+    ///     Some((
+    ///         [FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)],
+    ///         [FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)]
+    ///     ))
+    /// );
+    /// ```
+    pub fn bounds(&self) -> Option<([A; K], [A; K])> {
+        let mut points = self.iter().map(|(point, _)| point);
+        let first = *points.next()?;
+
+        Some(points.fold((first, first), |(mut min, mut max), point| {
+            for dim in 0..K {
+                min[dim] = min[dim].min(point[dim]);
+                max[dim] = max[dim].max(point[dim]);
+            }
+            (min, max)
+        }))
+    }
+
+    /// Returns the height of the tree: the number of stems on the longest path from
+    /// the root to a leaf.
+    ///
+    /// A tree whose items all fit into a single leaf has a height of zero. Trees built by
+    /// adding points in sorted order can end up much taller than the `log2` of their leaf
+    /// count, which makes queries slower.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    ///
+    /// assert_eq!(tree.height(), 0);
+    /// ```
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height_from(self.root_index)
+    }
+
+    /// Returns the number of leaf nodes in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    ///
+    /// assert_eq!(tree.leaf_count(), 1);
+    /// ```
+    #[inline]
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns the number of stem nodes in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    ///
+    /// assert_eq!(tree.stem_count(), 0);
+    /// ```
+    #[inline]
+    pub fn stem_count(&self) -> usize {
+        self.stems.len()
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by the tree's nodes.
+    ///
+    /// This reflects the capacity that has been allocated for stems and leaves rather than
+    /// the number of items stored, so it includes space that is reserved but not yet in
+    /// use. Leaves are usually the dominant cost, as each one reserves space for `B`
+    /// points and items: see [`leaf_node_memory_usage`](KdTree::leaf_node_memory_usage).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::with_capacity(1_000_000);
+    ///
+    /// let leaf_size = KdTree::<FXD, u32, 3, 32, u32>::leaf_node_memory_usage();
+    ///
+    /// assert!(tree.memory_usage() >= 1_000_000 / 32 * leaf_size);
+    /// ```
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        self.stems.capacity() * core::mem::size_of::<StemNode<A, K, IDX>>()
+            + self.leaves.capacity() * Self::leaf_node_memory_usage()
+    }
+
+    /// Returns the number of bytes of memory used by each leaf node.
+    ///
+    /// Every leaf uses this much memory no matter how many items it holds, so this can
+    /// be used to gauge the effect of changing the bucket size `B`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let small_leaf = KdTree::<FXD, u16, 3, 16, u16>::leaf_node_memory_usage();
+    /// let large_leaf = KdTree::<FXD, u32, 3, 32, u32>::leaf_node_memory_usage();
+    ///
+    /// assert!(small_leaf < large_leaf);
+    /// ```
+    #[inline]
+    pub fn leaf_node_memory_usage() -> usize {
+        core::mem::size_of::<LeafNode<A, T, K, B, IDX>>()
+    }
+
+    fn height_from(&self, node_idx: IDX) -> usize {
+        if !Self::is_stem_index(node_idx) {
+            return 0;
+        }
+
+        let node = &self.stems[node_idx.az::<usize>()];
+        1 + self
+            .height_from(node.left)
+            .max(self.height_from(node.right))
+    }
+
     pub(crate) fn is_stem_index(x: IDX) -> bool {
         x < <IDX as Index>::leaf_offset()
     }
 }
 
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    FromIterator<([A; K], T)> for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Creates a tree containing the `(point, item)` pairs yielded by `iter`.
+    fn from_iter<I: IntoIterator<Item = ([A; K], T)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut tree = KdTree::with_capacity(iter.size_hint().0);
+
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Extend<([A; K], T)>
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Adds each of the `(point, item)` pairs yielded by `iter` to the tree.
+    fn extend<I: IntoIterator<Item = ([A; K], T)>>(&mut self, iter: I) {
+        iter.into_iter()
+            .for_each(|(point, item)| self.add(&point, item));
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> IntoIterator
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    type Item = ([A; K], T);
+    type IntoIter = IntoIter<A, T, K, B, IDX>;
+
+    /// Consumes the tree, returning an iterator that moves every `(point, item)` pair
+    /// out of it, in no particular order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            leaves: self.leaves.into_iter(),
+            leaf: None,
+            idx: 0,
+        }
+    }
+}
+
+/// An iterator that moves every entry out of a [`KdTree`], in no particular order.
+///
+/// Returned by the [`into_iter`](IntoIterator::into_iter) method of `KdTree`, which
+/// allows a tree to be consumed with a `for` loop.
+///
+/// # Examples
+///
+/// ```rust
+/// use fixed::FixedU16;
+/// use fixed::types::extra::U0;
+/// use kiddo::fixed::kdtree::KdTree;
+///
+/// type FXD = FixedU16<U0>;
+///
+/// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+///
+/// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+/// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+///
+/// let mut items = Vec::new();
+/// for (_, item) in tree {
+///     items.push(item);
+/// }
+/// items.sort();
+///
+/// assert_eq!(items, vec![100, 101]);
+/// ```
+pub struct IntoIter<A: Copy + Default, T: Copy + Default, const K: usize, const B: usize, IDX> {
+    leaves: alloc::vec::IntoIter<LeafNode<A, T, K, B, IDX>>,
+    leaf: Option<LeafNode<A, T, K, B, IDX>>,
+    idx: usize,
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Iterator
+    for IntoIter<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    type Item = ([A; K], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf) = &self.leaf {
+                if self.idx < leaf.size.az::<usize>() {
+                    let entry = (leaf.content_points[self.idx], leaf.content_items[self.idx]);
+                    self.idx += 1;
+
+                    return Some(entry);
+                }
+            }
+
+            self.leaf = Some(self.leaves.next()?);
+            self.idx = 0;
+        }
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Debug
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Summarises the shape of the tree, rather than listing every node, which would
+    /// produce an unmanageable amount of output for large trees.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KdTree")
+            .field("size", &self.size)
+            .field("leaf_count", &self.leaf_count())
+            .field("stem_count", &self.stem_count())
+            .field("height", &self.height())
+            .field("K", &K)
+            .field("B", &B)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use fixed::types::extra::U14;
     use fixed::FixedU16;
 
     use crate::fixed::kdtree::KdTree;
+    use crate::test_utils::rand_data_fixed_u16_point;
 
     type FXD = FixedU16<U14>;
 
@@ -268,6 +943,265 @@ mod tests {
         assert_eq!(tree.size(), 0);
     }
 
+    #[test]
+    fn it_reports_its_height_and_node_counts() {
+        let mut tree: KdTree<FXD, u32, 1, 4, u32> = KdTree::new();
+
+        assert_eq!(
+            (tree.height(), tree.leaf_count(), tree.stem_count()),
+            (0, 1, 0)
+        );
+
+        for i in 0..4 {
+            tree.add(&[FXD::from_num(i as f32 / 10.0)], i);
+        }
+        assert_eq!(
+            (tree.height(), tree.leaf_count(), tree.stem_count()),
+            (0, 1, 0)
+        );
+
+        tree.add(&[FXD::from_num(0.4)], 4);
+        assert_eq!(
+            (tree.height(), tree.leaf_count(), tree.stem_count()),
+            (1, 2, 1)
+        );
+    }
+
+    #[test]
+    fn it_estimates_its_memory_usage_from_its_capacity() {
+        type Tree = KdTree<FXD, u32, 4, 32, u32>;
+
+        let small = Tree::with_capacity(100);
+        let large = Tree::with_capacity(100_000);
+
+        assert!(Tree::leaf_node_memory_usage() >= 32 * 4 * core::mem::size_of::<FXD>());
+        assert!(small.memory_usage() >= small.leaves.capacity() * Tree::leaf_node_memory_usage());
+        assert!(large.memory_usage() > small.memory_usage());
+    }
+
+    #[test]
+    fn with_capacity_avoids_reallocation_when_adding_that_many_points() {
+        const TREE_SIZE: usize = 10_000;
+
+        let mut tree: KdTree<FXD, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+
+        let stems_capacity = tree.stems.capacity();
+        let leaves_capacity = tree.leaves.capacity();
+
+        for item in 0..TREE_SIZE {
+            tree.add(&rand_data_fixed_u16_point::<U14, 4>(), item as u32);
+        }
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert_eq!(tree.stems.capacity(), stems_capacity);
+        assert_eq!(tree.leaves.capacity(), leaves_capacity);
+    }
+
+    #[test]
+    fn reserving_space_avoids_reallocation_when_adding() {
+        const TREE_SIZE: usize = 10_000;
+
+        let mut tree: KdTree<FXD, u32, 4, 32, u32> = KdTree::new();
+        tree.reserve(TREE_SIZE);
+
+        let stems_capacity = tree.stems.capacity();
+        let leaves_capacity = tree.leaves.capacity();
+
+        for item in 0..TREE_SIZE {
+            tree.add(&rand_data_fixed_u16_point::<U14, 4>(), item as u32);
+        }
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert_eq!(tree.stems.capacity(), stems_capacity);
+        assert_eq!(tree.leaves.capacity(), leaves_capacity);
+    }
+
+    #[test]
+    fn can_be_queried_from_many_threads_at_once() {
+        use crate::fixed::distance::chebyshev;
+        use std::sync::Arc;
+        use std::thread;
+
+        const TREE_SIZE: usize = 10_000;
+        const NUM_THREADS: usize = 4;
+        const NUM_QUERIES: usize = 1000;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<KdTree<FXD, u32, 4, 32, u32>>();
+
+        let mut tree: KdTree<FXD, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand_data_fixed_u16_point::<U14, 4>(), item as u32);
+        }
+
+        let query_points: Arc<Vec<[FXD; 4]>> = Arc::new(
+            (0..NUM_QUERIES)
+                .map(|_| rand_data_fixed_u16_point::<U14, 4>())
+                .collect(),
+        );
+
+        let expected: Vec<(FXD, u32)> = query_points
+            .iter()
+            .map(|query_point| tree.nearest_one(query_point, &chebyshev))
+            .collect();
+
+        let tree = Arc::new(tree);
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let tree = Arc::clone(&tree);
+                let query_points = Arc::clone(&query_points);
+                thread::spawn(move || {
+                    query_points
+                        .iter()
+                        .map(|query_point| tree.nearest_one(query_point, &chebyshev))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn a_clone_is_unaffected_by_changes_to_the_original() {
+        use crate::fixed::distance::manhattan;
+
+        let content_to_add: Vec<([FXD; 2], u32)> = (0..1000)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 2>(), i))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 2, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let snapshot = tree.clone();
+        assert_eq!(snapshot, tree);
+
+        content_to_add[..500].iter().for_each(|(point, item)| {
+            tree.remove(point, *item);
+        });
+
+        assert!(tree.size() < 1000);
+        assert_eq!(snapshot.size(), 1000);
+
+        for (point, _) in &content_to_add {
+            assert_eq!(snapshot.nearest_one(point, &manhattan).0, FXD::ZERO);
+        }
+    }
+
+    #[test]
+    fn it_can_be_collected_from_an_iterator_and_extended() {
+        let content_to_add: Vec<([FXD; 4], u32)> = (0..1000)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 4>(), i))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 4, 32, u32> =
+            content_to_add[..500].iter().copied().collect();
+        assert_eq!(tree.size(), 500);
+
+        tree.extend(content_to_add[500..].iter().copied());
+        assert_eq!(tree.size(), 1000);
+
+        let mut items: Vec<u32> = tree.iter().map(|(_, &item)| item).collect();
+        items.sort();
+        assert_eq!(items, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn can_iterate_over_every_stored_item() {
+        let content_to_add: Vec<([FXD; 4], u32)> = (0..1000)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 4>(), i))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 4, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let mut result: Vec<_> = tree.iter().map(|(&point, &item)| (point, item)).collect();
+        result.sort_by_key(|&(_, item)| item);
+
+        assert_eq!(result, content_to_add);
+    }
+
+    #[test]
+    fn can_be_consumed_into_an_iterator() {
+        let content_to_add: Vec<([FXD; 4], u32)> = (0..1000)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 4>(), i))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 4, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+        content_to_add[..500].iter().for_each(|(point, content)| {
+            tree.remove(point, *content);
+        });
+
+        let mut result: Vec<([FXD; 4], u32)> = Vec::new();
+        for (point, item) in tree {
+            result.push((point, item));
+        }
+        result.sort_by_key(|&(_, item)| item);
+
+        assert_eq!(result, content_to_add[500..]);
+    }
+
+    #[test]
+    fn can_be_cleared_and_reused() {
+        use crate::fixed::distance::squared_euclidean;
+
+        let mut tree: KdTree<FXD, u32, 4, 8, u32> = KdTree::new();
+        for i in 0..1000 {
+            tree.add(&rand_data_fixed_u16_point::<U14, 4>(), i);
+        }
+        let leaves_capacity = tree.leaves.capacity();
+
+        tree.clear();
+
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.leaves.capacity(), leaves_capacity);
+        assert_eq!(tree.iter().count(), 0);
+
+        let low = [FXD::from_num(0.1); 4];
+        let high = [FXD::from_num(0.9); 4];
+        tree.add(&low, 1);
+        tree.add(&high, 2);
+
+        assert_eq!(tree.size(), 2);
+        assert_eq!(
+            tree.nearest_one(&[FXD::from_num(0.8); 4], &squared_euclidean).1,
+            2
+        );
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn can_round_trip_through_bincode() {
+        use crate::fixed::distance::manhattan;
+
+        let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+        for i in 0..1000 {
+            tree.add(&rand_data_fixed_u16_point::<U14, 3>(), i);
+        }
+
+        let serialized = bincode::serialize(&tree).unwrap();
+        let deserialized: KdTree<FXD, u32, 3, 32, u32> =
+            bincode::deserialize(&serialized).unwrap();
+
+        assert_eq!(deserialized, tree);
+        for _ in 0..100 {
+            let query_point = rand_data_fixed_u16_point::<U14, 3>();
+            assert_eq!(
+                deserialized.nearest_one(&query_point, &manhattan),
+                tree.nearest_one(&query_point, &manhattan)
+            );
+        }
+    }
+
     // #[cfg(feature = "serialize")]
     // #[test]
     // fn can_serde() {
@@ -303,4 +1237,103 @@ mod tests {
     //     let deserialized: KdTree = serde_json::from_str(&serialized).unwrap();
     //     assert_eq!(tree, deserialized);
     // }
+
+    #[test]
+    fn drain_returns_every_entry_and_empties_the_tree() {
+        let content_to_add: Vec<([FXD; 2], u32)> = (0..1000)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 2>(), i))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 2, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let mut drained: Vec<([FXD; 2], u32)> = tree.drain().collect();
+        drained.sort_by_key(|&(_, item)| item);
+
+        assert_eq!(drained, content_to_add);
+        assert_eq!(tree.size(), 0);
+        assert_eq!((tree.leaf_count(), tree.stem_count()), (1, 0));
+
+        tree.add(&content_to_add[0].0, 0);
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn bounds_are_the_min_and_max_of_every_point() {
+        let mut tree: KdTree<FXD, u32, 3, 8, u32> = KdTree::new();
+        assert_eq!(tree.bounds(), None);
+
+        let content_to_add: Vec<([FXD; 3], u32)> = (0..1000)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 3>(), i))
+            .collect();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let mut expected_min = content_to_add[0].0;
+        let mut expected_max = content_to_add[0].0;
+        for (point, _) in &content_to_add {
+            for dim in 0..3 {
+                if point[dim] < expected_min[dim] {
+                    expected_min[dim] = point[dim];
+                }
+                if point[dim] > expected_max[dim] {
+                    expected_max[dim] = point[dim];
+                }
+            }
+        }
+
+        assert_eq!(tree.bounds(), Some((expected_min, expected_max)));
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket size B must be at least 2")]
+    fn it_cannot_be_constructed_with_a_bucket_size_of_one() {
+        let _tree: KdTree<FXD, u32, 2, 1, u32> = KdTree::new();
+    }
+
+    #[test]
+    fn it_can_hold_many_collinear_points_with_a_bucket_size_of_two() {
+        let mut tree: KdTree<FXD, u32, 2, 2, u32> = KdTree::new();
+
+        for i in 0..1000 {
+            tree.add(&[FXD::from_num(i as f32 / 1000.0); 2], i);
+        }
+        for i in 1000..1010 {
+            tree.add(&[FXD::from_num(0.5); 2], i);
+        }
+
+        assert_eq!(tree.size(), 1010);
+        assert_eq!(tree.iter().count(), 1010);
+    }
+
+    #[test]
+    fn shrinking_releases_unused_capacity() {
+        let mut tree: KdTree<FXD, u32, 2, 8, u32> = KdTree::with_capacity(100_000);
+        for i in 0..100 {
+            tree.add(&rand_data_fixed_u16_point::<U14, 2>(), i);
+        }
+
+        let before = tree.memory_usage();
+        tree.shrink_to_fit();
+
+        assert!(tree.memory_usage() < before);
+        assert_eq!(tree.size(), 100);
+        assert_eq!(tree.iter().count(), 100);
+    }
+
+    #[test]
+    fn debug_output_summarises_the_tree() {
+        let mut tree: KdTree<FXD, u32, 1, 4, u32> = KdTree::new();
+        for i in 0..5 {
+            tree.add(&[FXD::from_num(i as f32 / 10.0)], i);
+        }
+
+        assert_eq!(
+            format!("{tree:?}"),
+            "KdTree { size: 5, leaf_count: 2, stem_count: 1, height: 1, K: 1, B: 4 }"
+        );
+    }
 }
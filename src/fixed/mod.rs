@@ -1,7 +1,8 @@
 //! Fixed point k-d tree, for use when the co-ordinates of the points being stored in the tree
 //! are fixed point or integers. [`u8`], [`u16`], [`u32`], and [`u64`] based fixed-point / integers are supported
 //! via the Fixed crate, eg [`FixedU16<U14>`](fixed::FixedU16<U14>) for a 16-bit fixed point number with 14 bits after the
-//! decimal point.
+//! decimal point. [`i32`] and [`i64`] integers can also be used directly, along with the distance
+//! metrics in [`int::distance`](crate::int::distance).
 
 #[doc(hidden)]
 pub mod construction;
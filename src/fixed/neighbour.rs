@@ -1,7 +1,7 @@
 //! A result item returned by a query
 use crate::fixed::kdtree::Axis;
 use crate::types::Content;
-use std::cmp::Ordering;
+use core::cmp::Ordering;
 
 /// Represents an entry in the results of a query, with `distance` being the distance of this
 /// particular item from the query point, and `item` being the stored item index that was found
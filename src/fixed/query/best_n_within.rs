@@ -1,9 +1,11 @@
+use alloc::collections::BinaryHeap;
 use az::{Az, Cast};
-use std::collections::BinaryHeap;
-use std::ops::Rem;
+use core::ops::Rem;
 
+use crate::fixed::distance::DistanceMetric;
 use crate::fixed::kdtree::{Axis, KdTree, LeafNode};
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -37,15 +39,15 @@ where
     /// assert_eq!(first, 1);
     /// ```
     #[inline]
-    pub fn best_n_within<F>(
+    pub fn best_n_within<D>(
         &self,
         query: &[A; K],
         dist: A,
         max_qty: usize,
-        distance_fn: &F,
+        distance_fn: &D,
     ) -> impl Iterator<Item = T>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::ZERO; K];
         let mut best_items: BinaryHeap<T> = BinaryHeap::new();
@@ -68,29 +70,29 @@ where
     }
 
     #[allow(clippy::too_many_arguments)]
-    unsafe fn best_n_within_recurse<F>(
+    unsafe fn best_n_within_recurse<D>(
         &self,
         query: &[A; K],
         radius: A,
         max_qty: usize,
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
         split_dim: usize,
         best_items: &mut BinaryHeap<T>,
         off: &mut [A; K],
         rd: A,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = unsafe { self.stems.get_unchecked(curr_node_idx.az::<usize>()) };
+            let node = unsafe { self.stems.index_unchecked(curr_node_idx.az::<usize>()) };
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim].dist(node.split_val);
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
@@ -109,11 +111,7 @@ where
                 rd,
             );
 
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd.saturating_add(
-                (new_off.saturating_mul(new_off)).saturating_sub(old_off.saturating_mul(old_off)),
-            );
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
 
             if rd <= radius {
                 off[split_dim] = new_off;
@@ -133,28 +131,28 @@ where
         } else {
             let leaf_node = unsafe {
                 self.leaves
-                    .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>())
+                    .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>())
             };
 
             Self::process_leaf_node(query, radius, max_qty, distance_fn, best_items, leaf_node);
         }
     }
 
-    fn process_leaf_node<F>(
+    fn process_leaf_node<D>(
         query: &[A; K],
         radius: A,
         max_qty: usize,
-        distance_fn: &F,
+        distance_fn: &D,
         best_items: &mut BinaryHeap<T>,
         leaf_node: &LeafNode<A, T, K, B, IDX>,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         leaf_node
             .content_points
             .iter()
             .take(leaf_node.size.az::<usize>())
-            .map(|entry| distance_fn(query, entry))
+            .map(|entry| distance_fn.dist(query, entry))
             .enumerate()
             .filter(|(_, distance)| *distance <= radius)
             .for_each(|(idx, _)| unsafe {
@@ -168,7 +166,7 @@ where
         leaf_node: &LeafNode<A, T, K, B, IDX>,
         idx: usize,
     ) {
-        let item = *leaf_node.content_items.get_unchecked(idx.az::<usize>());
+        let item = *leaf_node.content_items.index_unchecked(idx.az::<usize>());
         if best_items.len() < max_qty {
             best_items.push(item);
         } else {
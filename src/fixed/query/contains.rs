@@ -0,0 +1,117 @@
+use crate::fixed::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+use az::{Az, Cast};
+use core::ops::Rem;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Returns `true` if `item` is stored in the tree at exactly the point `query`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 101);
+    ///
+    /// assert!(tree.contains(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100));
+    /// assert!(!tree.contains(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 102));
+    /// assert!(!tree.contains(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(6)], 100));
+    /// ```
+    #[inline]
+    pub fn contains(&self, query: &[A; K], item: T) -> bool {
+        unsafe { self.contains_recurse(query, item, self.root_index, 0) }
+    }
+
+    unsafe fn contains_recurse(
+        &self,
+        query: &[A; K],
+        item: T,
+        curr_node_idx: IDX,
+        split_dim: usize,
+    ) -> bool {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let next_split_dim = (split_dim + 1).rem(K);
+            let query_val = *query.index_unchecked(split_dim);
+
+            // points equal to the split value can end up on either side of it
+            // when a leaf gets split, so both sides need checking in that case.
+            if query_val == node.split_val {
+                self.contains_recurse(query, item, node.left, next_split_dim)
+                    || self.contains_recurse(query, item, node.right, next_split_dim)
+            } else if query_val < node.split_val {
+                self.contains_recurse(query, item, node.left, next_split_dim)
+            } else {
+                self.contains_recurse(query, item, node.right, next_split_dim)
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+            let size = leaf_node.size.az::<usize>();
+
+            leaf_node.content_points[..size]
+                .iter()
+                .zip(leaf_node.content_items[..size].iter())
+                .any(|(point, &stored_item)| point == query && stored_item == item)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed::kdtree::KdTree;
+    use crate::test_utils::rand_data_fixed_u16_point;
+    use fixed::types::extra::U14;
+    use fixed::FixedU16;
+
+    type FXD = FixedU16<U14>;
+
+    fn n(num: f32) -> FXD {
+        FXD::from_num(num)
+    }
+
+    #[test]
+    fn can_check_whether_an_item_is_contained() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        let content_to_add: Vec<([FXD; 2], u32)> = (0..100)
+            .map(|i| (rand_data_fixed_u16_point::<U14, 2>(), i))
+            .collect();
+        for (point, item) in content_to_add.iter() {
+            tree.add(point, *item);
+        }
+
+        for (point, item) in content_to_add.iter() {
+            assert!(tree.contains(point, *item));
+            assert!(!tree.contains(point, *item + 1000));
+        }
+        assert!(!tree.contains(&[n(3.5), n(3.5)], 1));
+    }
+
+    #[test]
+    fn can_check_whether_one_of_several_items_sharing_a_point_is_contained() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        for item in 0..20 {
+            tree.add(&[n(0.5), n(0.5)], item);
+        }
+        tree.remove_by_item(7);
+
+        for item in 0..20 {
+            assert_eq!(tree.contains(&[n(0.5), n(0.5)], item), item != 7);
+        }
+    }
+}
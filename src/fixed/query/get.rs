@@ -0,0 +1,117 @@
+use alloc::vec::Vec;
+use az::{Az, Cast};
+use core::ops::Rem;
+
+use crate::fixed::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Returns every item stored in the tree at exactly the point `query`, in no
+    /// particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 101);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 102);
+    ///
+    /// let mut items = tree.get(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)]);
+    /// items.sort();
+    ///
+    /// assert_eq!(items, vec![100, 101]);
+    /// assert!(tree.get(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(6)]).is_empty());
+    /// ```
+    #[inline]
+    pub fn get(&self, query: &[A; K]) -> Vec<T> {
+        let mut items = Vec::new();
+
+        unsafe { self.get_recurse(query, self.root_index, 0, &mut items) }
+
+        items
+    }
+
+    unsafe fn get_recurse(
+        &self,
+        query: &[A; K],
+        curr_node_idx: IDX,
+        split_dim: usize,
+        items: &mut Vec<T>,
+    ) {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let next_split_dim = (split_dim + 1).rem(K);
+            let query_val = *query.index_unchecked(split_dim);
+
+            // points equal to the split value can end up on either side of it
+            // when a leaf gets split, so both sides need checking in that case.
+            if query_val <= node.split_val {
+                self.get_recurse(query, node.left, next_split_dim, items);
+            }
+            if query_val >= node.split_val {
+                self.get_recurse(query, node.right, next_split_dim, items);
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+            let size = leaf_node.size.az::<usize>();
+
+            items.extend(
+                leaf_node.content_points[..size]
+                    .iter()
+                    .zip(leaf_node.content_items[..size].iter())
+                    .filter(|(point, _)| *point == query)
+                    .map(|(_, &item)| item),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed::kdtree::KdTree;
+    use fixed::types::extra::U14;
+    use fixed::FixedU16;
+
+    type FXD = FixedU16<U14>;
+
+    fn n(num: f32) -> FXD {
+        FXD::from_num(num)
+    }
+
+    #[test]
+    fn can_get_every_item_sharing_a_point() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        for item in 0..100 {
+            tree.add(&[n((item % 10) as f32 / 10.0), n(0.0)], item);
+        }
+        tree.remove_by_item(13);
+
+        for x in 0..10 {
+            let mut items = tree.get(&[n(x as f32 / 10.0), n(0.0)]);
+            items.sort();
+
+            let expected: Vec<u32> = (0..10)
+                .map(|i| i * 10 + x)
+                .filter(|&item| item != 13)
+                .collect();
+            assert_eq!(items, expected);
+        }
+        assert!(tree.get(&[n(0.55), n(0.0)]).is_empty());
+    }
+}
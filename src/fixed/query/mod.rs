@@ -1,5 +1,9 @@
 pub mod best_n_within;
+pub mod contains;
+pub mod get;
 pub mod nearest_n;
+pub mod nearest_n_within;
 pub mod nearest_one;
 pub mod within;
+pub mod within_bbox;
 pub mod within_unsorted;
@@ -1,9 +1,12 @@
+use crate::fixed::distance::DistanceMetric;
 use crate::fixed::kdtree::{Axis, KdTree};
 use crate::fixed::neighbour::Neighbour;
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
 use az::{Az, Cast};
-use std::collections::BinaryHeap;
-use std::ops::Rem;
+use core::ops::Rem;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -35,9 +38,9 @@ where
     /// assert_eq!(nearest[0].item, 100);
     /// ```
     #[inline]
-    pub fn nearest_n<F>(&self, query: &[A; K], qty: usize, distance_fn: &F) -> Vec<Neighbour<A, T>>
+    pub fn nearest_n<D>(&self, query: &[A; K], qty: usize, distance_fn: &D) -> Vec<Neighbour<A, T>>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::ZERO; K];
         let mut result: BinaryHeap<Neighbour<A, T>> = BinaryHeap::with_capacity(qty);
@@ -57,27 +60,27 @@ where
         result.into_sorted_vec()
     }
 
-    unsafe fn nearest_n_recurse<F>(
+    unsafe fn nearest_n_recurse<D>(
         &self,
         query: &[A; K],
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
         split_dim: usize,
         results: &mut BinaryHeap<Neighbour<A, T>>,
         off: &mut [A; K],
         rd: A,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = &self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim].dist(node.split_val);
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
@@ -94,11 +97,7 @@ where
                 rd,
             );
 
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd.saturating_add(
-                (new_off.saturating_mul(new_off)).saturating_sub(old_off.saturating_mul(old_off)),
-            );
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
 
             if Self::dist_belongs_in_heap(rd, results) {
                 off[split_dim] = new_off;
@@ -116,7 +115,7 @@ where
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
 
             leaf_node
                 .content_points
@@ -124,9 +123,9 @@ where
                 .take(leaf_node.size.az::<usize>())
                 .enumerate()
                 .for_each(|(idx, entry)| {
-                    let distance: A = distance_fn(query, entry);
+                    let distance: A = distance_fn.dist(query, entry);
                     if Self::dist_belongs_in_heap(distance, results) {
-                        let item = unsafe { *leaf_node.content_items.get_unchecked(idx) };
+                        let item = unsafe { *leaf_node.content_items.index_unchecked(idx) };
                         let element = Neighbour { distance, item };
                         if results.len() < results.capacity() {
                             results.push(element)
@@ -0,0 +1,273 @@
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use az::{Az, Cast};
+use core::ops::Rem;
+
+use crate::fixed::distance::DistanceMetric;
+use crate::fixed::kdtree::{Axis, KdTree};
+use crate::fixed::neighbour::Neighbour;
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Finds up to `max_qty` elements within `dist` of `query`, using the specified
+    /// distance metric function.
+    ///
+    /// This behaves like `nearest_n`, except that any item further than `dist` from
+    /// `query` is excluded, so fewer than `max_qty` results are returned if there are
+    /// not enough items within range. The radius is inclusive, as with `within`.
+    ///
+    /// Results are returned sorted nearest-first. Unlike `best_n_within`, which keeps
+    /// the items with the lowest values, this keeps the items closest to `query`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    /// use kiddo::fixed::distance::squared_euclidean;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(6)], 102);
+    /// tree.add(&[FXD::from_num(20), FXD::from_num(30), FXD::from_num(60)], 103);
+    ///
+    /// let nearest = tree.nearest_n_within(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], FXD::from_num(10), 2, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest.len(), 2);
+    /// assert_eq!(nearest[0].item, 100);
+    /// assert_eq!(nearest[1].item, 102);
+    /// ```
+    #[inline]
+    pub fn nearest_n_within<D>(
+        &self,
+        query: &[A; K],
+        dist: A,
+        max_qty: usize,
+        distance_fn: &D,
+    ) -> Vec<Neighbour<A, T>>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::ZERO; K];
+        let mut result: BinaryHeap<Neighbour<A, T>> = BinaryHeap::with_capacity(max_qty);
+
+        if max_qty > 0 {
+            unsafe {
+                self.nearest_n_within_recurse(
+                    query,
+                    dist,
+                    max_qty,
+                    distance_fn,
+                    self.root_index,
+                    0,
+                    &mut result,
+                    &mut off,
+                    A::ZERO,
+                )
+            }
+        }
+
+        result.into_sorted_vec()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_n_within_recurse<D>(
+        &self,
+        query: &[A; K],
+        radius: A,
+        max_qty: usize,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        split_dim: usize,
+        results: &mut BinaryHeap<Neighbour<A, T>>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+            let next_split_dim = (split_dim + 1).rem(K);
+
+            self.nearest_n_within_recurse(
+                query,
+                radius,
+                max_qty,
+                distance_fn,
+                closer_node_idx,
+                next_split_dim,
+                results,
+                off,
+                rd,
+            );
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+
+            if rd <= radius && Self::dist_belongs_in_capped_heap(rd, max_qty, results) {
+                off[split_dim] = new_off;
+                self.nearest_n_within_recurse(
+                    query,
+                    radius,
+                    max_qty,
+                    distance_fn,
+                    further_node_idx,
+                    next_split_dim,
+                    results,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .take(leaf_node.size.az::<usize>())
+                .enumerate()
+                .for_each(|(idx, entry)| {
+                    let distance: A = distance_fn.dist(query, entry);
+                    if distance <= radius
+                        && Self::dist_belongs_in_capped_heap(distance, max_qty, results)
+                    {
+                        let item = *leaf_node.content_items.index_unchecked(idx);
+                        let element = Neighbour { distance, item };
+                        if results.len() < max_qty {
+                            results.push(element)
+                        } else {
+                            let mut top = results.peek_mut().unwrap();
+                            if element.distance < top.distance {
+                                *top = element;
+                            }
+                        }
+                    }
+                });
+        }
+    }
+
+    fn dist_belongs_in_capped_heap(
+        dist: A,
+        max_qty: usize,
+        heap: &BinaryHeap<Neighbour<A, T>>,
+    ) -> bool {
+        heap.len() < max_qty || dist < heap.peek().unwrap().distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed::distance::{manhattan, Manhattan};
+    use crate::fixed::kdtree::KdTree;
+    use crate::test_utils::rand_data_fixed_u16_point;
+    use fixed::types::extra::U14;
+    use fixed::FixedU16;
+    use rand::Rng;
+
+    type FXD = FixedU16<U14>;
+
+    fn n(num: f32) -> FXD {
+        FXD::from_num(num)
+    }
+
+    #[test]
+    fn can_query_nearest_n_items_within_radius() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        let content_to_add: [([FXD; 2], u32); 8] = [
+            ([n(0.0), n(0.0)], 0),
+            ([n(0.1), n(0.0)], 1),
+            ([n(0.0), n(0.2)], 2),
+            ([n(0.3), n(0.0)], 3),
+            ([n(0.0), n(0.4)], 4),
+            ([n(0.5), n(0.0)], 5),
+            ([n(0.0), n(0.6)], 6),
+            ([n(0.7), n(0.0)], 7),
+        ];
+
+        for (point, item) in content_to_add {
+            tree.add(&point, item);
+        }
+
+        // plenty of items in range: capped at max_qty, closest first
+        let result: Vec<_> = tree
+            .nearest_n_within(&[n(0.0), n(0.0)], n(1.0), 3, &Manhattan)
+            .into_iter()
+            .map(|n| n.item)
+            .collect();
+        assert_eq!(result, vec![0, 1, 2]);
+
+        // fewer items in range than max_qty: only those are returned
+        let result: Vec<_> = tree
+            .nearest_n_within(&[n(0.0), n(0.0)], n(0.3), 5, &Manhattan)
+            .into_iter()
+            .map(|n| n.item)
+            .collect();
+        assert_eq!(result, vec![0, 1, 2, 3]);
+
+        // nothing in range
+        let result = tree.nearest_n_within(&[n(3.0), n(3.0)], n(0.1), 5, &Manhattan);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn can_query_nearest_n_items_within_radius_large_scale() {
+        const TREE_SIZE: usize = 100_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([FXD; 4], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand_data_fixed_u16_point::<U14, 4>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let radius = n(0.1);
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand_data_fixed_u16_point::<U14, 4>();
+            let max_qty = rng.gen_range(0usize..20);
+
+            let mut expected: Vec<FXD> = content_to_add
+                .iter()
+                .map(|(p, _)| manhattan(&query_point, p))
+                .filter(|dist| *dist <= radius)
+                .collect();
+            expected.sort();
+            expected.truncate(max_qty);
+
+            let result: Vec<_> = tree
+                .nearest_n_within(&query_point, radius, max_qty, &Manhattan)
+                .into_iter()
+                .map(|n| n.distance)
+                .collect();
+
+            assert_eq!(result, expected);
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use az::{Az, Cast};
-use std::ops::Rem;
+use core::ops::Rem;
 
+use crate::fixed::distance::DistanceMetric;
 use crate::fixed::kdtree::{Axis, KdTree, LeafNode};
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -12,9 +14,18 @@ where
     /// Queries the tree to find the nearest element to `query`, using the specified
     /// distance metric function.
     ///
+    /// `distance_fn` can be a plain distance function such as `squared_euclidean`, or any
+    /// other [`DistanceMetric`], such as [`Chebyshev`](crate::fixed::distance::Chebyshev).
+    ///
     /// Faster than querying for nearest_n(point, 1, ...) due
     /// to not needing to allocate memory or maintain sorted results.
     ///
+    /// If more than one item is at exactly the nearest distance to `query`, the smallest
+    /// of those items is returned, regardless of the order that they were added in.
+    ///
+    /// If the tree is empty, `(A::MAX, T::zero())` is returned. Use
+    /// [`try_nearest_one`](Self::try_nearest_one) to get `None` in that case instead.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -36,121 +47,140 @@ where
     /// assert_eq!(nearest.1, 100);
     /// ```
     #[inline]
-    pub fn nearest_one<F>(&self, query: &[A; K], distance_fn: &F) -> (A, T)
+    pub fn nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::ZERO; K];
-        unsafe {
+        let best = unsafe {
             self.nearest_one_recurse(
                 query,
                 distance_fn,
                 self.root_index,
                 0,
-                T::zero(),
-                A::MAX,
+                None,
                 &mut off,
                 A::ZERO,
             )
+        };
+
+        best.unwrap_or((A::MAX, T::zero()))
+    }
+
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function, returning `None` if the tree is empty.
+    ///
+    /// Behaves the same as [`nearest_one`](Self::nearest_one) otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    /// use kiddo::fixed::distance::squared_euclidean;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    /// let query = [FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)];
+    ///
+    /// assert_eq!(tree.try_nearest_one(&query, &squared_euclidean), None);
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    ///
+    /// let nearest = tree.try_nearest_one(&query, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest.map(|(_, item)| item), Some(100));
+    /// ```
+    #[inline]
+    pub fn try_nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> Option<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        if self.size == T::zero() {
+            return None;
         }
+
+        Some(self.nearest_one(query, distance_fn))
     }
 
     #[inline]
-    unsafe fn nearest_one_recurse<F>(
+    unsafe fn nearest_one_recurse<D>(
         &self,
         query: &[A; K],
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
         split_dim: usize,
-        mut best_item: T,
-        mut best_dist: A,
+        mut best: Option<(A, T)>,
         off: &mut [A; K],
         rd: A,
-    ) -> (A, T)
+    ) -> Option<(A, T)>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = &self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim].dist(node.split_val);
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
                 };
             let next_split_dim = (split_dim + 1).rem(K);
 
-            let (dist, item) = self.nearest_one_recurse(
+            best = self.nearest_one_recurse(
                 query,
                 distance_fn,
                 closer_node_idx,
                 next_split_dim,
-                best_item,
-                best_dist,
+                best,
                 off,
                 rd,
             );
 
-            if dist < best_dist {
-                best_dist = dist;
-                best_item = item;
-            }
-
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd.saturating_add(
-                (new_off.saturating_mul(new_off)).saturating_sub(old_off.saturating_mul(old_off)),
-            );
+            // saturating arithmetic only ever underestimates `rd`, and a saturating
+            // distance metric reports at least the saturated `rd` for everything on the
+            // further side, so a saturated `rd` never prunes a branch that holds a nearer
+            // element
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
 
-            if rd <= best_dist {
+            if Self::may_hold_nearer(rd, best) {
                 off[split_dim] = new_off;
-                let (dist, item) = self.nearest_one_recurse(
+                best = self.nearest_one_recurse(
                     query,
                     distance_fn,
                     further_node_idx,
                     next_split_dim,
-                    best_item,
-                    best_dist,
+                    best,
                     off,
                     rd,
                 );
                 off[split_dim] = old_off;
-
-                if dist < best_dist {
-                    best_dist = dist;
-                    best_item = item;
-                }
             }
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
 
-            Self::search_content_for_best(
-                query,
-                distance_fn,
-                &mut best_item,
-                &mut best_dist,
-                leaf_node,
-            );
+            Self::search_content_for_best(query, distance_fn, &mut best, leaf_node);
         }
 
-        (best_dist, best_item)
+        best
     }
 
-    fn search_content_for_best<F>(
+    pub(crate) fn search_content_for_best<D>(
         query: &[A; K],
-        distance_fn: &F,
-        best_item: &mut T,
-        best_dist: &mut A,
+        distance_fn: &D,
+        best: &mut Option<(A, T)>,
         leaf_node: &LeafNode<A, T, K, B, IDX>,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         leaf_node
             .content_points
@@ -158,18 +188,45 @@ where
             .enumerate()
             .take(leaf_node.size.az::<usize>())
             .for_each(|(idx, entry)| {
-                let dist = distance_fn(query, entry);
-                if dist < *best_dist {
-                    *best_dist = dist;
-                    *best_item = unsafe { *leaf_node.content_items.get_unchecked(idx) };
+                let dist = distance_fn.dist(query, entry);
+                let item = unsafe { *leaf_node.content_items.index_unchecked(idx) };
+                if Self::is_nearer(dist, item, *best) {
+                    *best = Some((dist, item));
                 }
             });
     }
+
+    /// Returns `true` if an element at `dist` from the query, with item `item`, should
+    /// replace `best`, the nearest element found so far: if it is nearer, or is equally
+    /// near and has a smaller item.
+    ///
+    /// `None` stands for nothing having been found yet, rather than a sentinel distance
+    /// such as `A::MAX`, which an element can legitimately be at, particularly when
+    /// distances saturate.
+    #[inline]
+    fn is_nearer(dist: A, item: T, best: Option<(A, T)>) -> bool {
+        match best {
+            Some((best_dist, best_item)) => {
+                dist < best_dist || (dist == best_dist && item < best_item)
+            }
+            None => true,
+        }
+    }
+
+    /// Returns `true` if a node whose contents are at least `rd` from the query could
+    /// hold an element that should replace `best`, the nearest element found so far.
+    #[inline]
+    fn may_hold_nearer(rd: A, best: Option<(A, T)>) -> bool {
+        match best {
+            Some((best_dist, _)) => rd <= best_dist,
+            None => true,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::fixed::distance::manhattan;
+    use crate::fixed::distance::{chebyshev, manhattan, Chebyshev};
     use crate::fixed::kdtree::{Axis, KdTree};
     use crate::test_utils::{rand_data_fixed_u16_entry, rand_data_fixed_u16_point};
     use fixed::types::extra::U14;
@@ -212,7 +269,7 @@ mod tests {
         assert_eq!(tree.size(), 16);
 
         let query_point = [n(0.78f32), n(0.55f32), n(0.78f32), n(0.55f32)];
-        let expected = (n(0.86), 7);
+        let expected = (n(0.86), 4);
 
         let result = tree.nearest_one(&query_point, &manhattan);
         assert_eq!(result, expected);
@@ -225,7 +282,7 @@ mod tests {
                 n(rng.gen_range(0f32..1f32)),
                 n(rng.gen_range(0f32..1f32)),
             ];
-            let expected = linear_search(&content_to_add, &query_point);
+            let expected = linear_search(&content_to_add, &query_point, &manhattan);
 
             let result = tree.nearest_one(&query_point, &manhattan);
 
@@ -253,7 +310,7 @@ mod tests {
             .collect();
 
         for query_point in query_points {
-            let expected = linear_search(&content_to_add, &query_point);
+            let expected = linear_search(&content_to_add, &query_point, &manhattan);
 
             let result = tree.nearest_one(&query_point, &manhattan);
 
@@ -261,15 +318,162 @@ mod tests {
         }
     }
 
-    fn linear_search<A: Axis, const K: usize>(
+    #[test]
+    fn can_query_nearest_one_item_with_chebyshev_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1_000;
+
+        let content_to_add: Vec<([FXD; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand_data_fixed_u16_entry::<U14, u32, 4>())
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 4, 4, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let query_points: Vec<[FXD; 4]> = (0..NUM_QUERIES)
+            .map(|_| rand_data_fixed_u16_point::<U14, 4>())
+            .collect();
+
+        for query_point in query_points {
+            let expected = linear_search(&content_to_add, &query_point, &chebyshev);
+
+            let result = tree.nearest_one(&query_point, &Chebyshev);
+
+            assert_eq!(result.0, expected.0);
+        }
+    }
+
+    #[test]
+    fn nearest_one_breaks_ties_in_favour_of_the_smallest_item() {
+        let equidistant: [([FXD; 2], u32); 2] = [([n(0.0), n(0.5)], 7), ([n(1.0), n(0.5)], 3)];
+        let others: Vec<([FXD; 2], u32)> = (0..20)
+            .map(|i| ([n(i as f32 / 20.0), n(2.0 + i as f32 / 20.0)], 100 + i))
+            .collect();
+
+        for order in [[0, 1], [1, 0]] {
+            let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+            for idx in order {
+                tree.add(&equidistant[idx].0, equidistant[idx].1);
+            }
+            others
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+
+            assert_eq!(tree.nearest_one(&[n(0.5), n(0.5)], &manhattan), (n(0.5), 3));
+        }
+    }
+
+    #[test]
+    fn querying_an_empty_tree_finds_nothing() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        assert_eq!(tree.try_nearest_one(&[n(0.5), n(0.5)], &manhattan), None);
+
+        tree.add(&[n(0.0), n(0.0)], 0);
+
+        assert_eq!(
+            tree.try_nearest_one(&[n(0.5), n(0.5)], &manhattan),
+            Some((n(1.0), 0))
+        );
+
+        tree.remove(&[n(0.0), n(0.0)], 0);
+
+        assert_eq!(tree.try_nearest_one(&[n(0.5), n(0.5)], &manhattan), None);
+    }
+
+    #[test]
+    fn finds_elements_whose_distance_saturates_at_the_largest_value() {
+        use crate::int::distance::squared_euclidean;
+
+        let mut tree: KdTree<i64, u32, 2, 4, u32> = KdTree::new();
+        tree.add(&[i64::MAX, i64::MAX], 7);
+        tree.add(&[i64::MAX, 0], 5);
+
+        assert_eq!(
+            tree.nearest_one(&[i64::MIN, i64::MIN], &squared_euclidean),
+            (i64::MAX, 5)
+        );
+
+        tree.add(&[0, 0], 9);
+
+        assert_eq!(
+            tree.nearest_one(&[i64::MIN, i64::MIN], &squared_euclidean),
+            (i64::MAX, 5)
+        );
+        assert_eq!(tree.nearest_one(&[1, 1], &squared_euclidean), (2, 9));
+    }
+
+    #[test]
+    fn finds_the_nearest_when_the_distance_bound_saturates() {
+        use crate::int::distance::{manhattan, squared_euclidean, Manhattan};
+
+        const TREE_SIZE: usize = 1000;
+        const NUM_QUERIES: usize = 100;
+        const SPREAD: i32 = 30_000;
+
+        // half of the points near the largest value and half near the smallest, so that
+        // the bound on the distance to the far half saturates while the distances to the
+        // near half do not
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([i32; 2], u32)> = (0..TREE_SIZE)
+            .map(|item| {
+                let point = if item % 2 == 0 {
+                    [
+                        i32::MAX - rng.gen_range(0..SPREAD),
+                        i32::MAX - rng.gen_range(0..SPREAD),
+                    ]
+                } else {
+                    [
+                        i32::MIN + rng.gen_range(0..SPREAD),
+                        i32::MIN + rng.gen_range(0..SPREAD),
+                    ]
+                };
+                (point, item as u32)
+            })
+            .collect();
+
+        let mut tree: KdTree<i32, u32, 2, 4, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = [
+                i32::MAX - rng.gen_range(0..SPREAD),
+                i32::MAX - rng.gen_range(0..SPREAD),
+            ];
+
+            let expected = content_to_add
+                .iter()
+                .map(|(point, item)| (squared_euclidean(&query_point, point), *item))
+                .min()
+                .unwrap();
+            assert_eq!(tree.nearest_one(&query_point, &squared_euclidean), expected);
+
+            let expected = content_to_add
+                .iter()
+                .map(|(point, item)| (manhattan(&query_point, point), *item))
+                .min()
+                .unwrap();
+            assert_eq!(tree.nearest_one(&query_point, &Manhattan), expected);
+        }
+    }
+
+    fn linear_search<A: Axis, const K: usize, F>(
         content: &[([A; K], u32)],
         query_point: &[A; K],
-    ) -> (A, u32) {
+        distance_fn: &F,
+    ) -> (A, u32)
+    where
+        F: Fn(&[A; K], &[A; K]) -> A,
+    {
         let mut best_dist: A = A::MAX;
         let mut best_item: u32 = u32::MAX;
 
         for &(p, item) in content {
-            let dist = manhattan(query_point, &p);
+            let dist = distance_fn(query_point, &p);
             if dist < best_dist {
                 best_item = item;
                 best_dist = dist;
@@ -1,10 +1,13 @@
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
 use az::{Az, Cast};
-use std::collections::BinaryHeap;
-use std::ops::Rem;
+use core::ops::Rem;
 
+use crate::fixed::distance::DistanceMetric;
 use crate::fixed::kdtree::{Axis, KdTree};
 use crate::fixed::neighbour::Neighbour;
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -14,6 +17,9 @@ where
     /// Finds all elements within `dist` of `query`, using the specified
     /// distance metric function.
     ///
+    /// The radius is inclusive, so a `dist` of zero returns only the items
+    /// stored at exactly the same position as `query`.
+    ///
     /// Results are returned sorted nearest-first
     ///
     /// # Examples
@@ -37,9 +43,9 @@ where
     /// assert_eq!(within.len(), 2);
     /// ```
     #[inline]
-    pub fn within<F>(&self, query: &[A; K], dist: A, distance_fn: &F) -> Vec<Neighbour<A, T>>
+    pub fn within<D>(&self, query: &[A; K], dist: A, distance_fn: &D) -> Vec<Neighbour<A, T>>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::ZERO; K];
         let mut matching_items: BinaryHeap<Neighbour<A, T>> = BinaryHeap::new();
@@ -60,28 +66,28 @@ where
         matching_items.into_sorted_vec()
     }
 
-    unsafe fn within_recurse<F>(
+    unsafe fn within_recurse<D>(
         &self,
         query: &[A; K],
         radius: A,
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
         split_dim: usize,
         matching_items: &mut BinaryHeap<Neighbour<A, T>>,
         off: &mut [A; K],
         rd: A,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim].dist(node.split_val);
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
@@ -99,11 +105,7 @@ where
                 rd,
             );
 
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd.saturating_add(
-                (new_off.saturating_mul(new_off)).saturating_sub(old_off.saturating_mul(old_off)),
-            );
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
 
             if rd <= radius {
                 off[split_dim] = new_off;
@@ -122,7 +124,7 @@ where
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
             // println!("Leaf node: {:?}", (curr_node_idx - LEAF_OFFSET) as usize);
 
             leaf_node
@@ -131,12 +133,12 @@ where
                 .enumerate()
                 .take(leaf_node.size.az::<usize>())
                 .for_each(|(idx, entry)| {
-                    let distance = distance_fn(query, entry);
+                    let distance = distance_fn.dist(query, entry);
 
-                    if distance < radius {
+                    if distance <= radius {
                         matching_items.push(Neighbour {
                             distance,
-                            item: *leaf_node.content_items.get_unchecked(idx.az::<usize>()),
+                            item: *leaf_node.content_items.index_unchecked(idx.az::<usize>()),
                         })
                     }
                 });
@@ -146,7 +148,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::fixed::distance::manhattan;
+    use crate::fixed::distance::{manhattan, squared_euclidean};
     use crate::fixed::kdtree::{Axis, KdTree};
     use crate::test_utils::{rand_data_fixed_u16_entry, rand_data_fixed_u16_point};
     use fixed::types::extra::U14;
@@ -247,15 +249,90 @@ mod tests {
         for query_point in query_points {
             let expected = linear_search(&content_to_add, &query_point, radius);
 
-            let result: Vec<_> = tree
+            let mut result: Vec<_> = tree
                 .within(&query_point, radius, &manhattan)
                 .into_iter()
                 .map(|n| (n.distance, n.item))
                 .collect();
+            stabilize_sort(&mut result);
+
             assert_eq!(result, expected);
         }
     }
 
+    #[test]
+    fn can_query_items_within_radius_matching_linear_search_up_to_max_radius() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 200;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([FXD; 2], u32)> = (0..TREE_SIZE)
+            .map(|_| {
+                (
+                    [n(rng.gen_range(0f32..1f32)), n(rng.gen_range(0f32..1f32))],
+                    rng.gen(),
+                )
+            })
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 2, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for i in 0..NUM_QUERIES {
+            let query_point = [n(rng.gen_range(0f32..1f32)), n(rng.gen_range(0f32..1f32))];
+            let radius = match i % 4 {
+                0 => FXD::MAX,
+                1 => FXD::MAX - FXD::DELTA,
+                _ => n(rng.gen_range(0f32..2f32)),
+            };
+
+            let mut expected: Vec<_> = content_to_add
+                .iter()
+                .map(|(p, item)| (squared_euclidean(&query_point, p), *item))
+                .filter(|(dist, _)| *dist <= radius)
+                .collect();
+            stabilize_sort(&mut expected);
+
+            let mut result: Vec<_> = tree
+                .within(&query_point, radius, &squared_euclidean)
+                .into_iter()
+                .map(|n| (n.distance, n.item))
+                .collect();
+            stabilize_sort(&mut result);
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn can_query_items_within_zero_radius() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        let content_to_add: [([FXD; 2], u32); 6] = [
+            ([n(0.1f32), n(0.2f32)], 1),
+            ([n(0.5f32), n(0.5f32)], 2),
+            ([n(0.5f32), n(0.5f32)], 3),
+            ([n(0.5f32), n(0.6f32)], 4),
+            ([n(0.9f32), n(0.1f32)], 5),
+            ([n(0.3f32), n(0.5f32)], 6),
+        ];
+
+        for (point, item) in content_to_add {
+            tree.add(&point, item);
+        }
+
+        let mut result: Vec<_> = tree
+            .within(&[n(0.5f32), n(0.5f32)], FXD::ZERO, &manhattan)
+            .into_iter()
+            .map(|n| (n.distance, n.item))
+            .collect();
+        stabilize_sort(&mut result);
+
+        assert_eq!(result, vec![(FXD::ZERO, 2), (FXD::ZERO, 3)]);
+    }
+
     fn linear_search<A: Axis, const K: usize>(
         content: &[([A; K], u32)],
         query_point: &[A; K],
@@ -265,7 +342,7 @@ mod tests {
 
         for &(p, item) in content {
             let dist = manhattan(query_point, &p);
-            if dist < radius {
+            if dist <= radius {
                 matching_items.push((dist, item));
             }
         }
@@ -0,0 +1,175 @@
+use alloc::vec::Vec;
+use az::{Az, Cast};
+use core::ops::Rem;
+
+use crate::fixed::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Finds all elements inside the axis-aligned box with corners `min` and `max`.
+    ///
+    /// An element is inside the box if each of its coordinates is between the
+    /// corresponding coordinates of `min` and `max`, inclusive. Returns the point and
+    /// item of each such element, in arbitrary order. If `min` is greater than `max`
+    /// along any axis, the box is empty and so are the results.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fixed::FixedU16;
+    /// use fixed::types::extra::U0;
+    /// use kiddo::fixed::kdtree::KdTree;
+    ///
+    /// type FXD = FixedU16<U0>;
+    ///
+    /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], 100);
+    /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
+    /// tree.add(&[FXD::from_num(20), FXD::from_num(30), FXD::from_num(60)], 102);
+    ///
+    /// let within = tree.within_bbox(
+    ///     &[FXD::from_num(0), FXD::from_num(0), FXD::from_num(0)],
+    ///     &[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)],
+    /// );
+    ///
+    /// assert_eq!(within.len(), 2);
+    /// ```
+    #[inline]
+    pub fn within_bbox(&self, min: &[A; K], max: &[A; K]) -> Vec<([A; K], T)> {
+        let mut matching_items = Vec::new();
+
+        if min.iter().zip(max.iter()).all(|(lo, hi)| lo <= hi) {
+            unsafe { self.within_bbox_recurse(min, max, self.root_index, 0, &mut matching_items) }
+        }
+
+        matching_items
+    }
+
+    unsafe fn within_bbox_recurse(
+        &self,
+        min: &[A; K],
+        max: &[A; K],
+        curr_node_idx: IDX,
+        split_dim: usize,
+        matching_items: &mut Vec<([A; K], T)>,
+    ) {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let next_split_dim = (split_dim + 1).rem(K);
+
+            // points equal to the split value can end up on either side of it
+            if *min.index_unchecked(split_dim) <= node.split_val {
+                self.within_bbox_recurse(min, max, node.left, next_split_dim, matching_items);
+            }
+            if *max.index_unchecked(split_dim) >= node.split_val {
+                self.within_bbox_recurse(min, max, node.right, next_split_dim, matching_items);
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .filter(|(entry, _)| {
+                    entry
+                        .iter()
+                        .zip(min.iter().zip(max.iter()))
+                        .all(|(val, (lo, hi))| lo <= val && val <= hi)
+                })
+                .for_each(|(&entry, &item)| matching_items.push((entry, item)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed::kdtree::KdTree;
+    use fixed::types::extra::U14;
+    use fixed::FixedU16;
+    use rand::Rng;
+
+    type FXD = FixedU16<U14>;
+
+    fn n(num: f32) -> FXD {
+        FXD::from_num(num)
+    }
+
+    #[test]
+    fn can_query_items_within_bbox() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([FXD; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rng.gen::<[f32; 3]>().map(n), item as u32))
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let min = rng.gen::<[f32; 3]>().map(|x| n(x * 0.8));
+            let max = min.map(|x| x + n(rng.gen_range(0.0..0.2)));
+
+            let mut expected: Vec<_> = content_to_add
+                .iter()
+                .filter(|(point, _)| (0..3).all(|d| min[d] <= point[d] && point[d] <= max[d]))
+                .map(|(_, item)| *item)
+                .collect();
+            expected.sort();
+
+            let mut result: Vec<_> = tree
+                .within_bbox(&min, &max)
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect();
+            result.sort();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn within_bbox_is_inclusive_and_handles_duplicate_split_values() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..20 {
+            tree.add(&[n(0.5), n(0.5)], i);
+        }
+        tree.add(&[n(0.25), n(0.75)], 20);
+
+        assert_eq!(
+            tree.within_bbox(&[n(0.5), n(0.5)], &[n(0.5), n(0.5)]).len(),
+            20
+        );
+        assert_eq!(
+            tree.within_bbox(&[n(0.0), n(0.0)], &[n(1.0), n(1.0)]).len(),
+            21
+        );
+    }
+
+    #[test]
+    fn within_bbox_with_inverted_corners_is_empty() {
+        let mut tree: KdTree<FXD, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..20 {
+            tree.add(&[n(i as f32 / 20.0), n(i as f32 / 20.0)], i);
+        }
+
+        assert!(tree
+            .within_bbox(&[n(0.0), n(1.0)], &[n(1.0), n(0.0)])
+            .is_empty());
+    }
+}
@@ -1,9 +1,12 @@
+use alloc::vec::Vec;
 use az::{Az, Cast};
-use std::ops::Rem;
+use core::ops::Rem;
 
+use crate::fixed::distance::DistanceMetric;
 use crate::fixed::kdtree::{Axis, KdTree};
 use crate::fixed::neighbour::Neighbour;
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -13,7 +16,9 @@ where
     /// Finds all elements within `dist` of `query`, using the specified
     /// distance metric function.
     ///
-    /// Results are returned in arbitrary order. Faster than `within`.
+    /// The radius is inclusive, as with `within`. Results are returned in
+    /// arbitrary order, which avoids the cost of sorting them: prefer this
+    /// over `within` when the order of the results is not needed.
     ///
     /// # Examples
     ///
@@ -24,7 +29,6 @@ where
     /// use kiddo::fixed::distance::squared_euclidean;
     ///
     /// type FXD = FixedU16<U0>;
-
     ///
     /// let mut tree: KdTree<FXD, u32, 3, 32, u32> = KdTree::new();
     ///
@@ -32,19 +36,19 @@ where
     /// tree.add(&[FXD::from_num(2), FXD::from_num(3), FXD::from_num(6)], 101);
     /// tree.add(&[FXD::from_num(20), FXD::from_num(30), FXD::from_num(60)], 102);
     ///
-    /// let within = tree.within(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], FXD::from_num(10), &squared_euclidean);
+    /// let within = tree.within_unsorted(&[FXD::from_num(1), FXD::from_num(2), FXD::from_num(5)], FXD::from_num(10), &squared_euclidean);
     ///
     /// assert_eq!(within.len(), 2);
     /// ```
     #[inline]
-    pub fn within_unsorted<F>(
+    pub fn within_unsorted<D>(
         &self,
         query: &[A; K],
         dist: A,
-        distance_fn: &F,
+        distance_fn: &D,
     ) -> Vec<Neighbour<A, T>>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::ZERO; K];
         let mut matching_items = Vec::with_capacity(32_000);
@@ -65,28 +69,28 @@ where
         matching_items
     }
 
-    unsafe fn within_unsorted_recurse<F>(
+    unsafe fn within_unsorted_recurse<D>(
         &self,
         query: &[A; K],
         radius: A,
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
         split_dim: usize,
         matching_items: &mut Vec<Neighbour<A, T>>,
         off: &mut [A; K],
         rd: A,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim].dist(node.split_val);
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
@@ -104,11 +108,7 @@ where
                 rd,
             );
 
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd.saturating_add(
-                (new_off.saturating_mul(new_off)).saturating_sub(old_off.saturating_mul(old_off)),
-            );
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
 
             if rd <= radius {
                 off[split_dim] = new_off;
@@ -127,7 +127,7 @@ where
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
             // println!("Leaf node: {:?}", (curr_node_idx - LEAF_OFFSET) as usize);
 
             leaf_node
@@ -136,12 +136,12 @@ where
                 .enumerate()
                 .take(leaf_node.size.az::<usize>())
                 .for_each(|(idx, entry)| {
-                    let distance = distance_fn(query, entry);
+                    let distance = distance_fn.dist(query, entry);
 
-                    if distance < radius {
+                    if distance <= radius {
                         matching_items.push(Neighbour {
                             distance,
-                            item: *leaf_node.content_items.get_unchecked(idx.az::<usize>()),
+                            item: *leaf_node.content_items.index_unchecked(idx.az::<usize>()),
                         });
                     }
                 });
@@ -262,6 +262,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn within_unsorted_matches_within_once_sorted() {
+        const TREE_SIZE: usize = 100_000;
+        const NUM_QUERIES: usize = 100;
+        let radius: FXD = n(0.5);
+
+        let content_to_add: Vec<([FXD; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand_data_fixed_u16_entry::<U14, u32, 4>())
+            .collect();
+
+        let mut tree: KdTree<FXD, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let query_points: Vec<[FXD; 4]> = (0..NUM_QUERIES)
+            .map(|_| rand_data_fixed_u16_point::<U14, 4>())
+            .collect();
+
+        for query_point in query_points {
+            let mut expected: Vec<_> = tree
+                .within(&query_point, radius, &manhattan)
+                .into_iter()
+                .map(|n| (n.distance, n.item))
+                .collect();
+            stabilize_sort(&mut expected);
+
+            let mut result: Vec<_> = tree
+                .within_unsorted(&query_point, radius, &manhattan)
+                .into_iter()
+                .map(|n| (n.distance, n.item))
+                .collect();
+            stabilize_sort(&mut result);
+
+            assert_eq!(result, expected);
+        }
+    }
+
     fn linear_search<A: Axis, const K: usize>(
         content: &[([A; K], u32)],
         query_point: &[A; K],
@@ -271,7 +309,7 @@ mod tests {
 
         for &(p, item) in content {
             let dist = manhattan(query_point, &p);
-            if dist < radius {
+            if dist <= radius {
                 matching_items.push((dist, item));
             }
         }
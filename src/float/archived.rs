@@ -0,0 +1,290 @@
+//! Queries that run directly against a float [`KdTree`](crate::float::kdtree::KdTree) that
+//! has been serialized with Rkyv, without needing to deserialize it first.
+//!
+//! This allows a serialized tree to be memory-mapped and queried straight away, which is
+//! near-instant even for very large trees.
+
+use alloc::collections::BinaryHeap;
+use az::{Az, Cast};
+use rkyv::Archive;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{ArchivedKdTree, ArchivedLeafNode, Axis, KdTree};
+use crate::float::neighbour::Neighbour;
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A, T, const K: usize, const B: usize, IDX> ArchivedKdTree<A, T, K, B, IDX>
+where
+    A: Axis + Archive<Archived = A>,
+    T: Content + Archive,
+    T::Archived: Copy + Cast<T>,
+    IDX: Index<T = IDX> + Archive<Archived = IDX>,
+    usize: Cast<IDX>,
+{
+    /// Returns the number of elements stored in the archived tree.
+    #[inline]
+    pub fn size(&self) -> T {
+        self.size.az::<T>()
+    }
+
+    /// Queries the archived tree to find the nearest element to `query`, using the specified
+    /// distance metric function. See [`KdTree::nearest_one`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+    /// let archived = unsafe { rkyv::archived_root::<KdTree<f64, u32, 3, 32, u32>>(&bytes) };
+    ///
+    /// let nearest = archived.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean);
+    ///
+    /// assert_eq!(nearest.1, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best_item = T::zero();
+        let mut best_dist = A::max_value();
+
+        unsafe {
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                self.root_index,
+                &mut best_item,
+                &mut best_dist,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        (best_dist, best_item)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_one_recurse<D>(
+        &self,
+        query: &[A; K],
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        best_item: &mut T,
+        best_dist: &mut A,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim as usize;
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                closer_node_idx,
+                best_item,
+                best_dist,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if rd <= *best_dist {
+                off[split_dim] = new_off;
+                self.nearest_one_recurse(
+                    query,
+                    distance_fn,
+                    further_node_idx,
+                    best_item,
+                    best_dist,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            Self::leaf_entries(leaf_node).for_each(|(entry, item)| {
+                let dist = distance_fn.dist(query, entry);
+                if dist < *best_dist || (dist == *best_dist && item < *best_item) {
+                    *best_dist = dist;
+                    *best_item = item;
+                }
+            });
+        }
+    }
+
+    /// Finds the nearest `qty` elements to `query` in the archived tree, using the specified
+    /// distance metric function. See [`KdTree::nearest_n`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+    /// let archived = unsafe { rkyv::archived_root::<KdTree<f64, u32, 3, 32, u32>>(&bytes) };
+    ///
+    /// let nearest = archived.nearest_n(&[1.0, 2.0, 5.1], 1, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest.len(), 1);
+    /// assert_eq!(nearest[0].item, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_n<D>(&self, query: &[A; K], qty: usize, distance_fn: &D) -> Vec<Neighbour<A, T>>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut result: BinaryHeap<Neighbour<A, T>> = BinaryHeap::with_capacity(qty);
+
+        if qty > 0 {
+            unsafe {
+                self.nearest_n_recurse(
+                    query,
+                    qty,
+                    distance_fn,
+                    self.root_index,
+                    &mut result,
+                    &mut off,
+                    A::zero(),
+                )
+            }
+        }
+
+        result.into_sorted_vec()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_n_recurse<D>(
+        &self,
+        query: &[A; K],
+        qty: usize,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        results: &mut BinaryHeap<Neighbour<A, T>>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim as usize;
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.nearest_n_recurse(query, qty, distance_fn, closer_node_idx, results, off, rd);
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if results.len() < qty || rd < results.peek().unwrap().distance {
+                off[split_dim] = new_off;
+                self.nearest_n_recurse(query, qty, distance_fn, further_node_idx, results, off, rd);
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            Self::leaf_entries(leaf_node).for_each(|(entry, item)| {
+                let distance = distance_fn.dist(query, entry);
+                if results.len() < qty {
+                    results.push(Neighbour { distance, item });
+                } else {
+                    let mut top = results.peek_mut().unwrap();
+                    if distance < top.distance {
+                        *top = Neighbour { distance, item };
+                    }
+                }
+            });
+        }
+    }
+
+    fn leaf_entries(
+        leaf_node: &ArchivedLeafNode<A, T, K, B, IDX>,
+    ) -> impl Iterator<Item = (&[A; K], T)> {
+        let size = leaf_node.size.az::<usize>();
+        leaf_node.content_points[..size]
+            .iter()
+            .zip(leaf_node.content_items[..size].iter())
+            .map(|(point, &item)| (point, item.az::<T>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn can_query_an_archived_tree() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        let bytes = rkyv::to_bytes::<_, 1024>(&tree).unwrap();
+        let archived = unsafe { rkyv::archived_root::<KdTree<AX, u32, 4, 32, u32>>(&bytes) };
+
+        assert_eq!(archived.size(), tree.size());
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            assert_eq!(
+                archived.nearest_one(&query_point, &squared_euclidean),
+                tree.nearest_one(&query_point, &squared_euclidean)
+            );
+            assert_eq!(
+                archived.nearest_n(&query_point, 10, &squared_euclidean),
+                tree.nearest_n(&query_point, 10, &squared_euclidean)
+            );
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! A builder for float [`KdTree`]s that gives control over how much memory is
+//! reserved for the tree up front.
+
+use alloc::vec::Vec;
+use az::Cast;
+use core::marker::PhantomData;
+use divrem::DivCeil;
+
+use crate::float::kdtree::{Axis, KdTree, LeafNode, SplitDimStrategy, SplitStrategy};
+use crate::types::{Content, Index};
+
+/// Builds a float [`KdTree`], reserving capacity for its stem and leaf nodes.
+///
+/// [`KdTree::with_capacity`] reserves space for a given number of items, but as the
+/// tree grows by splitting leaves, the number of stems and leaves that end up being
+/// needed depends on how full the leaves are. If too little space is reserved, the
+/// tree has to reallocate while items are being added to it. The builder allows the
+/// capacities to be set directly, or to be estimated generously from the expected
+/// number of points with [`expected_points`](KdTreeBuilder::expected_points).
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::builder::KdTreeBuilder;
+/// use kiddo::float::kdtree::KdTree;
+///
+/// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTreeBuilder::new()
+///     .expected_points(1_000)
+///     .build();
+///
+/// tree.add(&[1.0, 2.0, 5.0], 100);
+///
+/// assert_eq!(tree.size(), 1);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct KdTreeBuilder<A, T, const K: usize, const B: usize, IDX> {
+    stem_capacity: usize,
+    leaf_capacity: usize,
+    split_strategy: SplitStrategy,
+    split_dim_strategy: SplitDimStrategy,
+    _phantom: PhantomData<(A, T, IDX)>,
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTreeBuilder<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Creates a builder with capacity for the same number of points as [`KdTree::new`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            stem_capacity: 0,
+            leaf_capacity: 0,
+            split_strategy: SplitStrategy::default(),
+            split_dim_strategy: SplitDimStrategy::default(),
+            _phantom: PhantomData,
+        }
+        .expected_points(B * 10)
+    }
+
+    /// Sets the number of stem nodes to reserve space for.
+    #[inline]
+    pub fn with_stem_capacity(mut self, stem_capacity: usize) -> Self {
+        self.stem_capacity = stem_capacity;
+        self
+    }
+
+    /// Sets the number of leaf nodes to reserve space for.
+    #[inline]
+    pub fn with_leaf_capacity(mut self, leaf_capacity: usize) -> Self {
+        self.leaf_capacity = leaf_capacity;
+        self
+    }
+
+    /// Sets the stem and leaf capacities to be enough for `points` items to be added to
+    /// the tree without it needing to reallocate.
+    ///
+    /// When a leaf is split, each half gets about `B / 2` of its items, so up to
+    /// `2 * points / B` leaves could be needed, each of which (apart from the first)
+    /// needs a stem.
+    #[inline]
+    pub fn expected_points(self, points: usize) -> Self {
+        assert!(points <= <IDX as Index>::capacity_with_bucket_size(B));
+
+        let leaf_capacity = DivCeil::div_ceil(points * 2, B.max(2)) + 1;
+
+        self.with_leaf_capacity(leaf_capacity)
+            .with_stem_capacity(leaf_capacity)
+    }
+
+    /// Sets how full leaves are split as items are added to the tree.
+    ///
+    /// Defaults to [`SplitStrategy::Median`]. See [`SplitStrategy`] for the tradeoffs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::builder::KdTreeBuilder;
+    /// use kiddo::float::kdtree::{KdTree, SplitStrategy};
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTreeBuilder::new()
+    ///     .with_split_strategy(SplitStrategy::Midpoint)
+    ///     .build();
+    ///
+    /// assert_eq!(tree.split_strategy(), SplitStrategy::Midpoint);
+    /// ```
+    #[inline]
+    pub fn with_split_strategy(mut self, split_strategy: SplitStrategy) -> Self {
+        self.split_strategy = split_strategy;
+        self
+    }
+
+    /// Sets how the axis that full leaves are split along is chosen as items are added
+    /// to the tree.
+    ///
+    /// Defaults to [`SplitDimStrategy::RoundRobin`]. See [`SplitDimStrategy`] for the
+    /// tradeoffs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::builder::KdTreeBuilder;
+    /// use kiddo::float::kdtree::{KdTree, SplitDimStrategy};
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTreeBuilder::new()
+    ///     .with_split_dim_strategy(SplitDimStrategy::MaxVariance)
+    ///     .build();
+    ///
+    /// assert_eq!(tree.split_dim_strategy(), SplitDimStrategy::MaxVariance);
+    /// ```
+    #[inline]
+    pub fn with_split_dim_strategy(mut self, split_dim_strategy: SplitDimStrategy) -> Self {
+        self.split_dim_strategy = split_dim_strategy;
+        self
+    }
+
+    /// Creates an empty [`KdTree`] with the configured capacities and split strategies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bucket size `B` is less than 2.
+    #[inline]
+    pub fn build(self) -> KdTree<A, T, K, B, IDX> {
+        assert!(B >= 2, "bucket size B must be at least 2");
+
+        let mut leaves = Vec::with_capacity(self.leaf_capacity.max(1));
+        leaves.push(LeafNode::new());
+
+        KdTree {
+            size: T::zero(),
+            stems: Vec::with_capacity(self.stem_capacity),
+            leaves,
+            root_index: <IDX as Index>::leaf_offset(),
+            free_leaves: Vec::new(),
+            free_stems: Vec::new(),
+            split_strategy: self.split_strategy,
+            split_dim_strategy: self.split_dim_strategy,
+        }
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Default
+    for KdTreeBuilder<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::builder::KdTreeBuilder;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn expected_points_avoids_reallocation_during_add() {
+        const TREE_SIZE: usize = 100_000;
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> =
+            KdTreeBuilder::new().expected_points(TREE_SIZE).build();
+        let stem_capacity = tree.stems.capacity();
+        let leaf_capacity = tree.leaves.capacity();
+
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 3], u32)>();
+            tree.add(&point, item);
+        }
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert_eq!(tree.stems.capacity(), stem_capacity);
+        assert_eq!(tree.leaves.capacity(), leaf_capacity);
+    }
+
+    #[test]
+    fn explicit_capacities_are_used() {
+        let tree: KdTree<AX, u32, 3, 32, u32> = KdTreeBuilder::new()
+            .with_stem_capacity(123)
+            .with_leaf_capacity(456)
+            .build();
+
+        assert!(tree.stems.capacity() >= 123);
+        assert!(tree.leaves.capacity() >= 456);
+        assert_eq!(tree.size(), 0);
+    }
+}
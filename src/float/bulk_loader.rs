@@ -0,0 +1,188 @@
+//! Staging for float [`KdTree`]s that are loaded with all of their points up front and
+//! only then queried.
+
+use alloc::vec::Vec;
+use az::Cast;
+use core::marker::PhantomData;
+
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+/// Collects points for a float [`KdTree`] and builds the tree from them in one go.
+///
+/// [`append`](BulkLoader::append) only pushes each point and its item onto a flat
+/// buffer, and [`finalize`](BulkLoader::finalize) then builds a balanced tree from all
+/// of them, in the same way as [`KdTree::from_slice`]. Compared to calling
+/// [`KdTree::add`] for each point, this avoids descending the tree and splitting full
+/// leaves as points arrive, and the resulting tree is balanced, so it is also faster to
+/// query. It suits loading a tree whose points arrive one at a time, such as from a
+/// file, before it is queried.
+///
+/// The loader cannot be queried itself, so a tree cannot be queried before it has been
+/// finalized. Points can still be [`add`](KdTree::add)ed to the finalized tree as usual.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::bulk_loader::BulkLoader;
+/// use kiddo::float::distance::squared_euclidean;
+/// use kiddo::float::kdtree::KdTree;
+///
+/// let mut loader: BulkLoader<f64, u32, 3, 32, u32> = BulkLoader::new();
+///
+/// loader.append(&[1.0, 2.0, 5.0], 100);
+/// loader.append(&[2.0, 3.0, 6.0], 101);
+///
+/// let tree: KdTree<f64, u32, 3, 32, u32> = loader.finalize();
+///
+/// assert_eq!(tree.size(), 2);
+/// assert_eq!(tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean).1, 100);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BulkLoader<A, T, const K: usize, const B: usize, IDX> {
+    points: Vec<[A; K]>,
+    items: Vec<T>,
+    _phantom: PhantomData<IDX>,
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    BulkLoader<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+    usize: Cast<T>,
+{
+    /// Creates an empty loader.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty loader with room for `num_points` points to be appended without
+    /// it needing to reallocate.
+    #[inline]
+    pub fn with_capacity(num_points: usize) -> Self {
+        Self {
+            points: Vec::with_capacity(num_points),
+            items: Vec::with_capacity(num_points),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Stages a point and its item, to be added to the tree when it is
+    /// [`finalize`](BulkLoader::finalize)d.
+    #[inline]
+    pub fn append(&mut self, point: &[A; K], item: T) {
+        self.points.push(*point);
+        self.items.push(item);
+    }
+
+    /// Returns the number of points that have been appended so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns `true` if no points have been appended yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Builds a balanced [`KdTree`] containing every point that has been appended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more points have been appended than the tree's `IDX` type can index,
+    /// as with [`KdTree::with_capacity`].
+    #[inline]
+    pub fn finalize(self) -> KdTree<A, T, K, B, IDX> {
+        KdTree::from_points_and_items(self.points, self.items)
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Default
+    for BulkLoader<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+    usize: Cast<T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Extend<([A; K], T)>
+    for BulkLoader<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+    usize: Cast<T>,
+{
+    fn extend<I: IntoIterator<Item = ([A; K], T)>>(&mut self, iter: I) {
+        iter.into_iter()
+            .for_each(|(point, item)| self.append(&point, item));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::bulk_loader::BulkLoader;
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn finalizing_builds_the_same_tree_as_from_slice() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut loader: BulkLoader<AX, u32, 3, 32, u32> = BulkLoader::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| loader.append(point, *item));
+        assert_eq!(loader.len(), TREE_SIZE);
+
+        let tree = loader.finalize();
+        let expected: KdTree<AX, u32, 3, 32, u32> = KdTree::from_slice(&content_to_add);
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert_eq!(tree.leaf_count(), expected.leaf_count());
+        assert_eq!(tree.height(), expected.height());
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            assert_eq!(
+                tree.nearest_n(&query_point, 10, &squared_euclidean),
+                expected.nearest_n(&query_point, 10, &squared_euclidean)
+            );
+        }
+    }
+
+    #[test]
+    fn an_empty_loader_finalizes_to_an_empty_tree_that_can_be_added_to() {
+        let loader: BulkLoader<AX, u32, 2, 4, u32> = BulkLoader::default();
+        assert!(loader.is_empty());
+
+        let mut tree = loader.finalize();
+        assert_eq!(tree.size(), 0);
+
+        let mut extended: BulkLoader<AX, u32, 2, 4, u32> = BulkLoader::new();
+        extended.extend((0..10).map(|i| ([i as AX, 0.0], i)));
+        let mut extended = extended.finalize();
+
+        for i in 10..20 {
+            tree.add(&[i as AX, 0.0], i);
+            extended.add(&[i as AX, 0.0], i);
+        }
+
+        assert_eq!(tree.size(), 10);
+        assert_eq!(extended.size(), 20);
+        assert_eq!(extended.nearest_one(&[3.1, 0.0], &squared_euclidean).1, 3);
+        assert_eq!(extended.nearest_one(&[18.9, 0.0], &squared_euclidean).1, 19);
+    }
+}
@@ -0,0 +1,500 @@
+//! A compact, versioned binary format for float [`KdTree`]s that does not depend on serde.
+//!
+//! All values are little-endian. The layout is:
+//!
+//! | Field                      | Encoding                                          |
+//! |----------------------------|---------------------------------------------------|
+//! | magic number               | the 4 bytes `KDDO`                                |
+//! | format version             | `u8`, currently 2                                 |
+//! | sizes of `A`, `T` and `IDX`| one `u8` each, in bytes                           |
+//! | `K`                        | `u32`                                             |
+//! | `B`                        | `u32`                                             |
+//! | size                       | `u64`, the number of items stored in the tree     |
+//! | split strategy             | `u8`, 0 for median or 1 for midpoint              |
+//! | split dimension strategy   | `u8`, 0 for round robin or 1 for max variance     |
+//! | root index                 | `IDX`                                             |
+//! | stems                      | `u64` count, then `left: IDX, right: IDX, split_val: A, split_dim: u32` for each |
+//! | leaves                     | `u64` count, then for each leaf its size as an `IDX`, followed by that many points of `K` `A`s and then that many `T` items |
+//! | free leaves                | `u64` count, then an `IDX` for each               |
+//! | free stems                 | `u64` count, then an `IDX` for each               |
+//!
+//! Only the occupied part of each leaf is written, so the output is usually much smaller
+//! than the tree's size in memory. The bounding boxes of the stems that are cached with
+//! the `bbox-cache` feature are not written, and are recomputed when the tree is read.
+
+use alloc::{vec, vec::Vec};
+use az::{Az, Cast, CheckedAs, CheckedCast};
+use num_traits::{FromBytes, ToBytes};
+
+use crate::error::FromBytesError;
+use crate::float::kdtree::{Axis, KdTree, LeafNode, SplitDimStrategy, SplitStrategy, StemNode};
+use crate::types::{Content, Index};
+
+const MAGIC: [u8; 4] = *b"KDDO";
+const FORMAT_VERSION: u8 = 2;
+
+impl<A, T, const K: usize, const B: usize, IDX> KdTree<A, T, K, B, IDX>
+where
+    A: Axis + ToBytes + FromBytes,
+    <A as FromBytes>::Bytes: Default,
+    T: Content + ToBytes + FromBytes + Cast<usize>,
+    <T as FromBytes>::Bytes: Default,
+    IDX: Index<T = IDX> + ToBytes + FromBytes,
+    <IDX as FromBytes>::Bytes: Default,
+    usize: Cast<IDX> + CheckedCast<T>,
+{
+    /// Serializes the tree into the binary format described in the
+    /// [module documentation](crate::float::bytes).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// let bytes = tree.to_bytes();
+    ///
+    /// assert_eq!(&bytes[..4], b"KDDO");
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.push(core::mem::size_of::<A>() as u8);
+        bytes.push(core::mem::size_of::<T>() as u8);
+        bytes.push(core::mem::size_of::<IDX>() as u8);
+        bytes.extend_from_slice(&(K as u32).to_le_bytes());
+        bytes.extend_from_slice(&(B as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.size.az::<usize>() as u64).to_le_bytes());
+        bytes.push(match self.split_strategy {
+            SplitStrategy::Median => 0,
+            SplitStrategy::Midpoint => 1,
+        });
+        bytes.push(match self.split_dim_strategy {
+            SplitDimStrategy::RoundRobin => 0,
+            SplitDimStrategy::MaxVariance => 1,
+        });
+        write_value(&mut bytes, &self.root_index);
+
+        write_len(&mut bytes, self.stems.len());
+        for stem in &self.stems {
+            write_value(&mut bytes, &stem.left);
+            write_value(&mut bytes, &stem.right);
+            write_value(&mut bytes, &stem.split_val);
+            write_value(&mut bytes, &(stem.split_dim as u32));
+        }
+
+        write_len(&mut bytes, self.leaves.len());
+        for leaf in &self.leaves {
+            let size = leaf.size.az::<usize>();
+            write_value(&mut bytes, &leaf.size);
+            for point in &leaf.content_points[..size] {
+                point.iter().for_each(|val| write_value(&mut bytes, val));
+            }
+            for item in &leaf.content_items[..size] {
+                write_value(&mut bytes, item);
+            }
+        }
+
+        for free_list in [&self.free_leaves, &self.free_stems] {
+            write_len(&mut bytes, free_list.len());
+            free_list
+                .iter()
+                .for_each(|node_idx| write_value(&mut bytes, node_idx));
+        }
+
+        bytes
+    }
+
+    /// Deserializes a tree that was serialized by [`to_bytes`](KdTree::to_bytes).
+    ///
+    /// Returns an error rather than panicking if `bytes` is not a valid tree, including
+    /// if it was serialized from a tree with a different `K`, `B`, or type of `A`, `T` or
+    /// `IDX`. Every node index is checked, so a corrupted input cannot cause a later query
+    /// to access memory out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// let bytes = tree.to_bytes();
+    /// let deserialized: KdTree<f64, u32, 3, 32, u32> = KdTree::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(deserialized.size(), 1);
+    /// assert!(deserialized.contains(&[1.0, 2.0, 5.0], 100));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromBytesError> {
+        let mut reader = ByteReader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(FromBytesError::BadMagic);
+        }
+        let version = reader.read_u8()?;
+        if version != FORMAT_VERSION {
+            return Err(FromBytesError::UnsupportedVersion(version));
+        }
+
+        let type_sizes = [reader.read_u8()?, reader.read_u8()?, reader.read_u8()?];
+        let k = reader.read_u32()?;
+        let b = reader.read_u32()?;
+        let expected_type_sizes = [
+            core::mem::size_of::<A>(),
+            core::mem::size_of::<T>(),
+            core::mem::size_of::<IDX>(),
+        ];
+        if type_sizes.map(usize::from) != expected_type_sizes || k as usize != K || b as usize != B
+        {
+            return Err(FromBytesError::LayoutMismatch);
+        }
+
+        let size = reader.read_len()?;
+        let split_strategy = match reader.read_u8()? {
+            0 => SplitStrategy::Median,
+            1 => SplitStrategy::Midpoint,
+            _ => return Err(FromBytesError::Corrupt),
+        };
+        let split_dim_strategy = match reader.read_u8()? {
+            0 => SplitDimStrategy::RoundRobin,
+            1 => SplitDimStrategy::MaxVariance,
+            _ => return Err(FromBytesError::Corrupt),
+        };
+        let root_index: IDX = reader.read_value()?;
+
+        let stem_count = reader.read_len()?;
+        let mut stems = Vec::with_capacity(stem_count.min(reader.bytes.len()));
+        for _ in 0..stem_count {
+            stems.push(StemNode::new(
+                reader.read_value()?,
+                reader.read_value()?,
+                reader.read_value()?,
+                reader.read_u32()? as usize,
+            ));
+        }
+
+        let leaf_count = reader.read_len()?;
+        let mut leaves = Vec::with_capacity(leaf_count.min(reader.bytes.len()));
+        for _ in 0..leaf_count {
+            let mut leaf: LeafNode<A, T, K, B, IDX> = LeafNode::new();
+            leaf.size = reader.read_value()?;
+            let leaf_size = leaf.size.az::<usize>();
+            if leaf_size > B {
+                return Err(FromBytesError::Corrupt);
+            }
+            for point in &mut leaf.content_points[..leaf_size] {
+                for val in point.iter_mut() {
+                    *val = reader.read_value()?;
+                }
+            }
+            for item in &mut leaf.content_items[..leaf_size] {
+                *item = reader.read_value()?;
+            }
+            leaves.push(leaf);
+        }
+
+        let mut free_lists: [Vec<IDX>; 2] = [Vec::new(), Vec::new()];
+        for free_list in &mut free_lists {
+            let free_count = reader.read_len()?;
+            for _ in 0..free_count {
+                free_list.push(reader.read_value()?);
+            }
+        }
+        let [free_leaves, free_stems] = free_lists;
+
+        if !reader.bytes.is_empty() {
+            return Err(FromBytesError::Corrupt);
+        }
+
+        let tree = Self {
+            leaves,
+            stems,
+            root_index,
+            size: size.checked_as::<T>().ok_or(FromBytesError::Corrupt)?,
+            free_leaves,
+            free_stems,
+            split_strategy,
+            split_dim_strategy,
+        };
+
+        if !tree.is_well_formed(size) {
+            return Err(FromBytesError::Corrupt);
+        }
+
+        #[cfg(feature = "bbox-cache")]
+        let tree = {
+            let mut tree = tree;
+            tree.update_all_stem_bboxes();
+            tree
+        };
+
+        Ok(tree)
+    }
+
+    /// Checks that every node reachable from the root is in bounds and reached exactly
+    /// once, that every stem splits along an axis that exists, that the reachable leaves
+    /// hold `size` items between them, and that the free lists only refer to nodes that
+    /// exist and are not in the tree, each at most once, with every free leaf empty.
+    /// Otherwise, reusing a free node when splitting would overwrite part of the tree.
+    fn is_well_formed(&self, size: usize) -> bool {
+        let leaf_offset = IDX::leaf_offset().az::<usize>();
+        if self.stems.len() > leaf_offset
+            || self.leaves.len() > (<IDX as Index>::max() - IDX::leaf_offset()).az::<usize>() + 1
+        {
+            return false;
+        }
+
+        let mut stem_visited = vec![false; self.stems.len()];
+        let mut leaf_visited = vec![false; self.leaves.len()];
+        let mut reachable_size = 0;
+
+        let mut to_visit = vec![self.root_index];
+        while let Some(node_idx) = to_visit.pop() {
+            if Self::is_stem_index(node_idx) {
+                let stem_idx = node_idx.az::<usize>();
+                if stem_idx >= self.stems.len()
+                    || stem_visited[stem_idx]
+                    || self.stems[stem_idx].split_dim >= K
+                {
+                    return false;
+                }
+                stem_visited[stem_idx] = true;
+                to_visit.push(self.stems[stem_idx].left);
+                to_visit.push(self.stems[stem_idx].right);
+            } else {
+                let leaf_idx = (node_idx - IDX::leaf_offset()).az::<usize>();
+                if leaf_idx >= self.leaves.len() || leaf_visited[leaf_idx] {
+                    return false;
+                }
+                leaf_visited[leaf_idx] = true;
+                reachable_size += self.leaves[leaf_idx].size.az::<usize>();
+            }
+        }
+
+        if reachable_size != size {
+            return false;
+        }
+
+        // marking free nodes as visited too catches any that are listed twice
+        for &leaf_idx in &self.free_leaves {
+            let leaf_idx = leaf_idx.az::<usize>();
+            if leaf_idx >= self.leaves.len()
+                || leaf_visited[leaf_idx]
+                || self.leaves[leaf_idx].size != IDX::zero()
+            {
+                return false;
+            }
+            leaf_visited[leaf_idx] = true;
+        }
+        for &stem_idx in &self.free_stems {
+            let stem_idx = stem_idx.az::<usize>();
+            if stem_idx >= self.stems.len() || stem_visited[stem_idx] {
+                return false;
+            }
+            stem_visited[stem_idx] = true;
+        }
+
+        true
+    }
+}
+
+fn write_value<V: ToBytes>(bytes: &mut Vec<u8>, val: &V) {
+    bytes.extend_from_slice(val.to_le_bytes().as_ref());
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FromBytesError> {
+        if self.bytes.len() < len {
+            return Err(FromBytesError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, FromBytesError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, FromBytesError> {
+        self.read_value()
+    }
+
+    fn read_len(&mut self) -> Result<usize, FromBytesError> {
+        let len: u64 = self.read_value()?;
+        usize::try_from(len).map_err(|_| FromBytesError::Corrupt)
+    }
+
+    fn read_value<V>(&mut self) -> Result<V, FromBytesError>
+    where
+        V: FromBytes,
+        V::Bytes: Default,
+    {
+        let mut val_bytes = V::Bytes::default();
+        let len = val_bytes.as_ref().len();
+        val_bytes.as_mut().copy_from_slice(self.take(len)?);
+
+        Ok(V::from_le_bytes(&val_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::FromBytesError;
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::{KdTree, LeafNode};
+    use crate::types::Index;
+
+    type AX = f64;
+
+    fn build_tree() -> KdTree<AX, u32, 3, 8, u32> {
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        let content_to_add: Vec<([AX; 3], u32)> = (0..1000)
+            .map(|item| (rand::random::<[AX; 3]>(), item))
+            .collect();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+        content_to_add[..500].iter().for_each(|(point, item)| {
+            tree.remove_returning(point, *item);
+        });
+
+        tree
+    }
+
+    #[test]
+    fn can_round_trip_a_tree_through_bytes() {
+        let tree = build_tree();
+
+        let bytes = tree.to_bytes();
+        let deserialized: KdTree<AX, u32, 3, 8, u32> = KdTree::from_bytes(&bytes).unwrap();
+
+        // the unused parts of leaves are not written, so compare everything else
+        assert_eq!(deserialized.to_bytes(), bytes);
+        assert_eq!(
+            deserialized.iter().collect::<Vec<_>>(),
+            tree.iter().collect::<Vec<_>>()
+        );
+        for _ in 0..100 {
+            let query_point = rand::random::<[AX; 3]>();
+            assert_eq!(
+                deserialized.nearest_one(&query_point, &squared_euclidean),
+                tree.nearest_one(&query_point, &squared_euclidean)
+            );
+        }
+    }
+
+    #[test]
+    fn from_bytes_rejects_a_tree_with_a_different_k() {
+        let bytes = build_tree().to_bytes();
+
+        let result: Result<KdTree<AX, u32, 2, 8, u32>, _> = KdTree::from_bytes(&bytes);
+        assert_eq!(result.err(), Some(FromBytesError::LayoutMismatch));
+
+        let result: Result<KdTree<f32, u32, 3, 8, u32>, _> = KdTree::from_bytes(&bytes);
+        assert_eq!(result.err(), Some(FromBytesError::LayoutMismatch));
+    }
+
+    #[test]
+    fn from_bytes_rejects_invalid_input() {
+        let bytes = build_tree().to_bytes();
+
+        let from_bytes = |bytes: &[u8]| KdTree::<AX, u32, 3, 8, u32>::from_bytes(bytes).err();
+
+        assert_eq!(from_bytes(b"not a tree"), Some(FromBytesError::BadMagic));
+        assert_eq!(
+            from_bytes(&bytes[..bytes.len() - 1]),
+            Some(FromBytesError::Truncated)
+        );
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 99;
+        assert_eq!(
+            from_bytes(&wrong_version),
+            Some(FromBytesError::UnsupportedVersion(99))
+        );
+
+        // the root index, which follows the 26 byte header
+        let mut bad_root = bytes.clone();
+        bad_root[26..30].copy_from_slice(&12345u32.to_le_bytes());
+        assert_eq!(from_bytes(&bad_root), Some(FromBytesError::Corrupt));
+
+        // the split dimension of the first stem, after the root index, the stem count and
+        // the first stem's children and split value
+        let mut bad_split_dim = bytes.clone();
+        bad_split_dim[54..58].copy_from_slice(&3u32.to_le_bytes());
+        assert_eq!(from_bytes(&bad_split_dim), Some(FromBytesError::Corrupt));
+    }
+
+    #[test]
+    fn from_bytes_rejects_free_nodes_that_are_in_use() {
+        let mut tree = build_tree();
+        tree.leaves.push(LeafNode::new());
+        tree.free_leaves = vec![(tree.leaves.len() - 1) as u32];
+        tree.free_stems.clear();
+
+        let from_tree = |tree: &KdTree<AX, u32, 3, 8, u32>| {
+            KdTree::<AX, u32, 3, 8, u32>::from_bytes(&tree.to_bytes()).err()
+        };
+        assert_eq!(from_tree(&tree), None);
+
+        let mut live_node = tree.root_index;
+        while KdTree::<AX, u32, 3, 8, u32>::is_stem_index(live_node) {
+            live_node = tree.stems[live_node as usize].left;
+        }
+
+        let mut live_free_leaf = tree.clone();
+        live_free_leaf
+            .free_leaves
+            .push(live_node - u32::leaf_offset());
+        assert_eq!(from_tree(&live_free_leaf), Some(FromBytesError::Corrupt));
+
+        let mut repeated_free_leaf = tree.clone();
+        repeated_free_leaf
+            .free_leaves
+            .push(repeated_free_leaf.free_leaves[0]);
+        assert_eq!(
+            from_tree(&repeated_free_leaf),
+            Some(FromBytesError::Corrupt)
+        );
+
+        let mut non_empty_free_leaf = tree.clone();
+        let free_leaf = non_empty_free_leaf.free_leaves[0] as usize;
+        non_empty_free_leaf.leaves[free_leaf].size = 1;
+        assert_eq!(
+            from_tree(&non_empty_free_leaf),
+            Some(FromBytesError::Corrupt)
+        );
+
+        let mut live_free_stem = tree.clone();
+        live_free_stem.free_stems.push(tree.root_index);
+        assert_eq!(from_tree(&live_free_stem), Some(FromBytesError::Corrupt));
+
+        let mut repeated_free_stem = tree.clone();
+        repeated_free_stem
+            .stems
+            .push(repeated_free_stem.stems[0].clone());
+        let unused_stem = (repeated_free_stem.stems.len() - 1) as u32;
+        repeated_free_stem.free_stems = vec![unused_stem];
+        assert_eq!(from_tree(&repeated_free_stem), None);
+        repeated_free_stem.free_stems.push(unused_stem);
+        assert_eq!(
+            from_tree(&repeated_free_stem),
+            Some(FromBytesError::Corrupt)
+        );
+    }
+}
@@ -1,8 +1,14 @@
-use crate::float::kdtree::{Axis, KdTree, LeafNode, StemNode};
+use crate::error::{AddError, CapacityExceeded, NanError};
+#[cfg(feature = "bbox-cache")]
+use crate::float::kdtree::StemBbox;
+use crate::float::kdtree::{Axis, KdTree, LeafNode, SplitDimStrategy, SplitStrategy, StemNode};
 use crate::mirror_select_nth_unstable_by::mirror_select_nth_unstable_by;
+use crate::split_ties::tie_goes_left;
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+use alloc::{vec, vec::Vec};
 use az::{Az, Cast};
-use std::ops::Rem;
+use core::ops::Rem;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -14,6 +20,15 @@ where
     /// The first argument specifies co-ordinates of the point where the item is located.
     /// The second argument is an integer identifier / index for the item being stored.
     ///
+    /// When a leaf is full, it is split in two, with half of its points moving to a new
+    /// leaf. If the points all have the same co-ordinate along the axis that would be
+    /// split, the next axis along which they differ is split instead. Points that are at
+    /// the same position cannot be separated by any split, but they are still shared
+    /// between the two leaves, and points added at that position later are spread over
+    /// the leaves below it, so adding any number of them is fine and the tree only grows
+    /// with the logarithm of their number. Queries near them still have to look at all
+    /// of them, so if many items share a position, consider a larger bucket size `B`.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -27,18 +42,31 @@ where
     /// ```
     #[inline]
     pub fn add(&mut self, query: &[A; K], item: T) {
+        debug_assert!(
+            query.iter().all(|val| !val.is_nan()),
+            "cannot add a point with a NaN coordinate"
+        );
+
         unsafe {
             let mut stem_idx = self.root_index;
             let mut split_dim = 0;
             let mut stem_node;
             let mut parent_idx = <IDX as Index>::max();
             let mut is_left_child: bool = false;
+            let tie_seed = self.stems.len() - self.free_stems.len();
+            let mut depth = 0;
 
             while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
                 parent_idx = stem_idx;
-                stem_node = self.stems.get_unchecked_mut(stem_idx.az::<usize>());
+                stem_node = self.stems.index_unchecked_mut(stem_idx.az::<usize>());
+                split_dim = stem_node.split_dim;
+                #[cfg(feature = "bbox-cache")]
+                stem_node.extend_bbox(query);
 
-                stem_idx = if *query.get_unchecked(split_dim) <= stem_node.split_val {
+                let query_val = *query.index_unchecked(split_dim);
+                stem_idx = if query_val < stem_node.split_val
+                    || (query_val == stem_node.split_val && tie_goes_left(tie_seed, depth))
+                {
                     is_left_child = true;
                     stem_node.left
                 } else {
@@ -47,41 +75,336 @@ where
                 };
 
                 split_dim = (split_dim + 1).rem(K);
+                depth += 1;
             }
 
             let mut leaf_idx = stem_idx - IDX::leaf_offset();
-            let mut leaf_node = self.leaves.get_unchecked_mut(leaf_idx.az::<usize>());
+            let mut leaf_node = self.leaves.index_unchecked_mut(leaf_idx.az::<usize>());
 
             if leaf_node.size == B.az::<IDX>() {
                 stem_idx = self.split(leaf_idx, split_dim, parent_idx, is_left_child);
-                let node = self.stems.get_unchecked_mut(stem_idx.az::<usize>());
+                let node = self.stems.index_unchecked_mut(stem_idx.az::<usize>());
+                #[cfg(feature = "bbox-cache")]
+                node.extend_bbox(query);
 
-                leaf_idx = (if *query.get_unchecked(split_dim) < node.split_val {
+                leaf_idx = (if *query.index_unchecked(node.split_dim) < node.split_val {
                     node.left
                 } else {
                     node.right
                 } - IDX::leaf_offset());
 
-                leaf_node = self.leaves.get_unchecked_mut(leaf_idx.az::<usize>());
+                leaf_node = self.leaves.index_unchecked_mut(leaf_idx.az::<usize>());
             }
 
             *leaf_node
                 .content_points
-                .get_unchecked_mut(leaf_node.size.az::<usize>()) = *query;
+                .index_unchecked_mut(leaf_node.size.az::<usize>()) = *query;
             *leaf_node
                 .content_items
-                .get_unchecked_mut(leaf_node.size.az::<usize>()) = item;
+                .index_unchecked_mut(leaf_node.size.az::<usize>()) = item;
 
             leaf_node.size = leaf_node.size + IDX::one();
         }
         self.size = self.size + T::one();
     }
 
+    /// Adds an item to the tree, unless any of the co-ordinates of its point are NaN or
+    /// the tree has run out of space.
+    ///
+    /// Behaves the same as [`add`](KdTree::add), but leaves the tree unchanged and returns
+    /// an error if:
+    /// * the point has a NaN co-ordinate, as such a point cannot be ordered against the
+    ///   others in the tree ([`AddError::Nan`]), or
+    /// * adding the point would require splitting a leaf, but the tree already has as
+    ///   many stems or leaves as `IDX` can index ([`AddError::CapacityExceeded`]).
+    ///   `add` does not check for this in release builds, and would corrupt the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::error::{AddError, NanError};
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.try_add(&[1.0, 2.0, 5.0], 100), Ok(()));
+    /// assert_eq!(tree.try_add(&[1.0, f64::NAN, 5.0], 101), Err(AddError::Nan(NanError)));
+    ///
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn try_add(&mut self, query: &[A; K], item: T) -> Result<(), AddError> {
+        if query.iter().any(|val| val.is_nan()) {
+            return Err(NanError.into());
+        }
+
+        if !self.has_capacity_to_split() && self.add_would_split(query) {
+            return Err(CapacityExceeded.into());
+        }
+
+        self.add(query, item);
+
+        Ok(())
+    }
+
+    /// Adds every `(point, item)` pair in `items` to the tree.
+    ///
+    /// If the tree is empty, it is built in balanced form in the same way as
+    /// [`from_slice`](KdTree::from_slice), which is much faster than adding the items one at
+    /// a time and avoids splitting any leaves. Otherwise, enough space for the new stems
+    /// and leaves is reserved up front before the items are added individually.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add_many(&[([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)]);
+    /// tree.add_many(&[([3.0, 4.0, 7.0], 102)]);
+    ///
+    /// assert_eq!(tree.size(), 3);
+    /// ```
+    pub fn add_many(&mut self, items: &[([A; K], T)])
+    where
+        usize: Cast<T>,
+    {
+        if self.size == T::zero() {
+            let (mut content_points, mut content_items): (Vec<[A; K]>, Vec<T>) =
+                items.iter().copied().unzip();
+
+            self.leaves.clear();
+            self.stems.clear();
+            self.free_leaves.clear();
+            self.free_stems.clear();
+            self.root_index = self.build_balanced(&mut content_points, &mut content_items, 0);
+            self.size = items.len().az::<T>();
+
+            return;
+        }
+
+        self.reserve(items.len());
+
+        items
+            .iter()
+            .for_each(|(point, item)| self.add(point, *item));
+    }
+
+    /// Creates a balanced tree containing all of the `(point, item)` pairs in `points`.
+    ///
+    /// Rather than adding the points one at a time, which results in a tree whose shape
+    /// depends upon the order the points were added in, the points are repeatedly
+    /// split in half at the median along each axis in turn. This is faster than adding
+    /// the points individually, and produces a tree that is faster to query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let points = vec![([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)];
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTree::from_slice(&points);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// assert_eq!(tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean).1, 100);
+    /// ```
+    #[inline]
+    pub fn from_slice(points: &[([A; K], T)]) -> Self
+    where
+        usize: Cast<T>,
+    {
+        let mut tree = Self::with_capacity(points.len());
+        let (mut content_points, mut content_items): (Vec<[A; K]>, Vec<T>) =
+            points.iter().copied().unzip();
+
+        tree.leaves.clear();
+        tree.root_index = tree.build_balanced(&mut content_points, &mut content_items, 0);
+        tree.size = points.len().az::<T>();
+
+        tree
+    }
+
+    /// Creates a balanced tree containing all of the `(point, item)` pairs in `points`,
+    /// such as those returned by [`dump_points`](KdTree::dump_points).
+    ///
+    /// The tree is built in the same way as by [`from_slice`](KdTree::from_slice), but
+    /// the points can come from any iterator, so they can be read straight out of
+    /// whatever format they were stored in, without first being collected into a slice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// // points stored column by column
+    /// let xs = vec![1.0, 2.0];
+    /// let ys = vec![2.0, 3.0];
+    /// let items = vec![100, 101];
+    ///
+    /// let points = xs.into_iter().zip(ys).map(|(x, y)| [x, y]).zip(items);
+    /// let tree: KdTree<f64, u32, 2, 32, u32> = KdTree::load_points(points);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// assert_eq!(tree.nearest_one(&[1.0, 2.1], &squared_euclidean).1, 100);
+    /// ```
+    #[inline]
+    pub fn load_points<I>(points: I) -> Self
+    where
+        I: IntoIterator<Item = ([A; K], T)>,
+        usize: Cast<T>,
+    {
+        let (content_points, content_items): (Vec<[A; K]>, Vec<T>) = points.into_iter().unzip();
+
+        Self::from_points_and_items(content_points, content_items)
+    }
+
+    /// Creates a balanced tree from points and the items at the same positions in
+    /// `content_items`, reusing the vectors as scratch space while building it.
+    pub(crate) fn from_points_and_items(
+        mut content_points: Vec<[A; K]>,
+        mut content_items: Vec<T>,
+    ) -> Self
+    where
+        usize: Cast<T>,
+    {
+        debug_assert_eq!(content_points.len(), content_items.len());
+
+        let mut tree = Self::with_capacity(content_points.len());
+        tree.leaves.clear();
+        tree.root_index = tree.build_balanced(&mut content_points, &mut content_items, 0);
+        tree.size = content_points.len().az::<T>();
+
+        tree
+    }
+
+    /// Rebuilds the tree in balanced form.
+    ///
+    /// Adding and removing lots of items can leave the tree lopsided, particularly if
+    /// points are added in sorted order, which makes queries slower. This collects
+    /// everything that is stored in the tree and rebuilds it in the same way as
+    /// [`from_slice`](KdTree::from_slice). The contents of the tree are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 4, u32> = KdTree::new();
+    ///
+    /// for i in 0..100 {
+    ///     tree.add(&[i as f64, i as f64], i);
+    /// }
+    ///
+    /// tree.rebalance();
+    ///
+    /// assert_eq!(tree.size(), 100);
+    /// ```
+    pub fn rebalance(&mut self) {
+        let (mut content_points, mut content_items): (Vec<[A; K]>, Vec<T>) =
+            self.iter().map(|(&point, &item)| (point, item)).unzip();
+
+        self.leaves.clear();
+        self.stems.clear();
+        self.free_leaves.clear();
+        self.free_stems.clear();
+        self.root_index = self.build_balanced(&mut content_points, &mut content_items, 0);
+    }
+
+    /// Moves everything stored in `other` into this tree.
+    ///
+    /// Rather than adding the contents of `other` one at a time, everything from both
+    /// trees is rebuilt into a single balanced tree in the same way as
+    /// [`from_slice`](KdTree::from_slice). This takes O((n + m) log(n + m)) time for
+    /// trees of size n and m, so when `other` is much smaller than this tree, it can be
+    /// quicker to [`add`](KdTree::add) its contents instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// let mut other: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    /// other.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// tree.merge(other);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// assert_eq!(tree.nearest_one(&[2.0, 3.0, 6.1], &squared_euclidean).1, 101);
+    /// ```
+    pub fn merge(&mut self, other: KdTree<A, T, K, B, IDX>) {
+        let (mut content_points, mut content_items): (Vec<[A; K]>, Vec<T>) = self
+            .iter()
+            .chain(other.iter())
+            .map(|(&point, &item)| (point, item))
+            .unzip();
+
+        self.leaves.clear();
+        self.stems.clear();
+        self.free_leaves.clear();
+        self.free_stems.clear();
+        self.root_index = self.build_balanced(&mut content_points, &mut content_items, 0);
+        self.size = self.size + other.size;
+    }
+
+    fn build_balanced(&mut self, points: &mut [[A; K]], items: &mut [T], split_dim: usize) -> IDX {
+        if points.len() <= B {
+            let mut leaf_node = LeafNode::new();
+            leaf_node.content_points[..points.len()].copy_from_slice(points);
+            leaf_node.content_items[..items.len()].copy_from_slice(items);
+            leaf_node.size = points.len().az::<IDX>();
+            self.leaves.push(leaf_node);
+
+            return (self.leaves.len() - 1).az::<IDX>() + IDX::leaf_offset();
+        }
+
+        let pivot_idx = points.len() / 2;
+        mirror_select_nth_unstable_by(points, items, pivot_idx, |a, b| unsafe {
+            a.index_unchecked(split_dim)
+                .partial_cmp(b.index_unchecked(split_dim))
+                .expect("Bulk construction sort failed.")
+        });
+        let split_val = points[pivot_idx][split_dim];
+
+        let stem_idx = self.stems.len();
+        self.stems.push(StemNode::new(
+            IDX::zero(),
+            IDX::zero(),
+            split_val,
+            split_dim,
+        ));
+
+        let next_split_dim = (split_dim + 1).rem(K);
+        let (left_points, right_points) = points.split_at_mut(pivot_idx);
+        let (left_items, right_items) = items.split_at_mut(pivot_idx);
+        let left = self.build_balanced(left_points, left_items, next_split_dim);
+        let right = self.build_balanced(right_points, right_items, next_split_dim);
+
+        let stem_node = &mut self.stems[stem_idx];
+        stem_node.left = left;
+        stem_node.right = right;
+        #[cfg(feature = "bbox-cache")]
+        self.update_stem_bbox(stem_idx.az::<IDX>());
+
+        stem_idx.az::<IDX>()
+    }
+
     /// Removes an item from the tree.
     ///
     /// The first argument specifies co-ordinates of the point where the item is located.
     /// The second argument is the integer identifier / index for the stored item.
     ///
+    /// If this empties a leaf, the leaf is taken out of the tree where possible, and is
+    /// then reused the next time a leaf needs splitting. This stops the tree from growing
+    /// without bound when items keep being added and removed in different places.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -101,27 +424,56 @@ where
     /// ```
     #[inline]
     pub fn remove(&mut self, query: &[A; K], item: T) -> usize {
-        let mut stem_idx = self.root_index;
-        let mut split_dim = 0;
         let mut removed: usize = 0;
 
-        while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
-            let Some(stem_node) = self.stems.get_mut(stem_idx.az::<usize>()) else {
-                return removed;
-            };
+        // points equal to a split value can end up on either side of it when a leaf
+        // gets split, so both sides need checking in that case. Reclaiming an empty
+        // leaf changes the shape of the tree, so the search restarts after doing so.
+        'search: loop {
+            let no_parent = <IDX as Index>::max();
+            let mut to_visit = vec![(self.root_index, no_parent, no_parent)];
 
-            stem_idx = if query[split_dim] <= stem_node.split_val {
-                stem_node.left
-            } else {
-                stem_node.right
-            };
+            while let Some((mut stem_idx, mut parent_idx, mut grandparent_idx)) = to_visit.pop() {
+                while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
+                    let Some(stem_node) = self.stems.get(stem_idx.az::<usize>()) else {
+                        return removed;
+                    };
+                    let split_dim = stem_node.split_dim;
 
-            split_dim = (split_dim + 1).rem(K);
+                    if query[split_dim] == stem_node.split_val {
+                        to_visit.push((stem_node.right, stem_idx, parent_idx));
+                    }
+
+                    grandparent_idx = parent_idx;
+                    parent_idx = stem_idx;
+                    stem_idx = if query[split_dim] <= stem_node.split_val {
+                        stem_node.left
+                    } else {
+                        stem_node.right
+                    };
+                }
+
+                let leaf_idx = stem_idx - IDX::leaf_offset();
+                let removed_from_leaf = self.remove_from_leaf(leaf_idx, query, item);
+                removed += removed_from_leaf;
+
+                if removed_from_leaf > 0
+                    && self.leaves[leaf_idx.az::<usize>()].size == IDX::zero()
+                    && parent_idx != no_parent
+                    && self.reclaim_empty_leaf(leaf_idx, parent_idx, grandparent_idx)
+                {
+                    continue 'search;
+                }
+            }
+
+            return removed;
         }
+    }
 
-        let leaf_idx = stem_idx - IDX::leaf_offset();
+    fn remove_from_leaf(&mut self, leaf_idx: IDX, query: &[A; K], item: T) -> usize {
+        let mut removed: usize = 0;
 
-        if let Some(mut leaf_node) = self.leaves.get_mut(leaf_idx.az::<usize>()) {
+        if let Some(leaf_node) = self.leaves.get_mut(leaf_idx.az::<usize>()) {
             let mut p_index = 0;
             while p_index < leaf_node.size.az::<usize>() {
                 if &leaf_node.content_points[p_index] == query
@@ -144,145 +496,975 @@ where
         removed
     }
 
-    unsafe fn split(
-        &mut self,
-        leaf_idx: IDX,
-        split_dim: usize,
-        parent_idx: IDX,
-        was_parents_left: bool,
-    ) -> IDX {
-        let orig = self.leaves.get_unchecked_mut(leaf_idx.az::<usize>());
-        let pivot_idx: IDX = (B / 2).az::<IDX>();
-
-        mirror_select_nth_unstable_by(
-            &mut orig.content_points,
-            &mut orig.content_items,
-            pivot_idx.az::<usize>(),
-            |a, b| unsafe {
-                a.get_unchecked(split_dim)
-                    .partial_cmp(b.get_unchecked(split_dim))
-                    .expect("Leaf node sort failed.")
-            },
-        );
+    /// Takes an empty leaf out of the tree if what remains under its parent can fit into a
+    /// single leaf, so that the nodes no longer needed can be reused by `split`.
+    ///
+    /// Removing a stem moves everything beneath it up a level, which would change the
+    /// axis that each stem below it splits on, so only subtrees that can be replaced by
+    /// a single leaf get removed. Any other empty leaves stay in place to be refilled.
+    /// Returns `true` if the leaf was taken out of the tree.
+    fn reclaim_empty_leaf(&mut self, leaf_idx: IDX, parent_idx: IDX, grandparent_idx: IDX) -> bool {
+        let parent_node = &self.stems[parent_idx.az::<usize>()];
+        let sibling_idx = if parent_node.left == leaf_idx + IDX::leaf_offset() {
+            parent_node.right
+        } else {
+            parent_node.left
+        };
 
-        let split_val = *orig
-            .content_points
-            .get_unchecked(pivot_idx.az::<usize>())
-            .get_unchecked(split_dim);
+        let mut sibling_stems: Vec<IDX> = Vec::new();
+        let mut sibling_leaves: Vec<IDX> = Vec::new();
+        let mut sibling_size: usize = 0;
+        let mut to_visit = vec![sibling_idx];
+        while let Some(node_idx) = to_visit.pop() {
+            if KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+                let stem_node = &self.stems[node_idx.az::<usize>()];
+                to_visit.push(stem_node.left);
+                to_visit.push(stem_node.right);
+                sibling_stems.push(node_idx);
+            } else {
+                let sibling_leaf_idx = node_idx - IDX::leaf_offset();
+                sibling_size += self.leaves[sibling_leaf_idx.az::<usize>()]
+                    .size
+                    .az::<usize>();
+                if sibling_size > B {
+                    return false;
+                }
+                sibling_leaves.push(sibling_leaf_idx);
+            }
+        }
 
-        let mut left = LeafNode::new();
-        let mut right = LeafNode::new();
+        // gather the whole of the sibling subtree into the first of its leaves
+        let target_idx = sibling_leaves[0];
+        for &other_idx in &sibling_leaves[1..] {
+            let other = &mut self.leaves[other_idx.az::<usize>()];
+            let other_size = other.size.az::<usize>();
+            let points: [[A; K]; B] = other.content_points;
+            let items: [T; B] = other.content_items;
+            other.size = IDX::zero();
 
-        if B.rem(2) == 1 {
-            left.content_points
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
-                .copy_from_slice(
-                    orig.content_points
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
-                );
-            left.content_items
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
-                .copy_from_slice(
-                    orig.content_items
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
-                );
-            left.size = pivot_idx;
+            let target = &mut self.leaves[target_idx.az::<usize>()];
+            let target_size = target.size.az::<usize>();
+            target.content_points[target_size..target_size + other_size]
+                .copy_from_slice(&points[..other_size]);
+            target.content_items[target_size..target_size + other_size]
+                .copy_from_slice(&items[..other_size]);
+            target.size = target.size + other_size.az::<IDX>();
 
-            right
-                .content_points
-                .get_unchecked_mut(..((pivot_idx + IDX::one()).az::<usize>()))
-                .copy_from_slice(
-                    orig.content_points
-                        .get_unchecked((pivot_idx.az::<usize>())..),
-                );
-            right
-                .content_items
-                .get_unchecked_mut(..((pivot_idx + IDX::one()).az::<usize>()))
-                .copy_from_slice(
-                    orig.content_items
-                        .get_unchecked((pivot_idx.az::<usize>())..),
-                );
+            self.free_leaves.push(other_idx);
+        }
 
-            right.size = (B.az::<IDX>()) - pivot_idx;
+        let target_node_idx = target_idx + IDX::leaf_offset();
+        if grandparent_idx == <IDX as Index>::max() {
+            self.root_index = target_node_idx;
         } else {
-            left.content_points
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
-                .copy_from_slice(
-                    orig.content_points
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
-                );
-            left.content_items
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
-                .copy_from_slice(
-                    orig.content_items
-                        .get_unchecked(..(pivot_idx.az::<usize>())),
-                );
-            left.size = pivot_idx;
+            let grandparent_node = &mut self.stems[grandparent_idx.az::<usize>()];
+            if grandparent_node.left == parent_idx {
+                grandparent_node.left = target_node_idx;
+            } else {
+                grandparent_node.right = target_node_idx;
+            }
+        }
 
-            right
-                .content_points
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
-                .copy_from_slice(
-                    orig.content_points
-                        .get_unchecked((pivot_idx.az::<usize>())..),
-                );
-            right
-                .content_items
-                .get_unchecked_mut(..(pivot_idx.az::<usize>()))
-                .copy_from_slice(
-                    orig.content_items
-                        .get_unchecked((pivot_idx.az::<usize>())..),
-                );
+        self.free_leaves.push(leaf_idx);
+        self.free_stems.push(parent_idx);
+        self.free_stems.extend(sibling_stems);
 
-            right.size = (B.az::<IDX>()) - pivot_idx;
-        }
+        true
+    }
 
-        *orig = left;
-        self.leaves.push(right);
+    /// Removes an item from the tree, returning the co-ordinates of every entry removed.
+    ///
+    /// Behaves the same as [`remove`](KdTree::remove), but rather than just counting the
+    /// entries removed, returns the points they were stored at. Since points are matched
+    /// using floating point equality, these may differ from `query` (e.g. `-0.0` and `0.0`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, -0.0], 100);
+    /// tree.add(&[1.0, 2.0, 5.0], 200);
+    ///
+    /// let removed = tree.remove_returning(&[1.0, 2.0, 0.0], 100);
+    ///
+    /// assert_eq!(removed, vec![[1.0, 2.0, -0.0]]);
+    /// assert!(removed[0][2].is_sign_negative());
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_returning(&mut self, query: &[A; K], item: T) -> Vec<[A; K]> {
+        let mut removed: Vec<[A; K]> = Vec::new();
 
-        self.stems.push(StemNode {
-            left: leaf_idx + IDX::leaf_offset(),
-            right: (self.leaves.len().az::<IDX>()) - IDX::one() + IDX::leaf_offset(),
-            split_val,
-        });
-        let new_stem_index: IDX = (self.stems.len().az::<IDX>()) - IDX::one();
+        // points equal to a split value can end up on either side of it when
+        // a leaf gets split, so both sides need checking in that case.
+        let mut to_visit = vec![self.root_index];
+        while let Some(mut stem_idx) = to_visit.pop() {
+            while KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
+                let Some(stem_node) = self.stems.get(stem_idx.az::<usize>()) else {
+                    return removed;
+                };
+                let split_dim = stem_node.split_dim;
 
-        if parent_idx != <IDX as Index>::max() {
-            let parent_node = self.stems.get_unchecked_mut(parent_idx.az::<usize>());
-            if was_parents_left {
-                parent_node.left = new_stem_index;
-            } else {
-                parent_node.right = new_stem_index;
+                if query[split_dim] == stem_node.split_val {
+                    to_visit.push(stem_node.right);
+                }
+
+                stem_idx = if query[split_dim] <= stem_node.split_val {
+                    stem_node.left
+                } else {
+                    stem_node.right
+                };
             }
-        } else {
-            self.root_index = new_stem_index;
+
+            self.remove_returning_from_leaf(
+                stem_idx - IDX::leaf_offset(),
+                query,
+                item,
+                &mut removed,
+            );
         }
 
-        new_stem_index
+        removed
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::float::kdtree::KdTree;
-    use rand::Rng;
+    fn remove_returning_from_leaf(
+        &mut self,
+        leaf_idx: IDX,
+        query: &[A; K],
+        item: T,
+        removed: &mut Vec<[A; K]>,
+    ) {
+        if let Some(leaf_node) = self.leaves.get_mut(leaf_idx.az::<usize>()) {
+            let mut p_index = 0;
+            while p_index < leaf_node.size.az::<usize>() {
+                if &leaf_node.content_points[p_index] == query
+                    && leaf_node.content_items[p_index] == item
+                {
+                    removed.push(leaf_node.content_points[p_index]);
 
-    type FLT = f32;
+                    leaf_node.content_points[p_index] =
+                        leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
+                    leaf_node.content_items[p_index] =
+                        leaf_node.content_items[leaf_node.size.az::<usize>() - 1];
 
-    fn n(num: FLT) -> FLT {
-        num
+                    self.size -= T::one();
+                    leaf_node.size = leaf_node.size - IDX::one();
+                } else {
+                    p_index += 1;
+                }
+            }
+        }
     }
 
-    #[test]
-    fn can_add_an_item() {
-        let mut tree: KdTree<FLT, u32, 4, 32, u32> = KdTree::new();
+    /// Moves an item from one point in the tree to another.
+    ///
+    /// Equivalent to removing `item` from `old` and then adding it at `new`, but when
+    /// both points belong in the same leaf, the stored co-ordinates are simply
+    /// overwritten. Returns `false`, leaving the tree unchanged, if `item` is not
+    /// stored at `old`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// assert!(tree.update(&[1.0, 2.0, 5.0], &[1.1, 2.1, 5.1], 100));
+    /// assert!(tree.contains(&[1.1, 2.1, 5.1], 100));
+    /// assert!(!tree.update(&[1.0, 2.0, 5.0], &[1.1, 2.1, 5.1], 100));
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn update(&mut self, old: &[A; K], new: &[A; K], item: T) -> bool {
+        let mut stem_idx = self.root_index;
+        let mut same_leaf = true;
+
+        while same_leaf && KdTree::<A, T, K, B, IDX>::is_stem_index(stem_idx) {
+            let stem_node = &mut self.stems[stem_idx.az::<usize>()];
+            let split_dim = stem_node.split_dim;
+            let old_goes_left = old[split_dim] <= stem_node.split_val;
+            let new_goes_left = new[split_dim] <= stem_node.split_val;
+
+            // if `old` is equal to the split value, it could be on either side of it
+            same_leaf = old_goes_left == new_goes_left && old[split_dim] != stem_node.split_val;
+
+            // if the point moves to another leaf, it is added back in again below, which
+            // extends the bounding boxes along its new path instead
+            #[cfg(feature = "bbox-cache")]
+            if same_leaf {
+                stem_node.extend_bbox(new);
+            }
+
+            stem_idx = if old_goes_left {
+                stem_node.left
+            } else {
+                stem_node.right
+            };
+        }
+
+        if same_leaf {
+            let leaf_node = &mut self.leaves[(stem_idx - IDX::leaf_offset()).az::<usize>()];
+            let size = leaf_node.size.az::<usize>();
+            let mut found = false;
+
+            for (point, &stored_item) in leaf_node.content_points[..size]
+                .iter_mut()
+                .zip(leaf_node.content_items[..size].iter())
+            {
+                if point == old && stored_item == item {
+                    *point = *new;
+                    found = true;
+                }
+            }
+
+            return found;
+        }
+
+        let removed = self.remove_returning(old, item);
+        for _ in 0..removed.len() {
+            self.add(new, item);
+        }
+
+        !removed.is_empty()
+    }
+
+    /// Changes the item stored at `query` from `old` to `new`, without moving it.
+    ///
+    /// This is cheaper than removing `old` and adding `new`, as the shape of the tree is
+    /// not changed. It is useful when the items are indices into another collection whose
+    /// entries have moved, for example after compacting it. If `old` is stored at `query`
+    /// more than once, only one of its entries is changed. Returns `false`, leaving the
+    /// tree unchanged, if `old` is not stored at `query`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.0, 2.0, 5.0], 101);
+    ///
+    /// assert!(tree.replace_item(&[1.0, 2.0, 5.0], 100, 200));
+    /// assert!(tree.contains(&[1.0, 2.0, 5.0], 200));
+    /// assert!(tree.contains(&[1.0, 2.0, 5.0], 101));
+    /// assert!(!tree.replace_item(&[1.0, 2.0, 5.0], 100, 200));
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn replace_item(&mut self, query: &[A; K], old: T, new: T) -> bool {
+        // points equal to a split value can end up on either side of it when
+        // a leaf gets split, so both sides need checking in that case.
+        let mut to_visit = vec![self.root_index];
+        while let Some(mut node_idx) = to_visit.pop() {
+            while KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+                let stem_node = &self.stems[node_idx.az::<usize>()];
+                let split_dim = stem_node.split_dim;
+
+                if query[split_dim] == stem_node.split_val {
+                    to_visit.push(stem_node.right);
+                }
+
+                node_idx = if query[split_dim] <= stem_node.split_val {
+                    stem_node.left
+                } else {
+                    stem_node.right
+                };
+            }
+
+            let leaf_node = &mut self.leaves[(node_idx - IDX::leaf_offset()).az::<usize>()];
+            let size = leaf_node.size.az::<usize>();
+
+            if let Some((_, stored_item)) = leaf_node.content_points[..size]
+                .iter()
+                .zip(leaf_node.content_items[..size].iter_mut())
+                .find(|(point, stored_item)| *point == query && **stored_item == old)
+            {
+                *stored_item = new;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Removes every entry for an item from the tree, wherever it is located.
+    ///
+    /// Unlike [`remove`](KdTree::remove), the co-ordinates of the item do not need to
+    /// be known. This visits every leaf in the tree, and so is O(n) rather than
+    /// O(log n). Returns the number of entries removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 100);
+    /// tree.add(&[1.0, 2.0, 5.0], 200);
+    /// assert_eq!(tree.size(), 3);
+    ///
+    /// assert_eq!(tree.remove_by_item(100), 2);
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_by_item(&mut self, item: T) -> usize {
+        let mut removed: usize = 0;
+
+        for leaf_node in self.leaves.iter_mut() {
+            let mut p_index = 0;
+            while p_index < leaf_node.size.az::<usize>() {
+                if leaf_node.content_items[p_index] == item {
+                    leaf_node.content_points[p_index] =
+                        leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
+                    leaf_node.content_items[p_index] =
+                        leaf_node.content_items[leaf_node.size.az::<usize>() - 1];
+
+                    self.size -= T::one();
+                    removed += 1;
+                    leaf_node.size = leaf_node.size - IDX::one();
+                } else {
+                    p_index += 1;
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Retains only the entries for which `pred` returns `true`, removing all others.
+    ///
+    /// `pred` is called once for every entry in the tree, with its point and item. This
+    /// visits every leaf in the tree, and so is O(n), but is much faster than removing
+    /// lots of entries one at a time. The shape of the tree is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[3.0, 4.0, 7.0], 102);
+    ///
+    /// tree.retain(|_, item| item % 2 == 0);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn retain<P>(&mut self, pred: P)
+    where
+        P: Fn(&[A; K], T) -> bool,
+    {
+        for leaf_node in self.leaves.iter_mut() {
+            let mut p_index = 0;
+            while p_index < leaf_node.size.az::<usize>() {
+                if !pred(
+                    &leaf_node.content_points[p_index],
+                    leaf_node.content_items[p_index],
+                ) {
+                    // the last entry is swapped into this slot, so p_index is not advanced
+                    // in order for that entry to be checked too
+                    leaf_node.content_points[p_index] =
+                        leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
+                    leaf_node.content_items[p_index] =
+                        leaf_node.content_items[leaf_node.size.az::<usize>() - 1];
+
+                    self.size -= T::one();
+                    leaf_node.size = leaf_node.size - IDX::one();
+                } else {
+                    p_index += 1;
+                }
+            }
+        }
+    }
+
+    /// Removes every entry whose point is inside the axis-aligned box with corners
+    /// `min` and `max`, returning the number of entries removed.
+    ///
+    /// The box is inclusive, in the same way as for [`within_bbox`](KdTree::within_bbox),
+    /// and only the leaves that could overlap it are visited. The shape of the tree is
+    /// not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[20.0, 30.0, 60.0], 102);
+    ///
+    /// assert_eq!(tree.remove_within_bbox(&[0.0, 0.0, 0.0], &[2.0, 3.0, 6.0]), 2);
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_within_bbox(&mut self, min: &[A; K], max: &[A; K]) -> usize {
+        let mut removed: usize = 0;
+
+        for leaf_idx in self.leaves_within_bbox(min, max) {
+            let leaf_node = &mut self.leaves[leaf_idx.az::<usize>()];
+            let mut p_index = 0;
+            while p_index < leaf_node.size.az::<usize>() {
+                if Self::is_within_bbox(&leaf_node.content_points[p_index], min, max) {
+                    leaf_node.content_points[p_index] =
+                        leaf_node.content_points[leaf_node.size.az::<usize>() - 1];
+                    leaf_node.content_items[p_index] =
+                        leaf_node.content_items[leaf_node.size.az::<usize>() - 1];
+
+                    self.size -= T::one();
+                    removed += 1;
+                    leaf_node.size = leaf_node.size - IDX::one();
+                } else {
+                    p_index += 1;
+                }
+            }
+        }
+
+        removed
+    }
+
+    fn add_would_split(&self, query: &[A; K]) -> bool {
+        let mut node_idx = self.root_index;
+        let tie_seed = self.stems.len() - self.free_stems.len();
+        let mut depth = 0;
+
+        while KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+            let stem_node = &self.stems[node_idx.az::<usize>()];
+            let query_val = query[stem_node.split_dim];
+            node_idx = if query_val < stem_node.split_val
+                || (query_val == stem_node.split_val && tie_goes_left(tie_seed, depth))
+            {
+                stem_node.left
+            } else {
+                stem_node.right
+            };
+            depth += 1;
+        }
+
+        self.leaves[(node_idx - IDX::leaf_offset()).az::<usize>()].size == B.az::<IDX>()
+    }
+
+    fn has_capacity_to_split(&self) -> bool {
+        let has_stem_capacity =
+            !self.free_stems.is_empty() || self.stems.len() < IDX::leaf_offset().az::<usize>();
+        let has_leaf_capacity = !self.free_leaves.is_empty()
+            || self.leaves.len() <= (<IDX as Index>::max() - IDX::leaf_offset()).az::<usize>();
+
+        has_stem_capacity && has_leaf_capacity
+    }
+
+    /// Splits a full leaf in two, replacing it with a new stem. `split_dim` is the axis
+    /// that the leaf would be split along by [`SplitDimStrategy::RoundRobin`].
+    unsafe fn split(
+        &mut self,
+        leaf_idx: IDX,
+        split_dim: usize,
+        parent_idx: IDX,
+        was_parents_left: bool,
+    ) -> IDX {
+        debug_assert!(
+            self.has_capacity_to_split(),
+            "tree has too many nodes to be indexed by its IDX type"
+        );
+
+        let split_strategy = self.split_strategy;
+        let split_dim_strategy = self.split_dim_strategy;
+        let orig = self.leaves.index_unchecked_mut(leaf_idx.az::<usize>());
+
+        let split_dim = match split_dim_strategy {
+            SplitDimStrategy::RoundRobin => split_dim,
+            SplitDimStrategy::MaxVariance => Self::max_variance_dim(orig, split_dim),
+        };
+
+        // splitting along an axis that the points all have the same value on would not
+        // separate any of them, so use the next axis along which they differ, if any
+        let first_point = *orig.content_points.index_unchecked(0);
+        let split_dim = (0..K)
+            .map(|offset| (split_dim + offset).rem(K))
+            .find(|&dim| {
+                orig.content_points
+                    .iter()
+                    .any(|point| point[dim] != first_point[dim])
+            })
+            .unwrap_or(split_dim);
+
+        let midpoint_split = match split_strategy {
+            SplitStrategy::Median => None,
+            SplitStrategy::Midpoint => Self::partition_at_midpoint(orig, split_dim),
+        };
+        let (split_val, pivot_idx) =
+            midpoint_split.unwrap_or_else(|| Self::partition_at_median(orig, split_dim));
+
+        let mut left = LeafNode::new();
+        let mut right = LeafNode::new();
+
+        left.content_points
+            .index_unchecked_mut(..pivot_idx)
+            .copy_from_slice(orig.content_points.index_unchecked(..pivot_idx));
+        left.content_items
+            .index_unchecked_mut(..pivot_idx)
+            .copy_from_slice(orig.content_items.index_unchecked(..pivot_idx));
+        left.size = pivot_idx.az::<IDX>();
+
+        right
+            .content_points
+            .index_unchecked_mut(..(B - pivot_idx))
+            .copy_from_slice(orig.content_points.index_unchecked(pivot_idx..));
+        right
+            .content_items
+            .index_unchecked_mut(..(B - pivot_idx))
+            .copy_from_slice(orig.content_items.index_unchecked(pivot_idx..));
+        right.size = (B - pivot_idx).az::<IDX>();
+
+        *orig = left;
+        let right_idx = match self.free_leaves.pop() {
+            Some(free_idx) => {
+                *self.leaves.index_unchecked_mut(free_idx.az::<usize>()) = right;
+                free_idx
+            }
+            None => {
+                self.leaves.push(right);
+                (self.leaves.len() - 1).az::<IDX>()
+            }
+        };
+
+        let new_stem = StemNode::new(
+            leaf_idx + IDX::leaf_offset(),
+            right_idx + IDX::leaf_offset(),
+            split_val,
+            split_dim,
+        );
+        let new_stem_index: IDX = match self.free_stems.pop() {
+            Some(free_idx) => {
+                *self.stems.index_unchecked_mut(free_idx.az::<usize>()) = new_stem;
+                free_idx
+            }
+            None => {
+                self.stems.push(new_stem);
+                (self.stems.len() - 1).az::<IDX>()
+            }
+        };
+
+        if parent_idx != <IDX as Index>::max() {
+            let parent_node = self.stems.index_unchecked_mut(parent_idx.az::<usize>());
+            if was_parents_left {
+                parent_node.left = new_stem_index;
+            } else {
+                parent_node.right = new_stem_index;
+            }
+        } else {
+            self.root_index = new_stem_index;
+        }
+
+        #[cfg(feature = "bbox-cache")]
+        self.update_stem_bbox(new_stem_index);
+
+        new_stem_index
+    }
+
+    /// Sets the bounding box of a stem to the smallest box containing the bounding
+    /// boxes of its child stems and the points in its child leaves.
+    #[cfg(feature = "bbox-cache")]
+    fn update_stem_bbox(&mut self, stem_idx: IDX) {
+        let stem_node = &self.stems[stem_idx.az::<usize>()];
+        let mut min = [A::infinity(); K];
+        let mut max = [A::neg_infinity(); K];
+
+        for child_idx in [stem_node.left, stem_node.right] {
+            if KdTree::<A, T, K, B, IDX>::is_stem_index(child_idx) {
+                let child = &self.stems[child_idx.az::<usize>()];
+                for dim in 0..K {
+                    min[dim] = min[dim].min(child.bbox.min[dim]);
+                    max[dim] = max[dim].max(child.bbox.max[dim]);
+                }
+            } else {
+                let leaf_node = &self.leaves[(child_idx - IDX::leaf_offset()).az::<usize>()];
+                for point in &leaf_node.content_points[..leaf_node.size.az::<usize>()] {
+                    for dim in 0..K {
+                        min[dim] = min[dim].min(point[dim]);
+                        max[dim] = max[dim].max(point[dim]);
+                    }
+                }
+            }
+        }
+
+        self.stems[stem_idx.az::<usize>()].bbox = StemBbox { min, max };
+    }
+
+    /// Recomputes the bounding box of every stem in the tree from the points stored
+    /// under it.
+    #[cfg(feature = "bbox-cache")]
+    pub(crate) fn update_all_stem_bboxes(&mut self) {
+        let mut stems_in_order: Vec<IDX> = Vec::new();
+        let mut to_visit = vec![self.root_index];
+        while let Some(node_idx) = to_visit.pop() {
+            if KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+                let stem_node = &self.stems[node_idx.az::<usize>()];
+                to_visit.push(stem_node.left);
+                to_visit.push(stem_node.right);
+                stems_in_order.push(node_idx);
+            }
+        }
+
+        // every stem is visited after its parent, so going backwards updates the
+        // children of each stem before the stem itself
+        for &stem_idx in stems_in_order.iter().rev() {
+            self.update_stem_bbox(stem_idx);
+        }
+    }
+
+    /// Returns the axis that the points in a full leaf have the greatest variance along,
+    /// or `default_dim` if no axis has a greater variance than it.
+    fn max_variance_dim(leaf: &LeafNode<A, T, K, B, IDX>, default_dim: usize) -> usize {
+        let count = A::from(B).unwrap();
+        let mut mean = [A::zero(); K];
+        for point in &leaf.content_points {
+            for dim in 0..K {
+                mean[dim] = mean[dim] + point[dim] / count;
+            }
+        }
+
+        // the variances all have the same denominator, so comparing the sums of
+        // squared deviations is enough
+        let mut sum_sq_dev = [A::zero(); K];
+        for point in &leaf.content_points {
+            for dim in 0..K {
+                let dev = point[dim] - mean[dim];
+                sum_sq_dev[dim] = sum_sq_dev[dim] + dev * dev;
+            }
+        }
+
+        (0..K).fold(default_dim, |best_dim, dim| {
+            if sum_sq_dev[dim] > sum_sq_dev[best_dim] {
+                dim
+            } else {
+                best_dim
+            }
+        })
+    }
+
+    /// Partitions a full leaf around the median of its points along `split_dim`,
+    /// returning the split value and the number of points in the left half. The
+    /// median point itself goes in the right half.
+    ///
+    /// The halves are always the same size, even when the points all have the same
+    /// value along `split_dim` and so end up on both sides of the split value. This
+    /// guarantees that splitting makes room in the leaf, whatever the data.
+    fn partition_at_median(leaf: &mut LeafNode<A, T, K, B, IDX>, split_dim: usize) -> (A, usize) {
+        let pivot_idx = B / 2;
+
+        mirror_select_nth_unstable_by(
+            &mut leaf.content_points,
+            &mut leaf.content_items,
+            pivot_idx,
+            |a, b| unsafe {
+                a.index_unchecked(split_dim)
+                    .partial_cmp(b.index_unchecked(split_dim))
+                    .expect("Leaf node sort failed.")
+            },
+        );
+
+        (leaf.content_points[pivot_idx][split_dim], pivot_idx)
+    }
+
+    /// Partitions a full leaf so that the points at or below the midpoint of their range
+    /// along `split_dim` come first, returning the midpoint and the number of points at
+    /// or below it. Returns `None` if this would leave either half empty.
+    fn partition_at_midpoint(
+        leaf: &mut LeafNode<A, T, K, B, IDX>,
+        split_dim: usize,
+    ) -> Option<(A, usize)> {
+        let (min, max) = leaf
+            .content_points
+            .iter()
+            .fold((A::infinity(), A::neg_infinity()), |(min, max), point| {
+                (min.min(point[split_dim]), max.max(point[split_dim]))
+            });
+        let split_val = min + (max - min) / (A::one() + A::one());
+
+        let mut pivot_idx = 0;
+        for idx in 0..B {
+            if leaf.content_points[idx][split_dim] <= split_val {
+                leaf.content_points.swap(idx, pivot_idx);
+                leaf.content_items.swap(idx, pivot_idx);
+                pivot_idx += 1;
+            }
+        }
+
+        if pivot_idx == 0 || pivot_idx == B {
+            return None;
+        }
+
+        Some((split_val, pivot_idx))
+    }
+}
+
+/// Subtrees with fewer points than this are built on a single thread by
+/// [`par_from_slice`](KdTree::par_from_slice)
+#[cfg(feature = "rayon")]
+const PAR_BUILD_MIN_POINTS: usize = 1 << 15;
+
+#[cfg(feature = "rayon")]
+impl<A, T, const K: usize, const B: usize, IDX> KdTree<A, T, K, B, IDX>
+where
+    A: Axis + Send,
+    T: Content + Send,
+    IDX: Index<T = IDX> + Send,
+    usize: Cast<IDX>,
+{
+    /// Creates a balanced tree containing all of the `(point, item)` pairs in `points`,
+    /// using multiple threads.
+    ///
+    /// Once the points have been split at the median, the two halves are independent,
+    /// so they are built in parallel on rayon's global thread pool, down to subtrees of
+    /// a few tens of thousands of points. The resulting tree is identical to the one
+    /// built by [`from_slice`](KdTree::from_slice).
+    ///
+    /// Requires the `rayon` crate feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let points = vec![([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)];
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTree::par_from_slice(&points);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// assert_eq!(tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean).1, 100);
+    /// ```
+    pub fn par_from_slice(points: &[([A; K], T)]) -> Self
+    where
+        usize: Cast<T>,
+    {
+        let mut tree = Self::with_capacity(0);
+        let (mut content_points, mut content_items): (Vec<[A; K]>, Vec<T>) =
+            points.iter().copied().unzip();
+
+        let (stems, leaves, root_index) =
+            Self::par_build_balanced(&mut content_points, &mut content_items, 0);
+        tree.stems = stems;
+        tree.leaves = leaves;
+        tree.root_index = root_index;
+        tree.size = points.len().az::<T>();
+
+        tree
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn par_build_balanced(
+        points: &mut [[A; K]],
+        items: &mut [T],
+        split_dim: usize,
+    ) -> (
+        Vec<StemNode<A, K, IDX>>,
+        Vec<LeafNode<A, T, K, B, IDX>>,
+        IDX,
+    ) {
+        if points.len() < PAR_BUILD_MIN_POINTS {
+            let mut subtree = Self::with_capacity(points.len());
+            subtree.leaves.clear();
+            let root_index = subtree.build_balanced(points, items, split_dim);
+
+            return (subtree.stems, subtree.leaves, root_index);
+        }
+
+        let pivot_idx = points.len() / 2;
+        mirror_select_nth_unstable_by(points, items, pivot_idx, |a, b| unsafe {
+            a.index_unchecked(split_dim)
+                .partial_cmp(b.index_unchecked(split_dim))
+                .expect("Bulk construction sort failed.")
+        });
+        let split_val = points[pivot_idx][split_dim];
+
+        let next_split_dim = (split_dim + 1).rem(K);
+        let (left_points, right_points) = points.split_at_mut(pivot_idx);
+        let (left_items, right_items) = items.split_at_mut(pivot_idx);
+        let ((left_stems, mut leaves, left), (right_stems, right_leaves, right)) = rayon::join(
+            || Self::par_build_balanced(left_points, left_items, next_split_dim),
+            || Self::par_build_balanced(right_points, right_items, next_split_dim),
+        );
+
+        // lay the nodes out in the same order as build_balanced: this stem, followed by
+        // the left subtree's stems and then the right's, with all of the left subtree's
+        // leaves before the right's
+        let left_offsets = (1, 0);
+        let right_offsets = (1 + left_stems.len(), leaves.len());
+
+        let stem = StemNode::new(
+            Self::offset_node_index(left, left_offsets),
+            Self::offset_node_index(right, right_offsets),
+            split_val,
+            split_dim,
+        );
+        #[cfg(feature = "bbox-cache")]
+        let stem = {
+            let mut stem = stem;
+            points.iter().for_each(|point| stem.extend_bbox(point));
+            stem
+        };
+
+        let mut stems = Vec::with_capacity(1 + left_stems.len() + right_stems.len());
+        stems.push(stem);
+        stems.extend(left_stems.into_iter().map(|stem| StemNode {
+            left: Self::offset_node_index(stem.left, left_offsets),
+            right: Self::offset_node_index(stem.right, left_offsets),
+            ..stem
+        }));
+        stems.extend(right_stems.into_iter().map(|stem| StemNode {
+            left: Self::offset_node_index(stem.left, right_offsets),
+            right: Self::offset_node_index(stem.right, right_offsets),
+            ..stem
+        }));
+        leaves.extend(right_leaves);
+
+        (stems, leaves, IDX::zero())
+    }
+
+    fn offset_node_index(node_idx: IDX, (stem_offset, leaf_offset): (usize, usize)) -> IDX {
+        if Self::is_stem_index(node_idx) {
+            node_idx + stem_offset.az::<IDX>()
+        } else {
+            node_idx + leaf_offset.az::<IDX>()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+
+    type FLT = f32;
+
+    fn n(num: FLT) -> FLT {
+        num
+    }
+
+    #[test]
+    fn can_add_an_item() {
+        let mut tree: KdTree<FLT, u32, 4, 32, u32> = KdTree::new();
+
+        let point: [FLT; 4] = [n(0.1f32), n(0.2f32), n(0.3f32), n(0.4f32)];
+        let item = 123;
+
+        tree.add(&point, item);
+
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn try_add_rejects_points_with_nan_coordinates() {
+        use crate::error::{AddError, NanError};
+        use crate::float::distance::squared_euclidean;
+
+        let mut tree: KdTree<FLT, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..10 {
+            assert_eq!(tree.try_add(&[i as FLT, i as FLT], i), Ok(()));
+        }
+        let before = tree.clone();
+
+        assert_eq!(
+            tree.try_add(&[FLT::NAN, 1.0], 100),
+            Err(AddError::Nan(NanError))
+        );
+        assert_eq!(
+            tree.try_add(&[1.0, FLT::NAN], 101),
+            Err(AddError::Nan(NanError))
+        );
+
+        assert_eq!(tree, before);
+        assert_eq!(tree.nearest_one(&[3.1, 3.1], &squared_euclidean).1, 3);
+    }
+
+    #[test]
+    fn try_add_returns_an_error_once_idx_runs_out_of_nodes() {
+        use crate::error::{AddError, CapacityExceeded};
+        use crate::float::distance::squared_euclidean;
+
+        let mut tree: KdTree<FLT, u32, 2, 4, u8> = KdTree::new();
+
+        let mut added = Vec::new();
+        let err = loop {
+            let point = rand::random::<[FLT; 2]>();
+            let item = added.len() as u32;
+            match tree.try_add(&point, item) {
+                Ok(()) => added.push((point, item)),
+                Err(err) => break err,
+            }
+        };
+
+        assert_eq!(err, AddError::CapacityExceeded(CapacityExceeded));
+        assert_eq!(tree.size(), added.len() as u32);
+        assert_eq!(tree.stem_count(), 127);
+
+        // everything that was added can still be found
+        for (point, item) in &added {
+            assert_eq!(tree.nearest_one(point, &squared_euclidean), (0.0, *item));
+        }
+    }
+
+    #[cfg(feature = "ordered-float")]
+    #[test]
+    fn trees_of_not_nan_points_give_the_same_results_as_trees_of_float_points() {
+        use crate::float::distance::squared_euclidean;
+        use ordered_float::NotNan;
+
+        const TREE_SIZE: usize = 1000;
+
+        let content_to_add: Vec<([f64; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[f64; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<NotNan<f64>, u32, 3, 8, u32> = KdTree::new();
+        let mut expected: KdTree<f64, u32, 3, 8, u32> = KdTree::new();
+        for (point, item) in &content_to_add {
+            tree.add(&point.map(|val| NotNan::new(val).unwrap()), *item);
+            expected.add(point, *item);
+        }
 
-        let point: [FLT; 4] = [n(0.1f32), n(0.2f32), n(0.3f32), n(0.4f32)];
-        let item = 123;
+        assert_eq!(tree.size(), expected.size());
+        assert_eq!(tree.height(), expected.height());
 
-        tree.add(&point, item);
+        for _ in 0..100 {
+            let query_point = rand::random::<[f64; 3]>();
+            let not_nan_query_point = query_point.map(|val| NotNan::new(val).unwrap());
 
-        assert_eq!(tree.size(), 1);
+            let (dist, item) = tree.nearest_one(&not_nan_query_point, &squared_euclidean);
+            assert_eq!(
+                (dist.into_inner(), item),
+                expected.nearest_one(&query_point, &squared_euclidean)
+            );
+
+            let within: Vec<(f64, u32)> = tree
+                .within(&not_nan_query_point, NotNan::new(0.05).unwrap(), &squared_euclidean)
+                .into_iter()
+                .map(|neighbour| (neighbour.distance.into_inner(), neighbour.item))
+                .collect();
+            let expected_within: Vec<(f64, u32)> = expected
+                .within(&query_point, 0.05, &squared_euclidean)
+                .into_iter()
+                .map(|neighbour| (neighbour.distance, neighbour.item))
+                .collect();
+            assert_eq!(within, expected_within);
+        }
     }
 
     #[test]
@@ -350,6 +1532,70 @@ mod tests {
         assert_eq!(tree.size(), 15);
     }
 
+    #[test]
+    fn removes_every_copy_of_a_repeated_entry() {
+        let mut tree: KdTree<FLT, u32, 2, 32, u32> = KdTree::new();
+        let point = [n(0.5f32), n(0.5f32)];
+
+        tree.add(&[n(0.1f32), n(0.2f32)], 1);
+        for _ in 0..3 {
+            tree.add(&point, 2);
+        }
+        tree.add(&[n(0.9f32), n(0.8f32)], 3);
+
+        assert_eq!(tree.remove(&point, 2), 3);
+        assert_eq!(tree.size(), 2);
+        assert!(!tree.contains(&point, 2));
+        assert_eq!(tree.remove(&point, 2), 0);
+    }
+
+    #[test]
+    fn removes_copies_of_an_entry_that_were_split_across_leaves() {
+        let mut tree: KdTree<FLT, u32, 2, 4, u32> = KdTree::new();
+        let point = [n(0.5f32), n(0.5f32)];
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([FLT; 2], u32)> = (0..100)
+            .map(|item| ([rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)], item))
+            .collect();
+        for (point_to_add, item) in &content_to_add {
+            tree.add(point_to_add, *item);
+            tree.add(&point, 1000);
+        }
+
+        assert_eq!(tree.remove(&point, 1000), 100);
+        assert_eq!(tree.size(), 100);
+        assert!(!tree.contains(&point, 1000));
+
+        for (point_to_remove, item) in &content_to_add {
+            assert_eq!(tree.remove(point_to_remove, *item), 1);
+        }
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn replaces_items_that_share_a_point_one_at_a_time() {
+        let mut tree: KdTree<FLT, u32, 2, 4, u32> = KdTree::new();
+        let point = [n(0.5f32), n(0.5f32)];
+
+        let mut rng = rand::thread_rng();
+        for item in 0..100 {
+            tree.add(&[rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)], item);
+            tree.add(&point, 1000 + item);
+        }
+
+        for item in 0..100 {
+            assert!(tree.replace_item(&point, 1000 + item, 2000 + item));
+            assert!(!tree.replace_item(&point, 1000 + item, 2000 + item));
+        }
+        assert!(!tree.replace_item(&point, 0, 1));
+        assert_eq!(tree.size(), 200);
+
+        let mut items = tree.get(&point);
+        items.sort();
+        assert_eq!(items, (2000..2100).collect::<Vec<_>>());
+    }
+
     #[test]
     fn can_add_shitloads_of_points() {
         let mut tree: KdTree<FLT, u32, 4, 5, u32> = KdTree::new();
@@ -440,4 +1686,601 @@ mod tests {
 
         assert_eq!(tree.remove(&pts[0], 0), 1);
     }
+
+    #[test]
+    fn can_remove_an_item_by_item_alone() {
+        let mut tree: KdTree<FLT, u32, 4, 4, u32> = KdTree::new();
+
+        let mut rng = rand::thread_rng();
+        for i in 0..100 {
+            let point = [
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+            ];
+
+            tree.add(&point, i % 10);
+        }
+
+        assert_eq!(tree.size(), 100);
+
+        let removed = tree.remove_by_item(3);
+
+        assert_eq!(removed, 10);
+        assert_eq!(tree.size(), 90);
+        assert_eq!(tree.remove_by_item(3), 0);
+        assert_eq!(tree.size(), 90);
+        assert!(tree
+            .leaves
+            .iter()
+            .all(|leaf| leaf.content_items[..leaf.size as usize]
+                .iter()
+                .all(|&item| item != 3)));
+    }
+
+    #[test]
+    fn can_remove_an_item_returning_its_coordinates() {
+        let mut tree: KdTree<FLT, u32, 4, 4, u32> = KdTree::new();
+
+        let mut rng = rand::thread_rng();
+        let points: Vec<[FLT; 4]> = (0..100).map(|_| rng.gen::<[FLT; 4]>()).collect();
+        for (i, point) in points.iter().enumerate() {
+            tree.add(point, i as u32);
+        }
+        tree.add(&points[42], 1000);
+        tree.add(&points[42], 42);
+
+        let removed = tree.remove_returning(&points[42], 42);
+
+        assert_eq!(removed, vec![points[42], points[42]]);
+        assert_eq!(tree.size(), 100);
+        assert!(tree.remove_returning(&points[42], 42).is_empty());
+        assert_eq!(tree.remove_returning(&points[42], 1000), vec![points[42]]);
+        assert_eq!(tree.size(), 99);
+    }
+
+    #[test]
+    fn can_build_a_balanced_tree_from_a_slice() {
+        use crate::float::distance::squared_euclidean;
+
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        let content_to_add: Vec<([FLT; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand::random::<([FLT; 4], u32)>())
+            .collect();
+
+        let mut added_tree: KdTree<FLT, u32, 4, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| added_tree.add(point, *content));
+
+        let bulk_tree: KdTree<FLT, u32, 4, 8, u32> = KdTree::from_slice(&content_to_add);
+
+        assert_eq!(bulk_tree.size(), TREE_SIZE as u32);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let query_point = rng.gen::<[FLT; 4]>();
+
+            assert_eq!(
+                bulk_tree.nearest_one(&query_point, &squared_euclidean).0,
+                added_tree.nearest_one(&query_point, &squared_euclidean).0
+            );
+
+            let bulk_dists: Vec<_> = bulk_tree
+                .nearest_n(&query_point, 5, &squared_euclidean)
+                .into_iter()
+                .map(|n| n.distance)
+                .collect();
+            let added_dists: Vec<_> = added_tree
+                .nearest_n(&query_point, 5, &squared_euclidean)
+                .into_iter()
+                .map(|n| n.distance)
+                .collect();
+            assert_eq!(bulk_dists, added_dists);
+        }
+    }
+
+    #[test]
+    fn can_build_a_tree_from_an_empty_slice() {
+        let mut tree: KdTree<FLT, u32, 4, 8, u32> = KdTree::from_slice(&[]);
+
+        assert_eq!(tree.size(), 0);
+
+        tree.add(&[0.1, 0.2, 0.3, 0.4], 1);
+
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn can_rebalance_a_skewed_tree() {
+        use crate::float::distance::squared_euclidean;
+
+        const TREE_SIZE: usize = 1000;
+
+        let mut tree: KdTree<FLT, u32, 2, 4, u32> = KdTree::new();
+        for i in 0..TREE_SIZE {
+            tree.add(&[i as FLT, i as FLT], i as u32);
+        }
+        let skewed = tree.clone();
+        let skewed_height = tree.height();
+
+        tree.rebalance();
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert!(tree.height() < skewed_height);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let query_point = [
+                rng.gen_range(-10.0..TREE_SIZE as FLT + 10.0),
+                rng.gen_range(-10.0..TREE_SIZE as FLT + 10.0),
+            ];
+
+            // points on the diagonal can be equidistant from the query, so compare distances
+            assert_eq!(
+                tree.nearest_one(&query_point, &squared_euclidean).0,
+                skewed.nearest_one(&query_point, &squared_euclidean).0
+            );
+        }
+    }
+
+    #[test]
+    fn dumped_points_can_be_loaded_into_an_equivalent_tree() {
+        use crate::float::distance::squared_euclidean;
+
+        const TREE_SIZE: usize = 1000;
+
+        let mut tree: KdTree<FLT, u32, 3, 8, u32> = KdTree::new();
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[FLT; 3]>(), item as u32);
+        }
+        for item in (0..TREE_SIZE).step_by(3) {
+            tree.remove_by_item(item as u32);
+        }
+
+        let mut points = tree.dump_points();
+        let restored: KdTree<FLT, u32, 3, 8, u32> = KdTree::load_points(points.clone());
+
+        assert_eq!(restored.size(), tree.size());
+
+        let mut restored_points = restored.dump_points();
+        points.sort_by_key(|&(_, item)| item);
+        restored_points.sort_by_key(|&(_, item)| item);
+        assert_eq!(restored_points, points);
+
+        for _ in 0..100 {
+            let query_point = rand::random::<[FLT; 3]>();
+
+            assert_eq!(
+                restored.nearest_one(&query_point, &squared_euclidean),
+                tree.nearest_one(&query_point, &squared_euclidean)
+            );
+            assert_eq!(
+                restored.nearest_n_vec(&query_point, 10, &squared_euclidean),
+                tree.nearest_n_vec(&query_point, 10, &squared_euclidean)
+            );
+        }
+
+        let empty: KdTree<FLT, u32, 3, 8, u32> = KdTree::load_points(Vec::new());
+        assert_eq!(empty.size(), 0);
+        assert!(empty.dump_points().is_empty());
+    }
+
+    #[test]
+    fn can_update_an_item_within_a_leaf() {
+        let mut tree: KdTree<FLT, u32, 2, 32, u32> = KdTree::new();
+
+        tree.add(&[0.1, 0.1], 1);
+        tree.add(&[0.9, 0.9], 2);
+
+        assert!(tree.update(&[0.1, 0.1], &[0.2, 0.2], 1));
+
+        assert_eq!(tree.size(), 2);
+        assert!(tree.contains(&[0.2, 0.2], 1));
+        assert!(!tree.contains(&[0.1, 0.1], 1));
+        assert!(!tree.update(&[0.1, 0.1], &[0.2, 0.2], 1));
+        assert!(!tree.update(&[0.9, 0.9], &[0.2, 0.2], 1));
+    }
+
+    #[test]
+    fn can_update_an_item_across_a_split() {
+        use crate::float::distance::squared_euclidean;
+
+        let mut tree: KdTree<FLT, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..100u32 {
+            tree.add(&[i as FLT / 100.0, i as FLT / 100.0], i);
+        }
+        let root_split_val = tree.stems[tree.root_index as usize].split_val;
+        assert!(root_split_val > 0.0);
+        assert!(root_split_val < 0.955);
+
+        assert!(tree.update(&[0.0, 0.0], &[0.955, 0.955], 0));
+
+        assert_eq!(tree.size(), 100);
+        assert!(!tree.contains(&[0.0, 0.0], 0));
+        assert!(tree.contains(&[0.955, 0.955], 0));
+        assert_eq!(tree.nearest_one(&[0.956, 0.956], &squared_euclidean).1, 0);
+        assert_eq!(tree.nearest_one(&[0.0, 0.0], &squared_euclidean).1, 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_slice_builds_the_same_tree_as_from_slice() {
+        for tree_size in [0, 1, 1_000, 100_000, 300_001] {
+            let content_to_add: Vec<([FLT; 3], u32)> = (0..tree_size)
+                .map(|item| (rand::random::<[FLT; 3]>(), item as u32))
+                .collect();
+
+            let expected: KdTree<FLT, u32, 3, 32, u32> = KdTree::from_slice(&content_to_add);
+            let tree: KdTree<FLT, u32, 3, 32, u32> = KdTree::par_from_slice(&content_to_add);
+
+            assert!(tree == expected);
+            assert_eq!(tree.size(), tree_size as u32);
+        }
+    }
+
+    #[test]
+    fn removing_items_reclaims_empty_leaves_for_reuse() {
+        const WINDOW_SIZE: usize = 1_000;
+        const NUM_CYCLES: usize = 1_000_000;
+
+        // a cluster of points that drifts around the unit square, so that leaves in the
+        // areas it has moved away from are emptied and need reclaiming
+        let mut rng = rand::thread_rng();
+        let mut window: std::collections::VecDeque<([FLT; 2], u32)> =
+            std::collections::VecDeque::with_capacity(WINDOW_SIZE + 1);
+        let mut tree: KdTree<FLT, u32, 2, 32, u32> = KdTree::new();
+        let mut max_leaves = 0;
+
+        for i in 0..NUM_CYCLES {
+            let angle = i as FLT / 20_000.0;
+            let centre = [0.5 + 0.4 * angle.cos(), 0.5 + 0.4 * angle.sin()];
+            let point = [
+                centre[0] + rng.gen_range(-0.02..0.02),
+                centre[1] + rng.gen_range(-0.02..0.02),
+            ];
+
+            tree.add(&point, i as u32);
+            window.push_back((point, i as u32));
+
+            if window.len() > WINDOW_SIZE {
+                let (point, item) = window.pop_front().unwrap();
+                assert_eq!(tree.remove(&point, item), 1);
+            }
+
+            max_leaves = max_leaves.max(tree.leaves.len());
+        }
+
+        assert_eq!(tree.size(), WINDOW_SIZE as u32);
+        // without reclaiming leaves, this ends up with several thousand of them
+        assert!(max_leaves < WINDOW_SIZE / 2);
+
+        let mut items: Vec<u32> = tree.iter().map(|(_, &item)| item).collect();
+        items.sort();
+        let expected: Vec<u32> = window.iter().map(|&(_, item)| item).collect();
+        assert_eq!(items, expected);
+
+        for (point, item) in &window {
+            assert!(tree.contains(point, *item));
+        }
+    }
+
+    #[test]
+    fn can_remove_items_within_bbox() {
+        let mut tree: KdTree<FLT, u32, 2, 4, u32> = KdTree::new();
+
+        let mut content_to_add: Vec<([FLT; 2], u32)> = Vec::new();
+        for x in 0..20 {
+            for y in 0..20 {
+                content_to_add.push(([x as FLT, y as FLT], x * 20 + y));
+            }
+        }
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let min = [5.0, 3.0];
+        let max = [12.0, 9.0];
+
+        let removed = tree.remove_within_bbox(&min, &max);
+
+        assert_eq!(removed, 8 * 7);
+        assert_eq!(tree.size(), 400 - 8 * 7);
+
+        let mut expected: Vec<u32> = content_to_add
+            .iter()
+            .filter(|(p, _)| {
+                !(min[0] <= p[0] && p[0] <= max[0] && min[1] <= p[1] && p[1] <= max[1])
+            })
+            .map(|(_, item)| *item)
+            .collect();
+        expected.sort();
+
+        let mut remaining: Vec<u32> = tree.iter().map(|(_, &item)| item).collect();
+        remaining.sort();
+
+        assert_eq!(remaining, expected);
+        assert!(tree.within_bbox(&min, &max).is_empty());
+    }
+
+    #[test]
+    fn can_retain_only_matching_items() {
+        let mut tree: KdTree<FLT, u32, 4, 4, u32> = KdTree::new();
+
+        let mut rng = rand::thread_rng();
+        for i in 0..100 {
+            let point = [
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+                n(rng.gen_range(0f32..0.99998f32)),
+            ];
+
+            tree.add(&point, i);
+        }
+
+        tree.retain(|_, item| item % 2 == 0);
+
+        assert_eq!(tree.size(), 50);
+
+        let mut items: Vec<u32> = tree.iter().map(|(_, &item)| item).collect();
+        items.sort();
+        assert_eq!(items, (0..100).step_by(2).collect::<Vec<_>>());
+
+        tree.retain(|_, _| false);
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.iter().count(), 0);
+    }
+
+    #[test]
+    fn can_merge_two_trees() {
+        use crate::float::distance::squared_euclidean;
+
+        const TREE_SIZE: usize = 1000;
+
+        let content_to_add: Vec<([FLT; 2], u32)> = (0..TREE_SIZE * 2)
+            .map(|item| (rand::random::<[FLT; 2]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<FLT, u32, 2, 8, u32> = KdTree::new();
+        let mut other: KdTree<FLT, u32, 2, 8, u32> = KdTree::new();
+        let mut expected: KdTree<FLT, u32, 2, 8, u32> = KdTree::new();
+        for (idx, (point, item)) in content_to_add.iter().enumerate() {
+            if idx < TREE_SIZE {
+                tree.add(point, *item);
+            } else {
+                other.add(point, *item);
+            }
+            expected.add(point, *item);
+        }
+
+        tree.merge(other);
+
+        assert_eq!(tree.size(), (TREE_SIZE * 2) as u32);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let query_point = [rng.gen::<FLT>(), rng.gen::<FLT>()];
+
+            assert_eq!(
+                tree.nearest_one(&query_point, &squared_euclidean).0,
+                expected.nearest_one(&query_point, &squared_euclidean).0
+            );
+        }
+    }
+
+    #[test]
+    fn add_many_matches_adding_items_individually() {
+        use crate::float::distance::squared_euclidean;
+
+        const TREE_SIZE: usize = 1000;
+
+        let content_to_add: Vec<([FLT; 2], u32)> = (0..TREE_SIZE * 2)
+            .map(|item| (rand::random::<[FLT; 2]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<FLT, u32, 2, 8, u32> = KdTree::new();
+        let mut expected: KdTree<FLT, u32, 2, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| expected.add(point, *item));
+
+        tree.add_many(&content_to_add[..TREE_SIZE]);
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+
+        tree.add_many(&content_to_add[TREE_SIZE..]);
+        assert_eq!(tree.size(), expected.size());
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let query_point = [rng.gen::<FLT>(), rng.gen::<FLT>()];
+
+            assert_eq!(
+                tree.nearest_one(&query_point, &squared_euclidean).0,
+                expected.nearest_one(&query_point, &squared_euclidean).0
+            );
+            assert_eq!(
+                tree.within(&query_point, 0.01, &squared_euclidean).len(),
+                expected
+                    .within(&query_point, 0.01, &squared_euclidean)
+                    .len()
+            );
+        }
+    }
+
+    #[test]
+    fn queries_are_correct_for_every_split_strategy_on_clustered_data() {
+        use crate::float::builder::KdTreeBuilder;
+        use crate::float::distance::squared_euclidean;
+        use crate::float::kdtree::SplitStrategy;
+
+        const NUM_CLUSTERS: usize = 10;
+        const CLUSTER_SIZE: usize = 500;
+
+        let mut rng = rand::thread_rng();
+        let centres: Vec<[FLT; 2]> = (0..NUM_CLUSTERS)
+            .map(|_| [rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)])
+            .collect();
+        let content_to_add: Vec<([FLT; 2], u32)> = (0..NUM_CLUSTERS * CLUSTER_SIZE)
+            .map(|item| {
+                let centre = centres[item % NUM_CLUSTERS];
+                let point = [
+                    centre[0] + rng.gen_range(-0.5..0.5),
+                    centre[1] + rng.gen_range(-0.5..0.5),
+                ];
+                (point, item as u32)
+            })
+            .collect();
+
+        for split_strategy in [SplitStrategy::Median, SplitStrategy::Midpoint] {
+            let mut tree: KdTree<FLT, u32, 2, 8, u32> = KdTreeBuilder::new()
+                .with_split_strategy(split_strategy)
+                .build();
+            content_to_add
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+
+            assert_eq!(tree.split_strategy(), split_strategy);
+            assert_eq!(tree.size(), content_to_add.len() as u32);
+
+            for _ in 0..100 {
+                let query_point = [rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)];
+
+                let expected_dist = content_to_add
+                    .iter()
+                    .map(|(p, _)| squared_euclidean(&query_point, p))
+                    .fold(FLT::INFINITY, FLT::min);
+                let expected_within = content_to_add
+                    .iter()
+                    .filter(|(p, _)| squared_euclidean(&query_point, p) <= 4.0)
+                    .count();
+
+                assert_eq!(
+                    tree.nearest_one(&query_point, &squared_euclidean).0,
+                    expected_dist
+                );
+                assert_eq!(
+                    tree.within(&query_point, 4.0, &squared_euclidean).len(),
+                    expected_within
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn max_variance_splits_along_the_widest_axis() {
+        use crate::float::builder::KdTreeBuilder;
+        use crate::float::distance::squared_euclidean;
+        use crate::float::kdtree::SplitDimStrategy;
+
+        const TREE_SIZE: usize = 5000;
+
+        // spread a thousand times further along the first axis than the second
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([FLT; 2], u32)> = (0..TREE_SIZE)
+            .map(|item| {
+                let point = [rng.gen_range(0.0..1000.0), rng.gen_range(0.0..1.0)];
+                (point, item as u32)
+            })
+            .collect();
+
+        for split_dim_strategy in [SplitDimStrategy::RoundRobin, SplitDimStrategy::MaxVariance] {
+            let mut tree: KdTree<FLT, u32, 2, 8, u32> = KdTreeBuilder::new()
+                .with_split_dim_strategy(split_dim_strategy)
+                .build();
+            content_to_add
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+
+            assert_eq!(tree.split_dim_strategy(), split_dim_strategy);
+            assert_eq!(tree.size(), TREE_SIZE as u32);
+
+            let first_axis_stems = tree.stems.iter().filter(|stem| stem.split_dim == 0).count();
+            if split_dim_strategy == SplitDimStrategy::MaxVariance {
+                assert!(first_axis_stems > tree.stems.len() * 3 / 4);
+            }
+
+            for _ in 0..100 {
+                let query_point = [rng.gen_range(0.0..1000.0), rng.gen_range(0.0..1.0)];
+
+                let expected_dist = content_to_add
+                    .iter()
+                    .map(|(p, _)| squared_euclidean(&query_point, p))
+                    .fold(FLT::INFINITY, FLT::min);
+                let expected_within = content_to_add
+                    .iter()
+                    .filter(|(p, _)| squared_euclidean(&query_point, p) <= 25.0)
+                    .count();
+
+                assert_eq!(
+                    tree.nearest_one(&query_point, &squared_euclidean).0,
+                    expected_dist
+                );
+                assert_eq!(
+                    tree.within(&query_point, 25.0, &squared_euclidean).len(),
+                    expected_within
+                );
+            }
+
+            for (point, item) in &content_to_add {
+                assert_eq!(tree.remove(point, *item), 1);
+            }
+            assert_eq!(tree.size(), 0);
+        }
+    }
+
+    #[test]
+    fn can_add_many_points_with_identical_coordinates() {
+        use crate::float::builder::KdTreeBuilder;
+        use crate::float::distance::squared_euclidean;
+        use crate::float::kdtree::SplitStrategy;
+
+        const NUM_DUPLICATES: u32 = 100_000;
+        let point: [FLT; 3] = [1.0, 2.0, 3.0];
+
+        for split_strategy in [SplitStrategy::Median, SplitStrategy::Midpoint] {
+            let mut tree: KdTree<FLT, u32, 3, 32, u32> = KdTreeBuilder::new()
+                .with_split_strategy(split_strategy)
+                .build();
+            for item in 0..NUM_DUPLICATES {
+                tree.add(&point, item);
+            }
+            tree.add(&[5.0, 5.0, 5.0], NUM_DUPLICATES);
+
+            assert_eq!(tree.size(), NUM_DUPLICATES + 1);
+            // a balanced tree with half-full leaves would be around 13 levels deep,
+            // whereas a chain of stems would be thousands of levels deep
+            assert!(tree.height() <= 40, "height was {}", tree.height());
+            assert_eq!(tree.nearest_one(&point, &squared_euclidean), (0.0, 0));
+            assert_eq!(
+                tree.nearest_one(&[4.0, 5.0, 5.0], &squared_euclidean),
+                (1.0, NUM_DUPLICATES)
+            );
+
+            let mut found: Vec<u32> = tree
+                .within(&point, 0.1, &squared_euclidean)
+                .into_iter()
+                .map(|neighbour| neighbour.item)
+                .collect();
+            found.sort();
+            assert_eq!(found, (0..NUM_DUPLICATES).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn splits_along_another_axis_when_points_share_a_co_ordinate() {
+        const TREE_SIZE: u32 = 10_000;
+
+        let mut tree: KdTree<FLT, u32, 2, 32, u32> = KdTree::new();
+        for item in 0..TREE_SIZE {
+            tree.add(&[0.0, rand::random()], item);
+        }
+
+        assert!(tree.stems.iter().all(|stem| stem.split_dim == 1));
+        assert!(tree.height() <= 20, "height was {}", tree.height());
+    }
 }
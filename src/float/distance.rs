@@ -1,15 +1,102 @@
 //! Contains a selection of distance metrics that can be chosen from to measure the distance
 //! between two points stored inside the tree.
 
-// #[cfg(any(target_arch = "x86_64"))]
-// use std::arch::x86_64::*;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use core::arch::x86_64::*;
 
 use crate::float::kdtree::Axis;
 
+/// Implemented by distance metrics that can be used to query the tree.
+///
+/// As well as measuring the distance between two points, a metric needs to be
+/// able to provide a lower bound on the distance from the query point to anything
+/// on the far side of a split plane, so that queries can skip the parts of the tree
+/// that cannot contain anything closer than what has already been found. This bound
+/// is built up one axis at a time, by [`combine`](DistanceMetric::combine)-ing the
+/// [`axis_dist`](DistanceMetric::axis_dist) of each split plane crossed into a
+/// running total.
+///
+/// Any function or closure with the signature `Fn(&[A; K], &[A; K]) -> A`, such as
+/// [`squared_euclidean`], is a `DistanceMetric` that bounds each axis using the
+/// squared distance along it. This is only correct for metrics that behave like squared
+/// euclidean distance. Other metrics, such as [`Chebyshev`], implement this trait
+/// themselves and should be passed to queries instead of their distance function.
+pub trait DistanceMetric<A: Axis, const K: usize> {
+    /// Returns the distance between two points.
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A;
+
+    /// Returns the contribution made to the distance between two points by the difference
+    /// between `a` and `b` along axis `dim` alone.
+    fn axis_dist(&self, a: A, b: A, dim: usize) -> A;
+
+    /// Adds the contribution of one axis, as returned by `axis_dist`, to a running
+    /// distance bound.
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd + axis_dist
+    }
+
+    /// Removes the contribution of one axis, previously added by `combine`, from a
+    /// running distance bound.
+    #[inline]
+    fn uncombine(&self, rd: A, axis_dist: A) -> A {
+        rd - axis_dist
+    }
+}
+
+/// Implemented by distance metrics that can be used to query a
+/// [`DynKdTree`](crate::float::dyn_kdtree::DynKdTree), whose points are slices.
+///
+/// This is the counterpart of [`DistanceMetric`] for points whose number of dimensions
+/// is only known at runtime, and bounds the distance to the far side of a split plane in
+/// the same way. Unlike `DistanceMetric`, it is not implemented for plain functions, as
+/// nothing about a function says how to bound its distances. [`SquaredEuclidean`],
+/// [`Manhattan`] and [`Chebyshev`] implement it, as does [`SliceDistance`], which
+/// bounds each axis using the squared distance along it.
+pub trait DynDistanceMetric<A: Axis> {
+    /// Returns the distance between two points.
+    fn dist(&self, a: &[A], b: &[A]) -> A;
+
+    /// Returns the contribution made to the distance between two points by the difference
+    /// between `a` and `b` along axis `dim` alone.
+    fn axis_dist(&self, a: A, b: A, dim: usize) -> A;
+
+    /// Adds the contribution of one axis, as returned by `axis_dist`, to a running
+    /// distance bound.
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd + axis_dist
+    }
+
+    /// Removes the contribution of one axis, previously added by `combine`, from a
+    /// running distance bound.
+    #[inline]
+    fn uncombine(&self, rd: A, axis_dist: A) -> A {
+        rd - axis_dist
+    }
+}
+
+impl<A: Axis, const K: usize, F> DistanceMetric<A, K> for F
+where
+    F: Fn(&[A; K], &[A; K]) -> A,
+{
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        self(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b) * (a - b)
+    }
+}
+
 /// Returns the squared euclidean distance between two points.
 ///
 /// Faster than Euclidean distance due to not needing a square root, but still
-/// preserves the same distance ordering as with Euclidean distance.
+/// preserves the same distance ordering as with Euclidean distance. Bear in mind that
+/// distances returned by queries using this are squared too, including the radius
+/// passed to queries such as `within`. See [`euclidean`].
 ///
 /// # Examples
 ///
@@ -24,13 +111,329 @@ pub fn squared_euclidean<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
     a.iter()
         .zip(b.iter())
         .map(|(&a_val, &b_val)| (a_val - b_val) * (a_val - b_val))
-        .fold(A::zero(), std::ops::Add::add)
+        .fold(A::zero(), core::ops::Add::add)
+}
+
+/// Returns the euclidean distance between two points.
+///
+/// This is the square root of [`squared_euclidean`], and so is slower to compute while
+/// ordering points in exactly the same way. Don't pass it to queries: the parts of the
+/// tree that get skipped would be determined incorrectly. To get true euclidean
+/// distances from a query, use [`nearest_one_euclidean`](crate::float::kdtree::KdTree::nearest_one_euclidean)
+/// or [`nearest_n_euclidean`](crate::float::kdtree::KdTree::nearest_n_euclidean),
+/// which search using squared distances and only take the square root of the results.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::euclidean;
+///
+/// assert_eq!(0f32, euclidean(&[0f32, 0f32], &[0f32, 0f32]));
+/// assert_eq!(1f32, euclidean(&[0f32, 0f32], &[1f32, 0f32]));
+/// assert_eq!(5f32, euclidean(&[0f32, 0f32], &[3f32, 4f32]));
+/// ```
+pub fn euclidean<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    squared_euclidean(a, b).sqrt()
+}
+
+/// Returns the squared euclidean distance between two points given as slices.
+///
+/// To query a [`DynKdTree`](crate::float::dyn_kdtree::DynKdTree), pass [`SquaredEuclidean`].
+///
+/// Both slices should be the same length; any extra entries in the longer one are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::squared_euclidean_dyn;
+///
+/// assert_eq!(0f32, squared_euclidean_dyn(&[0f32, 0f32], &[0f32, 0f32]));
+/// assert_eq!(1f32, squared_euclidean_dyn(&[0f32, 0f32], &[1f32, 0f32]));
+/// assert_eq!(3f32, squared_euclidean_dyn(&[0f32, 0f32, 0f32], &[1f32, 1f32, 1f32]));
+/// ```
+pub fn squared_euclidean_dyn<A: Axis>(a: &[A], b: &[A]) -> A {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&a_val, &b_val)| (a_val - b_val) * (a_val - b_val))
+        .fold(A::zero(), core::ops::Add::add)
+}
+
+/// Adapts a distance function that takes slices, `Fn(&[A], &[A]) -> A`, such as
+/// [`squared_euclidean_dyn`], into a [`DistanceMetric`] for use in queries on trees with a
+/// fixed number of dimensions, or a [`DynDistanceMetric`] for use with
+/// [`DynKdTree`](crate::float::dyn_kdtree::DynKdTree).
+///
+/// Like functions that take arrays, each axis is bounded using the squared distance along
+/// it, so this is only correct for metrics that behave like squared euclidean distance.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::{squared_euclidean_dyn, SliceDistance};
+/// use kiddo::float::kdtree::KdTree;
+///
+/// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+///
+/// tree.add(&[1.0, 2.0, 5.0], 100);
+/// tree.add(&[2.0, 3.0, 6.0], 101);
+///
+/// let nearest = tree.nearest_one(&[1.0, 2.0, 5.0], &SliceDistance(squared_euclidean_dyn));
+///
+/// assert_eq!(nearest, (0.0, 100));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SliceDistance<F>(pub F);
+
+impl<A: Axis, const K: usize, F> DistanceMetric<A, K> for SliceDistance<F>
+where
+    F: Fn(&[A], &[A]) -> A,
+{
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        (self.0)(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b) * (a - b)
+    }
+}
+
+impl<A: Axis, F> DynDistanceMetric<A> for SliceDistance<F>
+where
+    F: Fn(&[A], &[A]) -> A,
+{
+    #[inline]
+    fn dist(&self, a: &[A], b: &[A]) -> A {
+        (self.0)(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b) * (a - b)
+    }
+}
+
+/// Adapts a distance function that takes arrays, `Fn(&[A; K], &[A; K]) -> A`, such as
+/// [`squared_euclidean`], into one that takes slices, for use with
+/// [`DynKdTree`](crate::float::dyn_kdtree::DynKdTree) or anywhere else that points are
+/// only available as slices.
+///
+/// The returned function panics if either slice does not have exactly `K` entries. To
+/// query a `DynKdTree` with it, wrap it in [`SliceDistance`], bearing in mind that this
+/// assumes that it behaves like squared euclidean distance.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::{dyn_distance, squared_euclidean};
+///
+/// let distance_fn = dyn_distance(squared_euclidean::<f32, 3>);
+///
+/// assert_eq!(3f32, distance_fn(&[0f32, 0f32, 0f32], &[1f32, 1f32, 1f32]));
+/// ```
+pub fn dyn_distance<A: Axis, const K: usize, F>(distance_fn: F) -> impl Fn(&[A], &[A]) -> A
+where
+    F: Fn(&[A; K], &[A; K]) -> A,
+{
+    move |a: &[A], b: &[A]| {
+        distance_fn(
+            a.try_into().expect("point should have K dimensions"),
+            b.try_into().expect("point should have K dimensions"),
+        )
+    }
+}
+
+/// Squared euclidean distance metric, for use in queries. See [`squared_euclidean`].
+///
+/// Behaves identically to passing `&squared_euclidean` itself.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SquaredEuclidean;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for SquaredEuclidean {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        squared_euclidean(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b) * (a - b)
+    }
+}
+
+impl<A: Axis> DynDistanceMetric<A> for SquaredEuclidean {
+    #[inline]
+    fn dist(&self, a: &[A], b: &[A]) -> A {
+        squared_euclidean_dyn(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b) * (a - b)
+    }
+}
+
+/// Returns the squared euclidean distance between two `f32` points, using SIMD
+/// instructions to process every axis at once when `K` is 3 or 4.
+///
+/// For other values of `K`, or on targets other than x86_64, this falls back to
+/// [`squared_euclidean`]. The result can differ from [`squared_euclidean`] by a
+/// rounding error, as the per-axis terms are summed in a different order.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::squared_euclidean_simd;
+///
+/// assert_eq!(0f32, squared_euclidean_simd(&[0f32, 0f32, 0f32], &[0f32, 0f32, 0f32]));
+/// assert_eq!(1f32, squared_euclidean_simd(&[0f32, 0f32, 0f32], &[1f32, 0f32, 0f32]));
+/// assert_eq!(3f32, squared_euclidean_simd(&[0f32, 0f32, 0f32], &[1f32, 1f32, 1f32]));
+/// ```
+#[cfg(feature = "simd")]
+#[inline]
+pub fn squared_euclidean_simd<const K: usize>(a: &[f32; K], b: &[f32; K]) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    if K == 3 || K == 4 {
+        let mut a_lanes = [0f32; 4];
+        let mut b_lanes = [0f32; 4];
+        a_lanes[..K].copy_from_slice(a);
+        b_lanes[..K].copy_from_slice(b);
+
+        // SSE is always available on x86_64, so no runtime feature detection is needed
+        return unsafe {
+            let diff = _mm_sub_ps(
+                _mm_loadu_ps(a_lanes.as_ptr()),
+                _mm_loadu_ps(b_lanes.as_ptr()),
+            );
+            let squares = _mm_mul_ps(diff, diff);
+
+            // horizontal sum: [0 + 2, 1 + 3, ..] then lane 0 + lane 1
+            let pairs = _mm_add_ps(squares, _mm_movehl_ps(squares, squares));
+            let total = _mm_add_ss(pairs, _mm_shuffle_ps(pairs, pairs, 0b01));
+            _mm_cvtss_f32(total)
+        };
+    }
+
+    squared_euclidean(a, b)
+}
+
+/// Returns a squared euclidean distance metric in which the squared difference along
+/// each axis is multiplied by the corresponding entry in `weights`.
+///
+/// Useful when the axes are measured in different units, such as when indexing by
+/// position and time together. The weights are also applied when deciding which
+/// parts of the tree can be skipped, so queries remain exact. All weights should be
+/// non-negative.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::{weighted_squared_euclidean, DistanceMetric};
+///
+/// let metric = weighted_squared_euclidean([1f32, 4f32]);
+///
+/// assert_eq!(1f32, metric.dist(&[0f32, 0f32], &[1f32, 0f32]));
+/// assert_eq!(4f32, metric.dist(&[0f32, 0f32], &[0f32, 1f32]));
+/// assert_eq!(20f32, metric.dist(&[0f32, 0f32], &[2f32, 2f32]));
+/// ```
+pub fn weighted_squared_euclidean<A: Axis, const K: usize>(
+    weights: [A; K],
+) -> WeightedSquaredEuclidean<A, K> {
+    WeightedSquaredEuclidean { weights }
+}
+
+/// Per-axis weighted squared euclidean distance metric, for use in queries.
+/// See [`weighted_squared_euclidean`].
+#[derive(Clone, Copy, Debug)]
+pub struct WeightedSquaredEuclidean<A, const K: usize> {
+    weights: [A; K],
+}
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for WeightedSquaredEuclidean<A, K> {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        a.iter()
+            .zip(b.iter())
+            .zip(self.weights.iter())
+            .map(|((&a_val, &b_val), &weight)| weight * (a_val - b_val) * (a_val - b_val))
+            .fold(A::zero(), core::ops::Add::add)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, dim: usize) -> A {
+        self.weights[dim] * (a - b) * (a - b)
+    }
+}
+
+/// Returns a squared euclidean distance metric for a periodic space that wraps around
+/// at the edges, such as a simulation box with periodic boundary conditions.
+///
+/// Along each axis, points are expected to lie between zero and the corresponding entry
+/// in `extents`. The separation of two points along an axis is the shorter of the direct
+/// distance between them and the distance going the other way around the boundary.
+///
+/// When deciding which parts of the tree can be skipped, the far side of a split plane
+/// is considered reachable either directly across the plane or by wrapping around the
+/// boundary, so queries remain exact. Wrapping does weaken the bounds that can be
+/// skipped against, so queries near the boundaries visit more of the tree than they
+/// otherwise would. Distances are only correct for points inside the extents.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::{periodic_squared_euclidean, DistanceMetric};
+///
+/// let metric = periodic_squared_euclidean([10f32, 10f32]);
+///
+/// assert_eq!(4f32, metric.dist(&[1f32, 5f32], &[3f32, 5f32]));
+/// assert_eq!(4f32, metric.dist(&[1f32, 5f32], &[9f32, 5f32]));
+/// assert_eq!(8f32, metric.dist(&[1f32, 1f32], &[9f32, 9f32]));
+/// ```
+pub fn periodic_squared_euclidean<A: Axis, const K: usize>(
+    extents: [A; K],
+) -> PeriodicSquaredEuclidean<A, K> {
+    PeriodicSquaredEuclidean { extents }
+}
+
+/// Squared euclidean distance metric for a space that wraps around at the edges, for
+/// use in queries. See [`periodic_squared_euclidean`].
+#[derive(Clone, Copy, Debug)]
+pub struct PeriodicSquaredEuclidean<A, const K: usize> {
+    extents: [A; K],
+}
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for PeriodicSquaredEuclidean<A, K> {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        a.iter()
+            .zip(b.iter())
+            .zip(self.extents.iter())
+            .map(|((&a_val, &b_val), &extent)| {
+                let direct = (a_val - b_val).abs();
+                let sep = direct.min(extent - direct);
+                sep * sep
+            })
+            .fold(A::zero(), core::ops::Add::add)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, dim: usize) -> A {
+        // the far side of the split from `a` can also be reached by wrapping around the
+        // boundary on the opposite side, which is at least this far away
+        let around = if a < b { a } else { self.extents[dim] - a };
+        let sep = (a - b).abs().min(around);
+        sep * sep
+    }
 }
 
 /// Returns the Manhattan / "taxi cab" distance between two points.
 ///
 /// Faster than squared Euclidean, and just as effective if not more so in higher-dimensional spaces
 ///
+/// When querying the tree, pass [`Manhattan`] rather than this function so that
+/// the parts of the tree that get skipped are determined correctly.
+///
 /// # Examples
 ///
 /// ```rust
@@ -44,5 +447,591 @@ pub fn manhattan<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
     a.iter()
         .zip(b.iter())
         .map(|(&a_val, &b_val)| (a_val - b_val).abs())
-        .fold(A::zero(), std::ops::Add::add)
+        .fold(A::zero(), core::ops::Add::add)
+}
+
+/// Manhattan / "taxi cab" distance metric, for use in queries. See [`manhattan`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Manhattan;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for Manhattan {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        manhattan(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b).abs()
+    }
+}
+
+impl<A: Axis> DynDistanceMetric<A> for Manhattan {
+    #[inline]
+    fn dist(&self, a: &[A], b: &[A]) -> A {
+        a.iter()
+            .zip(b.iter())
+            .map(|(&a_val, &b_val)| (a_val - b_val).abs())
+            .fold(A::zero(), core::ops::Add::add)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b).abs()
+    }
+}
+
+/// Returns the Chebyshev / L-infinity distance between two points.
+///
+/// This is the largest absolute difference between the two points along any single axis.
+/// When querying the tree, pass [`Chebyshev`] rather than this function so that
+/// the parts of the tree that get skipped are determined correctly.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::chebyshev;
+///
+/// assert_eq!(0f32, chebyshev(&[0f32, 0f32], &[0f32, 0f32]));
+/// assert_eq!(1f32, chebyshev(&[0f32, 0f32], &[1f32, 0f32]));
+/// assert_eq!(2f32, chebyshev(&[0f32, 0f32], &[1f32, -2f32]));
+/// ```
+pub fn chebyshev<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&a_val, &b_val)| (a_val - b_val).abs())
+        .fold(A::zero(), A::max)
+}
+
+/// Chebyshev / L-infinity distance metric, for use in queries. See [`chebyshev`].
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::kdtree::KdTree;
+/// use kiddo::float::distance::Chebyshev;
+///
+/// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+///
+/// tree.add(&[1.0, 2.0], 100);
+/// tree.add(&[2.5, 2.5], 101);
+///
+/// let nearest = tree.nearest_one(&[2.0, 0.0], &Chebyshev);
+///
+/// assert_eq!(nearest, (2.0, 100));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Chebyshev;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for Chebyshev {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        chebyshev(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b).abs()
+    }
+
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd.max(axis_dist)
+    }
+
+    /// The bound for an axis only ever grows as the query descends further from the
+    /// query point, so the largest contribution seen so far is always still valid.
+    #[inline]
+    fn uncombine(&self, rd: A, _axis_dist: A) -> A {
+        rd
+    }
+}
+
+impl<A: Axis> DynDistanceMetric<A> for Chebyshev {
+    #[inline]
+    fn dist(&self, a: &[A], b: &[A]) -> A {
+        a.iter()
+            .zip(b.iter())
+            .map(|(&a_val, &b_val)| (a_val - b_val).abs())
+            .fold(A::zero(), A::max)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b).abs()
+    }
+
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd.max(axis_dist)
+    }
+
+    #[inline]
+    fn uncombine(&self, rd: A, _axis_dist: A) -> A {
+        rd
+    }
+}
+
+/// Returns a Minkowski distance metric of order `p`, which measures the distance
+/// between two points as `(sum |a_i - b_i|^p)^(1/p)`.
+///
+/// With `p == 1.0` this is the same as [`manhattan`] distance, and with `p == 2.0` it is
+/// euclidean distance (the square root of [`squared_euclidean`]). Queries skip parts of
+/// the tree using the largest difference along any one axis crossed, which is never more
+/// than the Minkowski distance when `p >= 1.0`.
+///
+/// For `p < 1.0` the result is no longer a true metric, as it breaks the triangle
+/// inequality. Pruning may then be unsound, so this is not supported, and
+/// `p >= 1.0` is asserted in debug builds.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::{minkowski, DistanceMetric};
+///
+/// let metric = minkowski(3f64);
+///
+/// assert_eq!(0f64, metric.dist(&[0f64, 0f64], &[0f64, 0f64]));
+/// assert_eq!(2f64, metric.dist(&[0f64, 0f64], &[2f64, 0f64]));
+/// assert!((metric.dist(&[0f64, 0f64], &[1f64, 1f64]) - 2f64.cbrt()).abs() < 1e-12);
+/// ```
+pub fn minkowski<A: Axis>(p: A) -> Minkowski<A> {
+    debug_assert!(p >= A::one(), "Minkowski distance requires p >= 1");
+    Minkowski { p }
+}
+
+/// Minkowski distance metric of order `p`, for use in queries. See [`minkowski`].
+#[derive(Clone, Copy, Debug)]
+pub struct Minkowski<A> {
+    p: A,
+}
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for Minkowski<A> {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        a.iter()
+            .zip(b.iter())
+            .map(|(&a_val, &b_val)| (a_val - b_val).abs().powf(self.p))
+            .fold(A::zero(), core::ops::Add::add)
+            .powf(self.p.recip())
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        (a - b).abs()
+    }
+
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd.max(axis_dist)
+    }
+
+    #[inline]
+    fn uncombine(&self, rd: A, _axis_dist: A) -> A {
+        rd
+    }
+}
+
+/// Returns the Canberra distance between two points, `sum |a_i - b_i| / (|a_i| + |b_i|)`.
+///
+/// Each axis contributes between zero and one, so differences between small values count
+/// for as much as differences between large ones. Axes where both points are zero
+/// contribute zero. When querying the tree, pass [`Canberra`] rather than this function
+/// so that the parts of the tree that get skipped are determined correctly.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::canberra;
+///
+/// assert_eq!(0f32, canberra(&[0f32, 0f32], &[0f32, 0f32]));
+/// assert_eq!(1f32, canberra(&[0f32, 0f32], &[1f32, 0f32]));
+/// assert_eq!(1.5f32, canberra(&[1f32, 1f32], &[3f32, -1f32]));
+/// ```
+pub fn canberra<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&a_val, &b_val)| canberra_term(a_val, b_val))
+        .fold(A::zero(), core::ops::Add::add)
+}
+
+#[inline]
+fn canberra_term<A: Axis>(a: A, b: A) -> A {
+    let denominator = a.abs() + b.abs();
+    if denominator == A::zero() {
+        A::zero()
+    } else {
+        (a - b).abs() / denominator
+    }
+}
+
+/// Canberra distance metric, for use in queries. See [`canberra`].
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::kdtree::KdTree;
+/// use kiddo::float::distance::Canberra;
+///
+/// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+///
+/// tree.add(&[1.0, 10.0], 100);
+/// tree.add(&[2.0, 2.0], 101);
+///
+/// let nearest = tree.nearest_one(&[1.0, 1.0], &Canberra);
+///
+/// assert_eq!(nearest.1, 101);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Canberra;
+
+impl<A: Axis, const K: usize> DistanceMetric<A, K> for Canberra {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        canberra(a, b)
+    }
+
+    /// Moving a point further beyond the split plane never makes its term for this axis
+    /// smaller: it either grows, or is already one because the point has the opposite
+    /// sign to the query. So the term for a point on the plane itself is a lower bound.
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        canberra_term(a, b)
+    }
+}
+
+/// Returns the cosine distance between two points, `1 - dot(a, b) / (|a| |b|)`.
+///
+/// This treats each point as a vector from the origin, and measures the angle between
+/// them, ignoring their lengths. It ranges from zero for vectors pointing the same way to
+/// two for vectors pointing in opposite directions. If either vector is all zeros, the
+/// distance is one.
+///
+/// **Cosine distance cannot be used with the queries that skip parts of the tree**, such
+/// as [`nearest_one`](crate::float::kdtree::KdTree::nearest_one), as two points can be
+/// close in angle while being far apart along every axis. Those queries would silently
+/// return wrong results. Use
+/// [`nearest_one_bruteforce`](crate::float::kdtree::KdTree::nearest_one_bruteforce),
+/// which checks every point in the tree, instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::cosine;
+///
+/// assert_eq!(0f32, cosine(&[1f32, 0f32], &[2f32, 0f32]));
+/// assert_eq!(1f32, cosine(&[1f32, 0f32], &[0f32, 1f32]));
+/// assert_eq!(2f32, cosine(&[1f32, 1f32], &[-1f32, -1f32]));
+/// ```
+pub fn cosine<A: Axis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    let (dot, a_norm_sq, b_norm_sq) = a.iter().zip(b.iter()).fold(
+        (A::zero(), A::zero(), A::zero()),
+        |(dot, a_norm_sq, b_norm_sq), (&a_val, &b_val)| {
+            (
+                dot + a_val * b_val,
+                a_norm_sq + a_val * a_val,
+                b_norm_sq + b_val * b_val,
+            )
+        },
+    );
+
+    let norms = (a_norm_sq * b_norm_sq).sqrt();
+    if norms == A::zero() {
+        return A::one();
+    }
+
+    // rounding can push the similarity of near-identical vectors just past one
+    A::one() - (dot / norms).min(A::one()).max(-A::one())
+}
+
+/// Returns the great-circle distance between two points on a unit sphere, using the
+/// haversine formula.
+///
+/// Each point is given as `[latitude, longitude]` in radians, so this only works with
+/// trees where `K == 2`. The result is an angle in radians: multiply it by the radius
+/// of the sphere to get a ground distance. When querying the tree, pass [`Haversine`]
+/// rather than this function so that the parts of the tree that get skipped are
+/// determined correctly.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::distance::haversine;
+/// use std::f64::consts::PI;
+///
+/// let north_pole = [PI / 2.0, 0.0];
+/// let on_equator = [0.0, 1.0];
+///
+/// assert_eq!(0f64, haversine(&on_equator, &on_equator));
+/// assert!((haversine(&north_pole, &on_equator) - PI / 2.0).abs() < 1e-12);
+/// ```
+pub fn haversine<A: Axis>(a: &[A; 2], b: &[A; 2]) -> A {
+    let two = A::one() + A::one();
+    let half_lat_diff = (b[0] - a[0]) / two;
+    let half_lng_diff = (b[1] - a[1]) / two;
+
+    let h = half_lat_diff.sin().powi(2) + a[0].cos() * b[0].cos() * half_lng_diff.sin().powi(2);
+
+    two * h.sqrt().min(A::one()).asin()
+}
+
+/// Great-circle distance metric for `[latitude, longitude]` points in radians, for use
+/// in queries. See [`haversine`].
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::kdtree::KdTree;
+/// use kiddo::float::distance::Haversine;
+///
+/// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+///
+/// tree.add(&[0.9, 0.0], 100);
+/// tree.add(&[0.0, 0.1], 101);
+///
+/// let nearest = tree.nearest_one(&[0.0, 0.0], &Haversine);
+///
+/// assert_eq!(nearest.1, 101);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Haversine;
+
+impl<A: Axis> DistanceMetric<A, 2> for Haversine {
+    #[inline]
+    fn dist(&self, a: &[A; 2], b: &[A; 2]) -> A {
+        haversine(a, b)
+    }
+
+    /// Two points can never be closer than their difference in latitude, but can be
+    /// arbitrarily close whatever their difference in longitude, if they are near a pole.
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, dim: usize) -> A {
+        if dim == 0 {
+            (a - b).abs()
+        } else {
+            A::zero()
+        }
+    }
+
+    #[inline]
+    fn combine(&self, rd: A, axis_dist: A) -> A {
+        rd.max(axis_dist)
+    }
+
+    #[inline]
+    fn uncombine(&self, rd: A, _axis_dist: A) -> A {
+        rd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        canberra, cosine, haversine, manhattan, minkowski, squared_euclidean, DistanceMetric,
+    };
+    use rand::Rng;
+
+    const EARTH_RADIUS_IN_KM: f64 = 6371.0;
+
+    #[test]
+    fn haversine_distance_from_london_to_paris() {
+        let london = [51.5074f64.to_radians(), (-0.1278f64).to_radians()];
+        let paris = [48.8566f64.to_radians(), 2.3522f64.to_radians()];
+
+        let dist_km = haversine(&london, &paris) * EARTH_RADIUS_IN_KM;
+
+        assert!((dist_km - 343.56).abs() < 0.1, "{dist_km}");
+        assert_eq!(haversine(&london, &paris), haversine(&paris, &london));
+    }
+
+    #[test]
+    fn minkowski_distance_matches_manhattan_and_euclidean() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let a: [f64; 3] = rng.gen();
+            let b: [f64; 3] = rng.gen();
+
+            let l1 = minkowski(1f64).dist(&a, &b);
+            let l2 = minkowski(2f64).dist(&a, &b);
+
+            assert!((l1 - manhattan(&a, &b)).abs() < 1e-12);
+            assert!((l2 - squared_euclidean(&a, &b).sqrt()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn canberra_queries_match_a_brute_force_search() {
+        use crate::float::distance::Canberra;
+        use crate::float::kdtree::KdTree;
+
+        let mut rng = rand::thread_rng();
+
+        let points: Vec<([f64; 3], u32)> = (0..1000)
+            .map(|item| (rng.gen::<[f64; 3]>().map(|x| x * 20.0 - 10.0), item))
+            .collect();
+        let mut tree: KdTree<f64, u32, 3, 8, u32> = KdTree::new();
+        points
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..1000 {
+            let query = rng.gen::<[f64; 3]>().map(|x| x * 20.0 - 10.0);
+            let expected = points
+                .iter()
+                .map(|(point, _)| canberra(&query, point))
+                .fold(f64::INFINITY, f64::min);
+
+            assert_eq!(tree.nearest_one(&query, &Canberra).0, expected);
+        }
+
+        assert_eq!(canberra(&[0f64, 1f64], &[0f64, 1f64]), 0f64);
+    }
+
+    #[test]
+    fn cosine_distance_ignores_vector_length() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let a: [f64; 3] = rng.gen();
+            let b: [f64; 3] = rng.gen();
+            let scale = rng.gen_range(0.1..10.0);
+
+            let dist = cosine(&a, &b);
+
+            assert!((0.0..=2.0).contains(&dist));
+            assert!((cosine(&a, &b.map(|x| x * scale)) - dist).abs() < 1e-12);
+            assert!(cosine(&a, &a).abs() < 1e-12);
+        }
+
+        assert_eq!(cosine(&[0f64, 0f64], &[1f64, 1f64]), 1f64);
+    }
+
+    #[test]
+    fn periodic_queries_match_a_brute_force_search() {
+        use crate::float::distance::periodic_squared_euclidean;
+        use crate::float::kdtree::KdTree;
+
+        const EXTENT: f64 = 10.0;
+
+        fn brute_force<const K: usize>(
+            points: &[([f64; K], u32)],
+            query: &[f64; K],
+            extents: [f64; K],
+        ) -> f64 {
+            points
+                .iter()
+                .map(|(point, _)| {
+                    (0..K)
+                        .map(|dim| {
+                            let direct = (point[dim] - query[dim]).abs();
+                            let sep = direct.min(extents[dim] - direct);
+                            sep * sep
+                        })
+                        .sum::<f64>()
+                })
+                .fold(f64::INFINITY, f64::min)
+        }
+
+        let mut rng = rand::thread_rng();
+
+        let points: Vec<([f64; 1], u32)> = (0..1000)
+            .map(|item| ([rng.gen_range(0.0..EXTENT)], item))
+            .collect();
+        let tree: KdTree<f64, u32, 1, 4, u32> = KdTree::from_slice(&points);
+        let metric = periodic_squared_euclidean([EXTENT]);
+
+        for _ in 0..1000 {
+            let query = [rng.gen_range(0.0..EXTENT)];
+            assert_eq!(
+                tree.nearest_one(&query, &metric).0,
+                brute_force(&points, &query, [EXTENT])
+            );
+        }
+
+        // sparse points away from the edges, so that nearest neighbours of queries
+        // near the edges are often found by wrapping around
+        let points: Vec<([f64; 2], u32)> = (0..50)
+            .map(|item| ([rng.gen_range(2.0..8.0), rng.gen_range(2.0..8.0)], item))
+            .collect();
+        let tree: KdTree<f64, u32, 2, 4, u32> = KdTree::from_slice(&points);
+        let metric = periodic_squared_euclidean([EXTENT, EXTENT]);
+
+        for _ in 0..1000 {
+            let query = [rng.gen_range(0.0..EXTENT), rng.gen_range(0.0..EXTENT)];
+            assert_eq!(
+                tree.nearest_one(&query, &metric).0,
+                brute_force(&points, &query, [EXTENT, EXTENT])
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn minkowski_with_p_less_than_one_panics_in_debug_builds() {
+        minkowski(0.5f64);
+    }
+
+    #[cfg(feature = "simd")]
+    fn assert_simd_matches_scalar<const K: usize>() {
+        use super::squared_euclidean_simd;
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..1000 {
+            let a: [f32; K] = core::array::from_fn(|_| rng.gen());
+            let b: [f32; K] = core::array::from_fn(|_| rng.gen());
+
+            let expected = squared_euclidean(&a, &b);
+            let actual = squared_euclidean_simd(&a, &b);
+
+            assert!(
+                (actual - expected).abs() <= expected * f32::EPSILON * 4f32,
+                "{actual} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "simd")]
+    fn simd_squared_euclidean_matches_scalar() {
+        assert_simd_matches_scalar::<2>();
+        assert_simd_matches_scalar::<3>();
+        assert_simd_matches_scalar::<4>();
+        assert_simd_matches_scalar::<5>();
+    }
+
+    #[test]
+    fn slice_and_array_distance_functions_can_be_adapted_to_each_other() {
+        use crate::float::distance::{
+            dyn_distance, squared_euclidean_dyn, SliceDistance, SquaredEuclidean,
+        };
+        use crate::float::dyn_kdtree::DynKdTree;
+        use crate::float::kdtree::KdTree;
+
+        let mut rng = rand::thread_rng();
+
+        let points: Vec<([f64; 3], u32)> = (0..1000).map(|item| (rng.gen(), item)).collect();
+        let tree: KdTree<f64, u32, 3, 8, u32> = KdTree::from_slice(&points);
+        let mut dyn_tree: DynKdTree<f64, u32, 8> = DynKdTree::new(3);
+        points
+            .iter()
+            .for_each(|(point, item)| dyn_tree.add(point, *item));
+
+        let squared_euclidean_adapted = SliceDistance(dyn_distance(squared_euclidean::<f64, 3>));
+
+        for _ in 0..100 {
+            let query: [f64; 3] = rng.gen();
+
+            assert_eq!(
+                tree.nearest_one(&query, &SliceDistance(squared_euclidean_dyn)),
+                tree.nearest_one(&query, &squared_euclidean)
+            );
+            assert_eq!(
+                dyn_tree.nearest_one(&query, &squared_euclidean_adapted),
+                dyn_tree.nearest_one(&query, &SquaredEuclidean)
+            );
+        }
+    }
 }
@@ -0,0 +1,477 @@
+//! A floating point k-d tree whose bucket size is chosen at runtime.
+
+use alloc::vec::Vec;
+use core::ops::Rem;
+use divrem::DivCeil;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::Axis;
+use crate::mirror_select_nth_unstable_by::mirror_select_nth_unstable_by;
+use crate::split_ties::tie_goes_left;
+use crate::types::Content;
+
+// node indices at or above this refer to leaves rather than stems
+const LEAF_OFFSET: usize = usize::MAX >> 1;
+
+/// Floating point k-d tree with a runtime bucket size
+///
+/// [`KdTree`](crate::float::kdtree::KdTree) takes the bucket size as the const generic
+/// `B`, so a separate copy of all of its code is compiled for each bucket size that is
+/// used. `DynBucketKdTree` instead takes the bucket size when it is created, so trying
+/// out or tuning many different bucket sizes does not bloat the binary, and the bucket
+/// size can be chosen from configuration or by measuring the data.
+///
+/// Each leaf stores its points and items in a pair of `Vec`s, holding at most
+/// `bucket_size` of each, rather than in fixed size arrays within the leaf. This makes
+/// it somewhat slower than `KdTree` with the same bucket size, as each leaf's contents
+/// are in a separate allocation. Only [`add`](DynBucketKdTree::add) and
+/// [`nearest_one`](DynBucketKdTree::nearest_one) are available. Prefer `KdTree` once a
+/// bucket size has been settled on.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+/// use kiddo::float::distance::squared_euclidean;
+///
+/// let bucket_size = 32;
+/// let mut tree: DynBucketKdTree<f64, u32, 3> = DynBucketKdTree::new(bucket_size);
+///
+/// tree.add(&[1.0, 2.0, 5.0], 100);
+/// tree.add(&[2.0, 3.0, 6.0], 101);
+///
+/// assert_eq!(tree.size(), 2);
+/// assert_eq!(tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean).1, 100);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynBucketKdTree<A, T, const K: usize> {
+    bucket_size: usize,
+    leaves: Vec<DynBucketLeafNode<A, T, K>>,
+    stems: Vec<DynBucketStemNode<A>>,
+    root_index: usize,
+    size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DynBucketLeafNode<A, T, const K: usize> {
+    content_points: Vec<[A; K]>,
+    content_items: Vec<T>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DynBucketStemNode<A> {
+    left: usize,
+    right: usize,
+    split_val: A,
+    split_dim: usize,
+}
+
+impl<A: Axis, T: Content, const K: usize> DynBucketLeafNode<A, T, K> {
+    fn with_capacity(bucket_size: usize) -> Self {
+        Self {
+            content_points: Vec::with_capacity(bucket_size),
+            content_items: Vec::with_capacity(bucket_size),
+        }
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize> DynBucketKdTree<A, T, K> {
+    /// Creates a new tree whose leaves hold up to `bucket_size` points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_size` is less than 2, as a leaf holding a single point cannot be
+    /// split in two.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+    ///
+    /// let tree: DynBucketKdTree<f64, u32, 3> = DynBucketKdTree::new(32);
+    ///
+    /// assert_eq!(tree.bucket_size(), 32);
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    #[inline]
+    pub fn new(bucket_size: usize) -> Self {
+        Self::with_capacity(bucket_size, bucket_size * 10)
+    }
+
+    /// Creates a new tree whose leaves hold up to `bucket_size` points, with room for
+    /// `num_points` points to be added without it needing to reallocate.
+    ///
+    /// As with [`KdTree::with_capacity`](crate::float::kdtree::KdTree::with_capacity),
+    /// leaves are split at the median and so are always at least half full, and room is
+    /// made for `2 * num_points / bucket_size` leaves and stems.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_size` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+    ///
+    /// let tree: DynBucketKdTree<f64, u32, 3> = DynBucketKdTree::with_capacity(32, 1_000_000);
+    ///
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    pub fn with_capacity(bucket_size: usize, num_points: usize) -> Self {
+        assert!(bucket_size >= 2, "bucket size must be at least 2");
+
+        let max_leaves = DivCeil::div_ceil(num_points * 2, bucket_size);
+
+        let mut leaves = Vec::with_capacity(max_leaves + 1);
+        leaves.push(DynBucketLeafNode::with_capacity(bucket_size));
+
+        Self {
+            bucket_size,
+            leaves,
+            stems: Vec::with_capacity(max_leaves),
+            root_index: LEAF_OFFSET,
+            size: 0,
+        }
+    }
+
+    /// Returns the largest number of points that a leaf holds before it is split
+    #[inline]
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Returns the current number of elements stored in the tree
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Adds an item to the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+    ///
+    /// let mut tree: DynBucketKdTree<f64, u32, 3> = DynBucketKdTree::new(32);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    pub fn add(&mut self, point: &[A; K], item: T) {
+        let mut node_idx = self.root_index;
+        let mut split_dim = 0;
+        let mut parent = None;
+        let tie_seed = self.stems.len();
+        let mut depth = 0;
+
+        while Self::is_stem_index(node_idx) {
+            let stem_node = &self.stems[node_idx];
+            let point_val = point[stem_node.split_dim];
+            let is_left_child = point_val < stem_node.split_val
+                || (point_val == stem_node.split_val && tie_goes_left(tie_seed, depth));
+
+            parent = Some((node_idx, is_left_child));
+            node_idx = if is_left_child {
+                stem_node.left
+            } else {
+                stem_node.right
+            };
+
+            split_dim = (stem_node.split_dim + 1).rem(K);
+            depth += 1;
+        }
+
+        let mut leaf_idx = node_idx - LEAF_OFFSET;
+
+        if self.leaves[leaf_idx].content_points.len() == self.bucket_size {
+            let stem_idx = self.split(leaf_idx, split_dim, parent);
+            let stem_node = &self.stems[stem_idx];
+
+            leaf_idx = if point[stem_node.split_dim] < stem_node.split_val {
+                stem_node.left
+            } else {
+                stem_node.right
+            } - LEAF_OFFSET;
+        }
+
+        let leaf_node = &mut self.leaves[leaf_idx];
+        leaf_node.content_points.push(*point);
+        leaf_node.content_items.push(item);
+        self.size += 1;
+    }
+
+    fn split(&mut self, leaf_idx: usize, split_dim: usize, parent: Option<(usize, bool)>) -> usize {
+        let pivot_idx = self.bucket_size / 2;
+        let leaf_node = &mut self.leaves[leaf_idx];
+
+        // as with KdTree, splitting along an axis that the points all have the same value
+        // on would not separate any of them, so use the next axis along which they differ
+        let first_point = leaf_node.content_points[0];
+        let split_dim = (0..K)
+            .map(|offset| (split_dim + offset).rem(K))
+            .find(|&dim| {
+                leaf_node
+                    .content_points
+                    .iter()
+                    .any(|point| point[dim] != first_point[dim])
+            })
+            .unwrap_or(split_dim);
+
+        mirror_select_nth_unstable_by(
+            &mut leaf_node.content_points,
+            &mut leaf_node.content_items,
+            pivot_idx,
+            |a, b| {
+                a[split_dim]
+                    .partial_cmp(&b[split_dim])
+                    .expect("Leaf node sort failed.")
+            },
+        );
+        let split_val = leaf_node.content_points[pivot_idx][split_dim];
+
+        let mut right = DynBucketLeafNode::with_capacity(self.bucket_size);
+        right
+            .content_points
+            .extend(leaf_node.content_points.drain(pivot_idx..));
+        right
+            .content_items
+            .extend(leaf_node.content_items.drain(pivot_idx..));
+        self.leaves.push(right);
+
+        self.stems.push(DynBucketStemNode {
+            left: leaf_idx + LEAF_OFFSET,
+            right: self.leaves.len() - 1 + LEAF_OFFSET,
+            split_val,
+            split_dim,
+        });
+        let new_stem_index = self.stems.len() - 1;
+
+        match parent {
+            Some((parent_idx, true)) => self.stems[parent_idx].left = new_stem_index,
+            Some((parent_idx, false)) => self.stems[parent_idx].right = new_stem_index,
+            None => self.root_index = new_stem_index,
+        }
+
+        new_stem_index
+    }
+
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function.
+    ///
+    /// As with [`KdTree::nearest_one`](crate::float::kdtree::KdTree::nearest_one), ties
+    /// are won by the smallest item, and an empty tree returns `(A::max_value(), T::zero())`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_bucket_kdtree::DynBucketKdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let mut tree: DynBucketKdTree<f64, u32, 3> = DynBucketKdTree::new(32);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean);
+    ///
+    /// assert!((nearest.0 - 0.01f64).abs() < f64::EPSILON);
+    /// assert_eq!(nearest.1, 100);
+    /// ```
+    pub fn nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best = None;
+
+        self.nearest_one_recurse(
+            query,
+            distance_fn,
+            self.root_index,
+            &mut best,
+            &mut off,
+            A::zero(),
+        );
+
+        best.unwrap_or((A::max_value(), T::zero()))
+    }
+
+    fn nearest_one_recurse<D>(
+        &self,
+        query: &[A; K],
+        distance_fn: &D,
+        curr_node_idx: usize,
+        best: &mut Option<(A, T)>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if Self::is_stem_index(curr_node_idx) {
+            let node = &self.stems[curr_node_idx];
+            let split_dim = node.split_dim;
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] = if query[split_dim] < node.split_val {
+                [node.left, node.right]
+            } else {
+                [node.right, node.left]
+            };
+
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                closer_node_idx,
+                best,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if best.is_none_or(|(best_dist, _)| rd <= best_dist) {
+                off[split_dim] = new_off;
+                self.nearest_one_recurse(
+                    query,
+                    distance_fn,
+                    further_node_idx,
+                    best,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = &self.leaves[curr_node_idx - LEAF_OFFSET];
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .for_each(|(point, &item)| {
+                    let dist = distance_fn.dist(query, point);
+                    let is_nearer = match *best {
+                        None => !dist.is_nan(),
+                        Some((best_dist, best_item)) => {
+                            dist < best_dist || (dist == best_dist && item < best_item)
+                        }
+                    };
+                    if is_nearer {
+                        *best = Some((dist, item));
+                    }
+                });
+        }
+    }
+
+    #[inline]
+    fn is_stem_index(idx: usize) -> bool {
+        idx < LEAF_OFFSET
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{squared_euclidean, Manhattan};
+    use crate::float::dyn_bucket_kdtree::DynBucketKdTree;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn nearest_one_matches_kdtree_for_any_bucket_size() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut expected: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| expected.add(point, *item));
+
+        let query_points: Vec<[AX; 3]> = (0..NUM_QUERIES).map(|_| rand::random()).collect();
+
+        for bucket_size in [2, 3, 8, 32, 100] {
+            let mut tree: DynBucketKdTree<AX, u32, 3> =
+                DynBucketKdTree::with_capacity(bucket_size, TREE_SIZE);
+            content_to_add
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+
+            assert_eq!(tree.size(), TREE_SIZE);
+            assert!(tree
+                .leaves
+                .iter()
+                .all(|leaf| leaf.content_points.len() <= bucket_size));
+
+            for query_point in &query_points {
+                assert_eq!(
+                    tree.nearest_one(query_point, &squared_euclidean),
+                    expected.nearest_one(query_point, &squared_euclidean)
+                );
+                assert_eq!(
+                    tree.nearest_one(query_point, &Manhattan),
+                    expected.nearest_one(query_point, &Manhattan)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_one_breaks_ties_in_favour_of_the_smallest_item() {
+        let mut tree: DynBucketKdTree<AX, u32, 2> = DynBucketKdTree::new(2);
+        for (point, item) in [([1.0, 0.0], 7), ([0.0, 1.0], 3), ([-1.0, 0.0], 5)] {
+            tree.add(&point, item);
+        }
+
+        assert_eq!(tree.nearest_one(&[0.0, 0.0], &squared_euclidean), (1.0, 3));
+
+        let empty: DynBucketKdTree<AX, u32, 2> = DynBucketKdTree::new(2);
+        assert_eq!(
+            empty.nearest_one(&[0.0, 0.0], &squared_euclidean),
+            (AX::MAX, 0)
+        );
+    }
+
+    #[test]
+    fn can_add_many_points_with_identical_coordinates() {
+        const NUM_DUPLICATES: u32 = 1000;
+        let point: [AX; 3] = [1.0, 2.0, 3.0];
+
+        let mut tree: DynBucketKdTree<AX, u32, 3> = DynBucketKdTree::new(32);
+        for item in 0..NUM_DUPLICATES {
+            tree.add(&point, item);
+        }
+        tree.add(&[5.0, 5.0, 5.0], NUM_DUPLICATES);
+
+        fn height(tree: &DynBucketKdTree<AX, u32, 3>, node_idx: usize) -> usize {
+            if !DynBucketKdTree::<AX, u32, 3>::is_stem_index(node_idx) {
+                return 0;
+            }
+            let node = &tree.stems[node_idx];
+            1 + height(tree, node.left).max(height(tree, node.right))
+        }
+
+        assert_eq!(tree.size(), NUM_DUPLICATES as usize + 1);
+        // a chain of stems would be around 60 levels deep
+        let height = height(&tree, tree.root_index);
+        assert!(height <= 15, "height was {}", height);
+        assert_eq!(tree.nearest_one(&point, &squared_euclidean), (0.0, 0));
+        assert_eq!(
+            tree.nearest_one(&[4.0, 5.0, 5.0], &squared_euclidean),
+            (1.0, NUM_DUPLICATES)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket size must be at least 2")]
+    fn a_bucket_size_of_one_is_rejected() {
+        let _: DynBucketKdTree<AX, u32, 2> = DynBucketKdTree::new(1);
+    }
+}
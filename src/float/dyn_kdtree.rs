@@ -0,0 +1,593 @@
+//! A floating point k-d tree whose number of dimensions is chosen at runtime.
+
+use alloc::boxed::Box;
+use alloc::{vec, vec::Vec};
+use divrem::DivCeil;
+
+use crate::float::distance::DynDistanceMetric;
+use crate::float::kdtree::Axis;
+use crate::float::neighbour::Neighbour;
+use crate::split_ties::tie_goes_left;
+use crate::types::Content;
+
+// node indices at or above this refer to leaves rather than stems
+const LEAF_OFFSET: usize = usize::MAX >> 1;
+
+/// Floating point k-d tree with a runtime number of dimensions
+///
+/// [`KdTree`](crate::float::kdtree::KdTree) takes the number of dimensions as the const
+/// generic `K`, so it must be known when compiling. `DynKdTree` instead takes the number
+/// of dimensions, `k`, when it is created, for data whose dimensionality is only known at
+/// runtime, such as when it is read from a file header. Points are passed and stored as
+/// slices of length `k`; every method that is given a point of any other length panics.
+///
+/// Only a subset of the functionality of `KdTree` is available, and it is slower, as each
+/// point is stored in its own allocation. Prefer `KdTree` whenever `K` is known up front.
+///
+/// `B` is the bucket size, as for `KdTree`. Queries take a
+/// [`DynDistanceMetric`], such as [`SquaredEuclidean`](crate::float::distance::SquaredEuclidean),
+/// which is the counterpart of [`DistanceMetric`](crate::float::distance::DistanceMetric)
+/// for points that are slices.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::dyn_kdtree::DynKdTree;
+/// use kiddo::float::distance::SquaredEuclidean;
+///
+/// let k = 3;
+/// let mut tree: DynKdTree<f64, u32, 32> = DynKdTree::new(k);
+///
+/// tree.add(&[1.0, 2.0, 5.0], 100);
+/// tree.add(&[2.0, 3.0, 6.0], 101);
+///
+/// assert_eq!(tree.size(), 2);
+/// assert_eq!(tree.nearest_one(&[1.0, 2.0, 5.1], &SquaredEuclidean).1, 100);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynKdTree<A, T, const B: usize> {
+    k: usize,
+    leaves: Vec<Vec<(Box<[A]>, T)>>,
+    stems: Vec<DynStemNode<A>>,
+    root_index: usize,
+    size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DynStemNode<A> {
+    left: usize,
+    right: usize,
+    split_val: A,
+    split_dim: usize,
+}
+
+impl<A: Axis, T: Content, const B: usize> DynKdTree<A, T, B> {
+    /// Creates a new tree for points with `k` dimensions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero, or if the bucket size `B` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_kdtree::DynKdTree;
+    ///
+    /// let tree: DynKdTree<f64, u32, 32> = DynKdTree::new(5);
+    ///
+    /// assert_eq!(tree.k(), 5);
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    #[inline]
+    pub fn new(k: usize) -> Self {
+        Self::with_capacity(k, B * 10)
+    }
+
+    /// Creates a new tree for points with `k` dimensions, with room for `num_points`
+    /// points to be added without it needing to reallocate.
+    ///
+    /// `num_points` is the number of points that the tree is expected to hold, not the
+    /// number of leaves: room is made for `2 * num_points / B` leaves and stems, since
+    /// leaves are split at the median and so are always at least half full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero, or if the bucket size `B` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_kdtree::DynKdTree;
+    ///
+    /// let tree: DynKdTree<f64, u32, 32> = DynKdTree::with_capacity(5, 1_000_000);
+    ///
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    pub fn with_capacity(k: usize, num_points: usize) -> Self {
+        assert!(k > 0, "points must have at least one dimension");
+        assert!(B >= 2, "bucket size B must be at least 2");
+
+        // leaves are split at the median, so each one ends up at least half full
+        let max_leaves = DivCeil::div_ceil(num_points * 2, B);
+
+        let mut leaves = Vec::with_capacity(max_leaves + 1);
+        leaves.push(Vec::with_capacity(B));
+
+        Self {
+            k,
+            leaves,
+            stems: Vec::with_capacity(max_leaves),
+            root_index: LEAF_OFFSET,
+            size: 0,
+        }
+    }
+
+    /// Returns the number of dimensions of the points stored in the tree
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Returns the current number of elements stored in the tree
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Adds an item to the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` does not have [`k`](DynKdTree::k) dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_kdtree::DynKdTree;
+    ///
+    /// let mut tree: DynKdTree<f64, u32, 32> = DynKdTree::new(3);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// assert_eq!(tree.size(), 1);
+    /// ```
+    pub fn add(&mut self, point: &[A], item: T) {
+        self.check_dimensions(point);
+
+        let mut node_idx = self.root_index;
+        let mut split_dim = 0;
+        let mut parent = None;
+        let tie_seed = self.stems.len();
+        let mut depth = 0;
+
+        while Self::is_stem_index(node_idx) {
+            let stem_node = &self.stems[node_idx];
+            let point_val = point[stem_node.split_dim];
+            let is_left_child = point_val < stem_node.split_val
+                || (point_val == stem_node.split_val && tie_goes_left(tie_seed, depth));
+
+            parent = Some((node_idx, is_left_child));
+            node_idx = if is_left_child {
+                stem_node.left
+            } else {
+                stem_node.right
+            };
+
+            split_dim = (stem_node.split_dim + 1) % self.k;
+            depth += 1;
+        }
+
+        let mut leaf_idx = node_idx - LEAF_OFFSET;
+
+        if self.leaves[leaf_idx].len() == B {
+            let stem_idx = self.split(leaf_idx, split_dim, parent);
+            let stem_node = &self.stems[stem_idx];
+
+            leaf_idx = if point[stem_node.split_dim] < stem_node.split_val {
+                stem_node.left
+            } else {
+                stem_node.right
+            } - LEAF_OFFSET;
+        }
+
+        self.leaves[leaf_idx].push((point.into(), item));
+        self.size += 1;
+    }
+
+    fn split(&mut self, leaf_idx: usize, split_dim: usize, parent: Option<(usize, bool)>) -> usize {
+        let pivot_idx = B / 2;
+        let k = self.k;
+        let leaf_node = &mut self.leaves[leaf_idx];
+
+        // as with KdTree, splitting along an axis that the points all have the same value
+        // on would not separate any of them, so use the next axis along which they differ
+        let first_point = &leaf_node[0].0;
+        let split_dim = (0..k)
+            .map(|offset| (split_dim + offset) % k)
+            .find(|&dim| {
+                leaf_node
+                    .iter()
+                    .any(|(point, _)| point[dim] != first_point[dim])
+            })
+            .unwrap_or(split_dim);
+
+        leaf_node.select_nth_unstable_by(pivot_idx, |(a, _), (b, _)| {
+            a[split_dim]
+                .partial_cmp(&b[split_dim])
+                .expect("Leaf node sort failed.")
+        });
+        let split_val = leaf_node[pivot_idx].0[split_dim];
+
+        let mut right = Vec::with_capacity(B);
+        right.extend(leaf_node.drain(pivot_idx..));
+        self.leaves.push(right);
+
+        self.stems.push(DynStemNode {
+            left: leaf_idx + LEAF_OFFSET,
+            right: self.leaves.len() - 1 + LEAF_OFFSET,
+            split_val,
+            split_dim,
+        });
+        let new_stem_index = self.stems.len() - 1;
+
+        match parent {
+            Some((parent_idx, true)) => self.stems[parent_idx].left = new_stem_index,
+            Some((parent_idx, false)) => self.stems[parent_idx].right = new_stem_index,
+            None => self.root_index = new_stem_index,
+        }
+
+        new_stem_index
+    }
+
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function.
+    ///
+    /// As with [`KdTree::nearest_one`](crate::float::kdtree::KdTree::nearest_one), ties
+    /// are won by the smallest item, and an empty tree returns `(A::max_value(), T::zero())`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `query` does not have [`k`](DynKdTree::k) dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_kdtree::DynKdTree;
+    /// use kiddo::float::distance::SquaredEuclidean;
+    ///
+    /// let mut tree: DynKdTree<f64, u32, 32> = DynKdTree::new(3);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one(&[1.0, 2.0, 5.1], &SquaredEuclidean);
+    ///
+    /// assert!((nearest.0 - 0.01f64).abs() < f64::EPSILON);
+    /// assert_eq!(nearest.1, 100);
+    /// ```
+    pub fn nearest_one<D>(&self, query: &[A], distance_fn: &D) -> (A, T)
+    where
+        D: DynDistanceMetric<A>,
+    {
+        self.check_dimensions(query);
+
+        let mut off = vec![A::zero(); self.k];
+        let mut best_dist = A::max_value();
+        let mut best_item = T::zero();
+
+        self.nearest_one_recurse(
+            query,
+            distance_fn,
+            self.root_index,
+            &mut best_item,
+            &mut best_dist,
+            &mut off,
+            A::zero(),
+        );
+
+        (best_dist, best_item)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn nearest_one_recurse<D>(
+        &self,
+        query: &[A],
+        distance_fn: &D,
+        curr_node_idx: usize,
+        best_item: &mut T,
+        best_dist: &mut A,
+        off: &mut [A],
+        rd: A,
+    ) where
+        D: DynDistanceMetric<A>,
+    {
+        if Self::is_stem_index(curr_node_idx) {
+            let node = &self.stems[curr_node_idx];
+            let split_dim = node.split_dim;
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] = if query[split_dim] < node.split_val {
+                [node.left, node.right]
+            } else {
+                [node.right, node.left]
+            };
+
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                closer_node_idx,
+                best_item,
+                best_dist,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if rd <= *best_dist {
+                off[split_dim] = new_off;
+                self.nearest_one_recurse(
+                    query,
+                    distance_fn,
+                    further_node_idx,
+                    best_item,
+                    best_dist,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            self.leaves[curr_node_idx - LEAF_OFFSET]
+                .iter()
+                .for_each(|(point, item)| {
+                    let dist = distance_fn.dist(query, point);
+                    if dist < *best_dist || (dist == *best_dist && *item < *best_item) {
+                        *best_dist = dist;
+                        *best_item = *item;
+                    }
+                });
+        }
+    }
+
+    /// Finds all elements within `dist` of `query`, using the specified distance metric
+    /// function. Results are returned sorted nearest-first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `query` does not have [`k`](DynKdTree::k) dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::dyn_kdtree::DynKdTree;
+    /// use kiddo::float::distance::SquaredEuclidean;
+    ///
+    /// let mut tree: DynKdTree<f64, u32, 32> = DynKdTree::new(3);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[200.0, 300.0, 600.0], 102);
+    ///
+    /// let within = tree.within(&[1.0, 2.0, 5.0], 10f64, &SquaredEuclidean);
+    ///
+    /// assert_eq!(within.len(), 2);
+    /// ```
+    pub fn within<D>(&self, query: &[A], dist: A, distance_fn: &D) -> Vec<Neighbour<A, T>>
+    where
+        D: DynDistanceMetric<A>,
+    {
+        self.check_dimensions(query);
+
+        let mut off = vec![A::zero(); self.k];
+        let mut matching_items = Vec::new();
+
+        self.within_recurse(
+            query,
+            dist,
+            distance_fn,
+            self.root_index,
+            &mut matching_items,
+            &mut off,
+            A::zero(),
+        );
+
+        matching_items.sort();
+        matching_items
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn within_recurse<D>(
+        &self,
+        query: &[A],
+        radius: A,
+        distance_fn: &D,
+        curr_node_idx: usize,
+        matching_items: &mut Vec<Neighbour<A, T>>,
+        off: &mut [A],
+        rd: A,
+    ) where
+        D: DynDistanceMetric<A>,
+    {
+        if Self::is_stem_index(curr_node_idx) {
+            let node = &self.stems[curr_node_idx];
+            let split_dim = node.split_dim;
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] = if query[split_dim] < node.split_val {
+                [node.left, node.right]
+            } else {
+                [node.right, node.left]
+            };
+
+            self.within_recurse(
+                query,
+                radius,
+                distance_fn,
+                closer_node_idx,
+                matching_items,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if rd <= radius {
+                off[split_dim] = new_off;
+                self.within_recurse(
+                    query,
+                    radius,
+                    distance_fn,
+                    further_node_idx,
+                    matching_items,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            self.leaves[curr_node_idx - LEAF_OFFSET]
+                .iter()
+                .for_each(|(point, item)| {
+                    let distance = distance_fn.dist(query, point);
+                    if distance <= radius {
+                        matching_items.push(Neighbour {
+                            distance,
+                            item: *item,
+                        });
+                    }
+                });
+        }
+    }
+
+    #[inline]
+    fn check_dimensions(&self, point: &[A]) {
+        assert_eq!(
+            point.len(),
+            self.k,
+            "point has {} dimensions, but the tree has {}",
+            point.len(),
+            self.k
+        );
+    }
+
+    #[inline]
+    fn is_stem_index(idx: usize) -> bool {
+        idx < LEAF_OFFSET
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{Chebyshev, DynDistanceMetric, Manhattan, SquaredEuclidean};
+    use crate::float::dyn_kdtree::DynKdTree;
+    use rand::Rng;
+
+    type AX = f64;
+
+    fn assert_queries_match_a_linear_search<D: DynDistanceMetric<AX>>(distance_fn: &D) {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const RADIUS: AX = 0.2;
+
+        let mut rng = rand::thread_rng();
+
+        for k in [1, 3, 7] {
+            let content_to_add: Vec<(Vec<AX>, u32)> = (0..TREE_SIZE)
+                .map(|item| ((0..k).map(|_| rng.gen::<AX>()).collect(), item as u32))
+                .collect();
+
+            let mut tree: DynKdTree<AX, u32, 8> = DynKdTree::with_capacity(k, TREE_SIZE);
+            content_to_add
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+            assert_eq!(tree.size(), TREE_SIZE);
+
+            for _ in 0..NUM_QUERIES {
+                let query_point: Vec<AX> = (0..k).map(|_| rng.gen::<AX>()).collect();
+
+                let expected_nearest = content_to_add
+                    .iter()
+                    .map(|(point, _)| distance_fn.dist(&query_point, point))
+                    .fold(AX::INFINITY, AX::min);
+                let expected_within = content_to_add
+                    .iter()
+                    .filter(|(point, _)| distance_fn.dist(&query_point, point) <= RADIUS)
+                    .count();
+
+                assert_eq!(
+                    tree.nearest_one(&query_point, distance_fn).0,
+                    expected_nearest
+                );
+                assert_eq!(
+                    tree.within(&query_point, RADIUS, distance_fn).len(),
+                    expected_within
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn queries_match_a_linear_search() {
+        assert_queries_match_a_linear_search(&SquaredEuclidean);
+    }
+
+    #[test]
+    fn queries_using_other_metrics_match_a_linear_search() {
+        assert_queries_match_a_linear_search(&Manhattan);
+        assert_queries_match_a_linear_search(&Chebyshev);
+    }
+
+    #[test]
+    fn can_add_many_points_with_identical_coordinates() {
+        const NUM_DUPLICATES: u32 = 1000;
+        let point: [AX; 3] = [1.0, 2.0, 3.0];
+
+        let mut tree: DynKdTree<AX, u32, 32> = DynKdTree::new(3);
+        for item in 0..NUM_DUPLICATES {
+            tree.add(&point, item);
+        }
+        tree.add(&[5.0, 5.0, 5.0], NUM_DUPLICATES);
+
+        fn height(tree: &DynKdTree<AX, u32, 32>, node_idx: usize) -> usize {
+            if !DynKdTree::<AX, u32, 32>::is_stem_index(node_idx) {
+                return 0;
+            }
+            let node = &tree.stems[node_idx];
+            1 + height(tree, node.left).max(height(tree, node.right))
+        }
+
+        assert_eq!(tree.size(), NUM_DUPLICATES as usize + 1);
+        // a chain of stems would be around 60 levels deep
+        let height = height(&tree, tree.root_index);
+        assert!(height <= 15, "height was {}", height);
+        assert_eq!(tree.nearest_one(&point, &SquaredEuclidean), (0.0, 0));
+        assert_eq!(
+            tree.nearest_one(&[4.0, 5.0, 5.0], &SquaredEuclidean),
+            (1.0, NUM_DUPLICATES)
+        );
+        assert_eq!(
+            tree.within(&point, 0.1, &SquaredEuclidean).len(),
+            NUM_DUPLICATES as usize
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "point has 2 dimensions, but the tree has 3")]
+    fn adding_a_point_with_the_wrong_number_of_dimensions_panics() {
+        let mut tree: DynKdTree<AX, u32, 8> = DynKdTree::new(3);
+
+        tree.add(&[1.0, 2.0], 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "point has 4 dimensions, but the tree has 3")]
+    fn querying_with_the_wrong_number_of_dimensions_panics() {
+        let mut tree: DynKdTree<AX, u32, 8> = DynKdTree::new(3);
+        tree.add(&[1.0, 2.0, 3.0], 100);
+
+        tree.nearest_one(&[1.0, 2.0, 3.0, 4.0], &SquaredEuclidean);
+    }
+}
@@ -0,0 +1,585 @@
+//! An immutable floating point k-d tree, optimised for trees that are built once and then
+//! only queried.
+
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use az::{Az, Cast};
+use core::ops::Rem;
+use divrem::DivCeil;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree, StemNode};
+use crate::float::neighbour::Neighbour;
+use crate::mirror_select_nth_unstable_by::mirror_select_nth_unstable_by;
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+/// Immutable floating point k-d tree
+///
+/// Built in one go from a set of points, in the same balanced way as
+/// [`KdTree::from_slice`], after which it cannot be changed. Rather than each leaf
+/// reserving space for `B` points, the points and items of every leaf are stored one
+/// after another in a single contiguous array. This uses less memory than a [`KdTree`]
+/// containing the same points, and keeps the points that queries visit closer together,
+/// which makes better use of the CPU cache.
+///
+/// The generic parameters are the same as for [`KdTree`], with `B` being the largest
+/// number of points that are stored in a single leaf.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::immutable::ImmutableKdTree;
+/// use kiddo::float::distance::squared_euclidean;
+///
+/// let points = vec![([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)];
+///
+/// let tree: ImmutableKdTree<f64, u32, 3, 32, u32> = ImmutableKdTree::from_slice(&points);
+///
+/// assert_eq!(tree.size(), 2);
+/// assert_eq!(tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean).1, 100);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImmutableKdTree<
+    A: Copy + Default,
+    T: Copy + Default,
+    const K: usize,
+    const B: usize,
+    IDX,
+> {
+    stems: Vec<StemNode<A, K, IDX>>,
+    points: Vec<[A; K]>,
+    items: Vec<T>,
+    // the entries of leaf `n` are those between leaf_starts[n] and leaf_starts[n + 1]
+    leaf_starts: Vec<usize>,
+    root_index: IDX,
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    ImmutableKdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Creates an immutable tree containing all of the `(point, item)` pairs in `points`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::immutable::ImmutableKdTree;
+    ///
+    /// let points = vec![([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)];
+    ///
+    /// let tree: ImmutableKdTree<f64, u32, 3, 32, u32> = ImmutableKdTree::from_slice(&points);
+    ///
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn from_slice(points: &[([A; K], T)]) -> Self {
+        assert!(points.len() <= <IDX as Index>::capacity_with_bucket_size(B));
+
+        let (mut content_points, mut content_items): (Vec<[A; K]>, Vec<T>) =
+            points.iter().copied().unzip();
+
+        let mut tree = Self {
+            stems: Vec::with_capacity(DivCeil::div_ceil(points.len(), B.max(1))),
+            points: Vec::with_capacity(points.len()),
+            items: Vec::with_capacity(points.len()),
+            leaf_starts: Vec::with_capacity(DivCeil::div_ceil(points.len(), B.max(1)) * 2 + 1),
+            root_index: <IDX as Index>::leaf_offset(),
+        };
+        tree.leaf_starts.push(0);
+        tree.root_index = tree.build(&mut content_points, &mut content_items, 0);
+
+        tree
+    }
+
+    fn build(&mut self, points: &mut [[A; K]], items: &mut [T], split_dim: usize) -> IDX {
+        if points.len() <= B {
+            self.points.extend_from_slice(points);
+            self.items.extend_from_slice(items);
+            self.leaf_starts.push(self.points.len());
+
+            return (self.leaf_starts.len() - 2).az::<IDX>() + IDX::leaf_offset();
+        }
+
+        let pivot_idx = points.len() / 2;
+        mirror_select_nth_unstable_by(points, items, pivot_idx, |a, b| unsafe {
+            a.index_unchecked(split_dim)
+                .partial_cmp(b.index_unchecked(split_dim))
+                .expect("Bulk construction sort failed.")
+        });
+        let split_val = points[pivot_idx][split_dim];
+
+        let stem_idx = self.stems.len();
+        // the bounding box of each stem is left empty, as no queries on an immutable
+        // tree use it
+        self.stems.push(StemNode::new(
+            IDX::zero(),
+            IDX::zero(),
+            split_val,
+            split_dim,
+        ));
+
+        let next_split_dim = (split_dim + 1).rem(K);
+        let (left_points, right_points) = points.split_at_mut(pivot_idx);
+        let (left_items, right_items) = items.split_at_mut(pivot_idx);
+        let left = self.build(left_points, left_items, next_split_dim);
+        let right = self.build(right_points, right_items, next_split_dim);
+
+        let stem_node = &mut self.stems[stem_idx];
+        stem_node.left = left;
+        stem_node.right = right;
+
+        stem_idx.az::<IDX>()
+    }
+
+    /// Returns the number of elements stored in the tree
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by the tree's nodes,
+    /// based on the capacity that has been allocated for them.
+    ///
+    /// Comparable with [`KdTree::memory_usage`].
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        self.stems.capacity() * core::mem::size_of::<StemNode<A, K, IDX>>()
+            + self.points.capacity() * core::mem::size_of::<[A; K]>()
+            + self.items.capacity() * core::mem::size_of::<T>()
+            + self.leaf_starts.capacity() * core::mem::size_of::<usize>()
+    }
+
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function. See [`KdTree::nearest_one`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::immutable::ImmutableKdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let points = vec![([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)];
+    /// let tree: ImmutableKdTree<f64, u32, 3, 32, u32> = ImmutableKdTree::from_slice(&points);
+    ///
+    /// let nearest = tree.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean);
+    ///
+    /// assert!((nearest.0 - 0.01f64).abs() < f64::EPSILON);
+    /// assert_eq!(nearest.1, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best_dist = A::max_value();
+        let mut best_item = T::zero();
+
+        unsafe {
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                self.root_index,
+                0,
+                &mut best_item,
+                &mut best_dist,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        (best_dist, best_item)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_one_recurse<D>(
+        &self,
+        query: &[A; K],
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        split_dim: usize,
+        best_item: &mut T,
+        best_dist: &mut A,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+            let next_split_dim = (split_dim + 1).rem(K);
+
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                closer_node_idx,
+                next_split_dim,
+                best_item,
+                best_dist,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if rd <= *best_dist {
+                off[split_dim] = new_off;
+                self.nearest_one_recurse(
+                    query,
+                    distance_fn,
+                    further_node_idx,
+                    next_split_dim,
+                    best_item,
+                    best_dist,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            self.leaf_entries(curr_node_idx).for_each(|(entry, &item)| {
+                let dist = distance_fn.dist(query, entry);
+                if dist < *best_dist || (dist == *best_dist && item < *best_item) {
+                    *best_dist = dist;
+                    *best_item = item;
+                }
+            });
+        }
+    }
+
+    /// Finds the nearest `qty` elements to `query`, using the specified distance metric
+    /// function. See [`KdTree::nearest_n`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::immutable::ImmutableKdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let points = vec![([1.0, 2.0, 5.0], 100), ([2.0, 3.0, 6.0], 101)];
+    /// let tree: ImmutableKdTree<f64, u32, 3, 32, u32> = ImmutableKdTree::from_slice(&points);
+    ///
+    /// let nearest = tree.nearest_n(&[1.0, 2.0, 5.1], 1, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest.len(), 1);
+    /// assert_eq!(nearest[0].item, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_n<D>(&self, query: &[A; K], qty: usize, distance_fn: &D) -> Vec<Neighbour<A, T>>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut result: BinaryHeap<Neighbour<A, T>> = BinaryHeap::with_capacity(qty);
+
+        if qty > 0 {
+            unsafe {
+                self.nearest_n_recurse(
+                    query,
+                    qty,
+                    distance_fn,
+                    self.root_index,
+                    0,
+                    &mut result,
+                    &mut off,
+                    A::zero(),
+                )
+            }
+        }
+
+        result.into_sorted_vec()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_n_recurse<D>(
+        &self,
+        query: &[A; K],
+        qty: usize,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        split_dim: usize,
+        results: &mut BinaryHeap<Neighbour<A, T>>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+            let next_split_dim = (split_dim + 1).rem(K);
+
+            self.nearest_n_recurse(
+                query,
+                qty,
+                distance_fn,
+                closer_node_idx,
+                next_split_dim,
+                results,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if results.len() < qty || rd < results.peek().unwrap().distance {
+                off[split_dim] = new_off;
+                self.nearest_n_recurse(
+                    query,
+                    qty,
+                    distance_fn,
+                    further_node_idx,
+                    next_split_dim,
+                    results,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            self.leaf_entries(curr_node_idx).for_each(|(entry, &item)| {
+                let distance = distance_fn.dist(query, entry);
+                if results.len() < qty {
+                    results.push(Neighbour { distance, item });
+                } else {
+                    let mut top = results.peek_mut().unwrap();
+                    if distance < top.distance {
+                        *top = Neighbour { distance, item };
+                    }
+                }
+            });
+        }
+    }
+
+    /// Finds all elements within `dist` of `query`, using the specified distance metric
+    /// function. Results are returned sorted nearest-first. See [`KdTree::within`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::immutable::ImmutableKdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let points = vec![
+    ///     ([1.0, 2.0, 5.0], 100),
+    ///     ([2.0, 3.0, 6.0], 101),
+    ///     ([200.0, 300.0, 600.0], 102),
+    /// ];
+    /// let tree: ImmutableKdTree<f64, u32, 3, 32, u32> = ImmutableKdTree::from_slice(&points);
+    ///
+    /// let within = tree.within(&[1.0, 2.0, 5.0], 10f64, &squared_euclidean);
+    ///
+    /// assert_eq!(within.len(), 2);
+    /// ```
+    #[inline]
+    pub fn within<D>(&self, query: &[A; K], dist: A, distance_fn: &D) -> Vec<Neighbour<A, T>>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut matching_items = Vec::new();
+
+        unsafe {
+            self.within_recurse(
+                query,
+                dist,
+                distance_fn,
+                self.root_index,
+                0,
+                &mut matching_items,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        matching_items.sort();
+        matching_items
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn within_recurse<D>(
+        &self,
+        query: &[A; K],
+        radius: A,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        split_dim: usize,
+        matching_items: &mut Vec<Neighbour<A, T>>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+            let next_split_dim = (split_dim + 1).rem(K);
+
+            self.within_recurse(
+                query,
+                radius,
+                distance_fn,
+                closer_node_idx,
+                next_split_dim,
+                matching_items,
+                off,
+                rd,
+            );
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if rd <= radius {
+                off[split_dim] = new_off;
+                self.within_recurse(
+                    query,
+                    radius,
+                    distance_fn,
+                    further_node_idx,
+                    next_split_dim,
+                    matching_items,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            self.leaf_entries(curr_node_idx).for_each(|(entry, &item)| {
+                let distance = distance_fn.dist(query, entry);
+                if distance <= radius {
+                    matching_items.push(Neighbour { distance, item });
+                }
+            });
+        }
+    }
+
+    unsafe fn leaf_entries(&self, leaf_idx: IDX) -> impl Iterator<Item = (&[A; K], &T)> {
+        let leaf_idx = (leaf_idx - IDX::leaf_offset()).az::<usize>();
+        let start = *self.leaf_starts.index_unchecked(leaf_idx);
+        let end = *self.leaf_starts.index_unchecked(leaf_idx + 1);
+
+        self.points
+            .index_unchecked(start..end)
+            .iter()
+            .zip(self.items.index_unchecked(start..end).iter())
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Converts the tree into an [`ImmutableKdTree`] containing the same items.
+    ///
+    /// The items are rebuilt into a balanced tree with every point stored contiguously,
+    /// which uses less memory and is quicker to query, but can no longer be changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let immutable = tree.into_immutable();
+    ///
+    /// assert_eq!(immutable.size(), 2);
+    /// assert_eq!(immutable.nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean).1, 100);
+    /// ```
+    pub fn into_immutable(self) -> ImmutableKdTree<A, T, K, B, IDX> {
+        let points: Vec<([A; K], T)> = self.iter().map(|(&point, &item)| (point, item)).collect();
+
+        ImmutableKdTree::from_slice(&points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{squared_euclidean, Manhattan};
+    use crate::float::immutable::ImmutableKdTree;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn queries_match_the_mutable_tree() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let immutable = tree.clone().into_immutable();
+        assert_eq!(immutable.size(), TREE_SIZE);
+        assert!(immutable.memory_usage() < tree.memory_usage());
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            assert_eq!(
+                immutable.nearest_one(&query_point, &squared_euclidean).0,
+                tree.nearest_one(&query_point, &squared_euclidean).0
+            );
+            assert_eq!(
+                immutable.nearest_n(&query_point, 10, &Manhattan),
+                tree.nearest_n(&query_point, 10, &Manhattan)
+            );
+            assert_eq!(
+                immutable.within(&query_point, 0.01, &squared_euclidean),
+                tree.within(&query_point, 0.01, &squared_euclidean)
+            );
+        }
+    }
+
+    #[test]
+    fn can_query_an_empty_tree() {
+        let tree: ImmutableKdTree<AX, u32, 2, 4, u32> = ImmutableKdTree::from_slice(&[]);
+
+        assert_eq!(tree.size(), 0);
+        assert_eq!(
+            tree.nearest_one(&[0.5, 0.5], &squared_euclidean),
+            (AX::MAX, 0)
+        );
+        assert!(tree
+            .nearest_n(&[0.5, 0.5], 3, &squared_euclidean)
+            .is_empty());
+        assert!(tree.within(&[0.5, 0.5], 1.0, &squared_euclidean).is_empty());
+    }
+}
@@ -1,10 +1,17 @@
 //! Floating point k-d tree, for use when the co-ordinates of the points being stored in the tree
-//! are floats. f64 or f32 are supported currently.
+//! are floats. f64 or f32 are supported currently, as is `half::f16` when the `half` feature
+//! is enabled.
 
+#[cfg(feature = "serialize")]
+use alloc::vec;
+use alloc::vec::Vec;
 use az::{Az, Cast};
-use num_traits::Float;
-use std::cmp::PartialEq;
-use std::fmt::Debug;
+use core::ops::Neg;
+use num_traits::{Bounded, Float, Num, NumCast};
+use core::cmp::PartialEq;
+use core::fmt::Debug;
+#[cfg(feature = "serialize")]
+use core::ops::Rem;
 use divrem::DivCeil;
 
 #[cfg(feature = "serialize")]
@@ -15,25 +22,466 @@ use serde::{Deserialize, Serialize};
 
 /// Axis trait represents the traits that must be implemented
 /// by the type that is used as the first generic parameter, `A`,
-/// on the float `KdTree`. This will be `f64` or `f32`.
-pub trait Axis: Float + Default + Debug + Copy + Sync {}
-impl<T: Float + Default + Debug + Copy + Sync> Axis for T {}
+/// on the float `KdTree`. This will be `f64` or `f32`, or `half::f16` if the
+/// `half` feature is enabled, or `ordered_float::NotNan<f64>` or `NotNan<f32>` if the
+/// `ordered-float` feature is enabled.
+///
+/// `f16` halves the memory needed to store each point compared to `f32`, but only
+/// has around three significant decimal digits of precision. Points that are
+/// distinct in `f32` may round to the same `f16` value, and distances that differ
+/// slightly will often round to the same `f16` distance. Such distances are treated
+/// as exact ties, so queries like `nearest_one` will pick between them using the tie-break
+/// rule rather than by which point is truly closer.
+///
+/// `NotNan` co-ordinates guarantee at compile time that every point can be ordered
+/// against the others in the tree. As with any other arithmetic on `NotNan`, an operation
+/// on the co-ordinates that gives NaN, such as taking the square root of a negative
+/// distance, panics.
+///
+/// Before version 3, this was implemented for every type that implements
+/// `num_traits::Float`. The methods that are needed by the tree are now methods of `Axis`
+/// itself, so that types that cannot represent NaN can implement it.
+pub trait Axis:
+    Num + NumCast + Bounded + PartialOrd + Neg<Output = Self> + Default + Debug + Copy + Sync
+{
+    /// Returns positive infinity.
+    fn infinity() -> Self;
+
+    /// Returns negative infinity.
+    fn neg_infinity() -> Self;
+
+    /// Returns `true` if this value is NaN.
+    fn is_nan(self) -> bool;
+
+    /// Returns the absolute value of `self`.
+    fn abs(self) -> Self;
+
+    /// Returns the square root of `self`.
+    fn sqrt(self) -> Self;
+
+    /// Returns `self` raised to the integer power `n`.
+    fn powi(self, n: i32) -> Self;
+
+    /// Returns `self` raised to the power `n`.
+    fn powf(self, n: Self) -> Self;
+
+    /// Returns `1 / self`.
+    fn recip(self) -> Self;
+
+    /// Returns the largest integer less than or equal to `self`.
+    fn floor(self) -> Self;
+
+    /// Returns the sine of `self`, in radians.
+    fn sin(self) -> Self;
+
+    /// Returns the cosine of `self`, in radians.
+    fn cos(self) -> Self;
+
+    /// Returns the arcsine of `self`, in radians.
+    fn asin(self) -> Self;
+
+    /// Returns the smaller of `self` and `other`, ignoring NaN.
+    fn min(self, other: Self) -> Self;
+
+    /// Returns the larger of `self` and `other`, ignoring NaN.
+    fn max(self, other: Self) -> Self;
+}
+
+macro_rules! impl_axis_for_float {
+    ($($float:ty)*) => {
+        $(
+            impl Axis for $float {
+                #[inline]
+                fn infinity() -> Self {
+                    Float::infinity()
+                }
+
+                #[inline]
+                fn neg_infinity() -> Self {
+                    Float::neg_infinity()
+                }
+
+                #[inline]
+                fn is_nan(self) -> bool {
+                    Float::is_nan(self)
+                }
+
+                #[inline]
+                fn abs(self) -> Self {
+                    Float::abs(self)
+                }
+
+                #[inline]
+                fn sqrt(self) -> Self {
+                    Float::sqrt(self)
+                }
+
+                #[inline]
+                fn powi(self, n: i32) -> Self {
+                    Float::powi(self, n)
+                }
+
+                #[inline]
+                fn powf(self, n: Self) -> Self {
+                    Float::powf(self, n)
+                }
+
+                #[inline]
+                fn recip(self) -> Self {
+                    Float::recip(self)
+                }
+
+                #[inline]
+                fn floor(self) -> Self {
+                    Float::floor(self)
+                }
+
+                #[inline]
+                fn sin(self) -> Self {
+                    Float::sin(self)
+                }
+
+                #[inline]
+                fn cos(self) -> Self {
+                    Float::cos(self)
+                }
+
+                #[inline]
+                fn asin(self) -> Self {
+                    Float::asin(self)
+                }
+
+                #[inline]
+                fn min(self, other: Self) -> Self {
+                    Float::min(self, other)
+                }
+
+                #[inline]
+                fn max(self, other: Self) -> Self {
+                    Float::max(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_axis_for_float! { f32 f64 }
+
+#[cfg(feature = "half")]
+impl_axis_for_float! { half::f16 }
+
+// applies a float operation to the values inside `NotNan`s, panicking if it gives NaN
+#[cfg(feature = "ordered-float")]
+macro_rules! not_nan {
+    ($op:expr) => {
+        ordered_float::NotNan::new($op).expect("operation on NotNan co-ordinates gave NaN")
+    };
+}
+
+#[cfg(feature = "ordered-float")]
+macro_rules! impl_axis_for_not_nan {
+    ($($float:ty)*) => {
+        $(
+            impl Axis for ordered_float::NotNan<$float> {
+                #[inline]
+                fn infinity() -> Self {
+                    not_nan!(<$float>::INFINITY)
+                }
+
+                #[inline]
+                fn neg_infinity() -> Self {
+                    not_nan!(<$float>::NEG_INFINITY)
+                }
+
+                #[inline]
+                fn is_nan(self) -> bool {
+                    false
+                }
+
+                #[inline]
+                fn abs(self) -> Self {
+                    not_nan!(Float::abs(self.into_inner()))
+                }
+
+                #[inline]
+                fn sqrt(self) -> Self {
+                    not_nan!(Float::sqrt(self.into_inner()))
+                }
+
+                #[inline]
+                fn powi(self, n: i32) -> Self {
+                    not_nan!(Float::powi(self.into_inner(), n))
+                }
+
+                #[inline]
+                fn powf(self, n: Self) -> Self {
+                    not_nan!(Float::powf(self.into_inner(), n.into_inner()))
+                }
+
+                #[inline]
+                fn recip(self) -> Self {
+                    not_nan!(Float::recip(self.into_inner()))
+                }
+
+                #[inline]
+                fn floor(self) -> Self {
+                    not_nan!(Float::floor(self.into_inner()))
+                }
+
+                #[inline]
+                fn sin(self) -> Self {
+                    not_nan!(Float::sin(self.into_inner()))
+                }
+
+                #[inline]
+                fn cos(self) -> Self {
+                    not_nan!(Float::cos(self.into_inner()))
+                }
+
+                #[inline]
+                fn asin(self) -> Self {
+                    not_nan!(Float::asin(self.into_inner()))
+                }
+
+                #[inline]
+                fn min(self, other: Self) -> Self {
+                    Ord::min(self, other)
+                }
+
+                #[inline]
+                fn max(self, other: Self) -> Self {
+                    Ord::max(self, other)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "ordered-float")]
+impl_axis_for_not_nan! { f32 f64 }
+
+/// How a full leaf is split in two when an item is [`add`](KdTree::add)ed to it.
+///
+/// The strategy is chosen when the tree is created, using
+/// [`KdTreeBuilder::with_split_strategy`](crate::float::builder::KdTreeBuilder::with_split_strategy).
+/// It only affects leaves that are split as items are added: trees built in one go by
+/// [`from_slice`](KdTree::from_slice), [`rebalance`](KdTree::rebalance) or
+/// [`merge`](KdTree::merge) are always split at the median.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serialize_rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SplitStrategy {
+    /// Splits the leaf at the median of its points along the split axis.
+    ///
+    /// Each half gets half of the points, so leaves are always at least half full and
+    /// memory usage is predictable. However, in clustered data, the split can land
+    /// inside a cluster, giving long, thin cells that extend far into the empty space
+    /// around it, which queries near the cluster then have to visit.
+    #[default]
+    Median,
+
+    /// Splits the leaf halfway between the smallest and largest of its points along the
+    /// split axis.
+    ///
+    /// Cells stay closer to cube-shaped, and splits tend to fall in the gaps between
+    /// clusters rather than through them, which can make queries on clustered data
+    /// faster. The halves can be very uneven though, so more leaves may be needed,
+    /// and the tree can end up deeper. If every point would end up on the same side,
+    /// the leaf is split at the median instead.
+    Midpoint,
+}
+
+/// How the axis that a full leaf is split along is chosen when an item is
+/// [`add`](KdTree::add)ed to it.
+///
+/// Like [`SplitStrategy`], this is chosen when the tree is created, using
+/// [`KdTreeBuilder::with_split_dim_strategy`](crate::float::builder::KdTreeBuilder::with_split_dim_strategy),
+/// and only affects leaves that are split as items are added. Trees built in one go by
+/// [`from_slice`](KdTree::from_slice), [`rebalance`](KdTree::rebalance) or
+/// [`merge`](KdTree::merge) always split along each axis in turn.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serialize_rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SplitDimStrategy {
+    /// Splits along each axis in turn, so that a leaf is split along the axis after the
+    /// one that its parent splits along.
+    ///
+    /// This is the classic k-d tree layout, and works well when the points are spread
+    /// over a similar range along every axis.
+    #[default]
+    RoundRobin,
+
+    /// Splits along the axis that the leaf's points have the greatest variance along.
+    ///
+    /// When the points are spread much further along some axes than others, or
+    /// the spread differs from one part of the space to another, this keeps leaves
+    /// closer to cube-shaped, which lets queries skip more of the tree. Working out the
+    /// variances makes each split a little slower. If the points have no variance along
+    /// any axis, the leaf is split as it would be by `RoundRobin`.
+    MaxVariance,
+}
 
 /// Floating point k-d tree
 ///
 /// For use when the co-ordinates of the points being stored in the tree
 /// are floats. f64 or f32 are supported currently
+///
+/// # Sharing between threads
+///
+/// Queries only need a shared reference to the tree, and never modify it, so a tree
+/// whose axis and item types are `Send` and `Sync` is itself `Send` and `Sync`. Once
+/// it has been built, it can be shared between threads, for example in an
+/// [`Arc`](alloc::sync::Arc), and queried from all of them at once without any locking.
+/// Modifying the tree needs a `&mut` reference, so the borrow checker ensures that
+/// no queries are running while it is being changed.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::thread;
+/// use kiddo::float::kdtree::KdTree;
+/// use kiddo::distance::squared_euclidean;
+///
+/// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+/// tree.add(&[1.0, 2.0, 5.0], 100);
+/// tree.add(&[2.0, 3.0, 6.0], 101);
+///
+/// let tree = Arc::new(tree);
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|i| {
+///         let tree = Arc::clone(&tree);
+///         thread::spawn(move || tree.nearest_one(&[i as f64, 2.0, 5.0], &squared_euclidean))
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     assert!(handle.join().unwrap().1 >= 100);
+/// }
+/// ```
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serialize",
+    serde(
+        from = "DeserializedKdTree<A, T, K, B, IDX>",
+        bound(deserialize = "DeserializedKdTree<A, T, K, B, IDX>: Deserialize<'de>, \
+            KdTree<A, T, K, B, IDX>: From<DeserializedKdTree<A, T, K, B, IDX>>")
+    )
+)]
 #[cfg_attr(
     feature = "serialize_rkyv",
     derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
 )]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct KdTree<A: Copy + Default, T: Copy + Default, const K: usize, const B: usize, IDX> {
     pub(crate) leaves: Vec<LeafNode<A, T, K, B, IDX>>,
     pub(crate) stems: Vec<StemNode<A, K, IDX>>,
     pub(crate) root_index: IDX,
     pub(crate) size: T,
+    // nodes that have been removed from the tree by `remove`, for reuse by `split`
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub(crate) free_leaves: Vec<IDX>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub(crate) free_stems: Vec<IDX>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub(crate) split_strategy: SplitStrategy,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub(crate) split_dim_strategy: SplitDimStrategy,
+}
+
+// trees are deserialized into this first, so that the split axes of stems serialized
+// before they were stored, and with the bbox-cache feature the stems' bounding boxes,
+// can be filled in
+#[doc(hidden)]
+#[cfg(feature = "serialize")]
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "A: Axis + Deserialize<'de>, T: Deserialize<'de>, IDX: Deserialize<'de>"))]
+pub struct DeserializedKdTree<
+    A: Copy + Default,
+    T: Copy + Default,
+    const K: usize,
+    const B: usize,
+    IDX,
+> {
+    leaves: Vec<LeafNode<A, T, K, B, IDX>>,
+    stems: Vec<StemNode<A, K, IDX>>,
+    root_index: IDX,
+    size: T,
+    #[serde(default)]
+    free_leaves: Vec<IDX>,
+    #[serde(default)]
+    free_stems: Vec<IDX>,
+    #[serde(default)]
+    split_strategy: SplitStrategy,
+    #[serde(default)]
+    split_dim_strategy: SplitDimStrategy,
+}
+
+#[cfg(feature = "serialize")]
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    From<DeserializedKdTree<A, T, K, B, IDX>> for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    fn from(tree: DeserializedKdTree<A, T, K, B, IDX>) -> Self {
+        let mut tree = KdTree {
+            leaves: tree.leaves,
+            stems: tree.stems,
+            root_index: tree.root_index,
+            size: tree.size,
+            free_leaves: tree.free_leaves,
+            free_stems: tree.free_stems,
+            split_strategy: tree.split_strategy,
+            split_dim_strategy: tree.split_dim_strategy,
+        };
+
+        // trees serialized by versions that did not store the split axis of each stem
+        // split along each axis in turn, starting with the first at the root
+        if tree
+            .stems
+            .iter()
+            .any(|stem| stem.split_dim == MISSING_SPLIT_DIM)
+        {
+            let mut to_visit = vec![(tree.root_index, 0)];
+            // the number of stems visited is limited so that this ends even if the
+            // deserialized tree is malformed and has a cycle
+            let mut unvisited = tree.stems.len();
+            while let Some((node_idx, split_dim)) = to_visit.pop() {
+                if !KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) || unvisited == 0 {
+                    continue;
+                }
+                unvisited -= 1;
+
+                if let Some(stem) = tree.stems.get_mut(node_idx.az::<usize>()) {
+                    if stem.split_dim == MISSING_SPLIT_DIM {
+                        stem.split_dim = split_dim;
+                    }
+                    let next_split_dim = (stem.split_dim + 1).rem(K);
+                    to_visit.push((stem.left, next_split_dim));
+                    to_visit.push((stem.right, next_split_dim));
+                }
+            }
+        }
+
+        #[cfg(feature = "bbox-cache")]
+        tree.update_all_stem_bboxes();
+
+        tree
+    }
+}
+
+// stands in for the split axis of stems serialized before it was stored
+#[cfg(feature = "serialize")]
+const MISSING_SPLIT_DIM: usize = usize::MAX;
+
+#[cfg(feature = "serialize")]
+fn missing_split_dim() -> usize {
+    MISSING_SPLIT_DIM
 }
 
 #[doc(hidden)]
@@ -47,6 +495,73 @@ pub struct StemNode<A: Copy + Default, const K: usize, IDX> {
     pub(crate) left: IDX,
     pub(crate) right: IDX,
     pub(crate) split_val: A,
+    #[cfg_attr(feature = "serialize", serde(default = "missing_split_dim"))]
+    pub(crate) split_dim: usize,
+
+    // the bounding box is left out when serializing, so that trees serialized with and
+    // without the bbox-cache feature are the same
+    #[cfg(feature = "bbox-cache")]
+    #[cfg_attr(feature = "serialize", serde(skip, bound(deserialize = "A: Axis")))]
+    #[cfg_attr(feature = "serialize_rkyv", with(rkyv::with::Skip))]
+    pub(crate) bbox: StemBbox<A, K>,
+}
+
+/// Corners of a box containing every point stored under a stem. Removing points does not
+/// shrink it, so it can be larger than the smallest such box.
+///
+/// The default box covers all of space, so that a stem whose box has not been computed,
+/// such as one deserialized using rkyv, is never skipped over by queries.
+#[cfg(feature = "bbox-cache")]
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StemBbox<A, const K: usize> {
+    pub(crate) min: [A; K],
+    pub(crate) max: [A; K],
+}
+
+#[cfg(feature = "bbox-cache")]
+impl<A: Axis, const K: usize> Default for StemBbox<A, K> {
+    fn default() -> Self {
+        Self {
+            min: [A::neg_infinity(); K],
+            max: [A::infinity(); K],
+        }
+    }
+}
+
+impl<A: Axis, const K: usize, IDX> StemNode<A, K, IDX> {
+    /// Creates a stem whose bounding box, if cached, is empty until points are added to it.
+    pub(crate) fn new(left: IDX, right: IDX, split_val: A, split_dim: usize) -> Self {
+        Self {
+            left,
+            right,
+            split_val,
+            split_dim,
+            #[cfg(feature = "bbox-cache")]
+            bbox: StemBbox {
+                min: [A::infinity(); K],
+                max: [A::neg_infinity(); K],
+            },
+        }
+    }
+
+    /// Grows the stem's bounding box to contain `point`.
+    #[cfg(feature = "bbox-cache")]
+    #[inline]
+    pub(crate) fn extend_bbox(&mut self, point: &[A; K]) {
+        let bbox = &mut self.bbox;
+        for ((min, max), &val) in bbox.min.iter_mut().zip(bbox.max.iter_mut()).zip(point) {
+            *min = min.min(val);
+            *max = max.max(val);
+        }
+    }
+
+    /// Returns `true` if the stem's bounding box overlaps the box with corners `min`
+    /// and `max`, which it never does if nothing has been added under the stem.
+    #[cfg(feature = "bbox-cache")]
+    #[inline]
+    pub(crate) fn bbox_overlaps(&self, min: &[A; K], max: &[A; K]) -> bool {
+        (0..K).all(|dim| self.bbox.min[dim] <= max[dim] && min[dim] <= self.bbox.max[dim])
+    }
 }
 
 #[doc(hidden)]
@@ -121,7 +636,14 @@ where
         KdTree::with_capacity(B * 10)
     }
 
-    /// Creates a new float KdTree and reserve capacity for a specific number of items.
+    /// Creates a new float KdTree with room for `num_points` points to be added without
+    /// it needing to reallocate.
+    ///
+    /// `num_points` is the number of points that the tree is expected to hold, not the
+    /// number of leaves. Since leaves are split at the median as points are added, each
+    /// one ends up between half full and full, so as with [`reserve`](KdTree::reserve),
+    /// room is made for `2 * num_points / B` leaves and stems. Trees using
+    /// [`SplitStrategy::Midpoint`] can end up with emptier leaves, and so may need more.
     ///
     /// # Examples
     ///
@@ -134,16 +656,27 @@ where
     ///
     /// assert_eq!(tree.size(), 1);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bucket size `B` is less than 2, as a leaf holding a single point
+    /// cannot be split in two.
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
-        assert!(capacity <= <IDX as Index>::capacity_with_bucket_size(B));
+    pub fn with_capacity(num_points: usize) -> Self {
+        assert!(B >= 2, "bucket size B must be at least 2");
+        assert!(num_points <= <IDX as Index>::capacity_with_bucket_size(B));
         let mut tree = Self {
             size: T::zero(),
-            stems: Vec::with_capacity(capacity.max(1).ilog2() as usize),
-            leaves: Vec::with_capacity(DivCeil::div_ceil(capacity, B.az::<usize>())),
+            stems: Vec::new(),
+            leaves: Vec::new(),
             root_index: <IDX as Index>::leaf_offset(),
+            free_leaves: Vec::new(),
+            free_stems: Vec::new(),
+            split_strategy: SplitStrategy::default(),
+            split_dim_strategy: SplitDimStrategy::default(),
         };
 
+        tree.reserve(num_points);
         tree.leaves.push(LeafNode::new());
 
         tree
@@ -168,54 +701,811 @@ where
         self.size
     }
 
-    pub(crate) fn is_stem_index(x: IDX) -> bool {
-        x < <IDX as Index>::leaf_offset()
+    /// Returns the current number of elements stored in the tree, as a `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.1, 2.1, 5.1], 101);
+    ///
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize
+    where
+        T: Cast<usize>,
+    {
+        self.size.az::<usize>()
     }
-}
-
-impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> From<&Vec<[A; K]>>
-    for KdTree<A, T, K, B, IDX>
-where
-    usize: Cast<IDX>,
-    usize: Cast<T>,
-{
-    fn from(vec: &Vec<[A; K]>) -> Self {
-        let mut tree: KdTree<A, T, K, B, IDX> = KdTree::with_capacity(vec.len());
 
-        vec.iter().enumerate().for_each(|(idx, pos)| {
-            tree.add(pos, idx.az::<T>());
-        });
-
-        tree
+    /// Returns `true` if there are no elements stored in the tree
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    /// assert!(tree.is_empty());
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// assert!(!tree.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size == T::zero()
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::float::kdtree::KdTree;
-    type AX = f64;
-
-    #[test]
-    fn it_can_be_constructed_with_new() {
-        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::new();
 
-        assert_eq!(tree.size(), 0);
+    /// Returns the [`SplitStrategy`] used when leaves are split as items are added
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::{KdTree, SplitStrategy};
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.split_strategy(), SplitStrategy::Median);
+    /// ```
+    #[inline]
+    pub fn split_strategy(&self) -> SplitStrategy {
+        self.split_strategy
     }
 
-    #[test]
-    fn it_can_be_constructed_with_a_defined_capacity() {
-        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(10);
+    /// Returns the [`SplitDimStrategy`] used to choose the axis that leaves are split along
+    /// as items are added
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::{KdTree, SplitDimStrategy};
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.split_dim_strategy(), SplitDimStrategy::RoundRobin);
+    /// ```
+    #[inline]
+    pub fn split_dim_strategy(&self) -> SplitDimStrategy {
+        self.split_dim_strategy
+    }
 
-        assert_eq!(tree.size(), 0);
+    /// Removes everything from the tree, keeping the memory that has been allocated
+    /// for it so that it can be reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.1, 2.1, 5.1], 101);
+    ///
+    /// tree.clear();
+    ///
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.stems.clear();
+        self.leaves.clear();
+        self.free_stems.clear();
+        self.free_leaves.clear();
+        self.leaves.push(LeafNode::new());
+        self.root_index = <IDX as Index>::leaf_offset();
+        self.size = T::zero();
     }
 
-    #[test]
-    fn it_can_be_constructed_with_a_capacity_of_zero() {
-        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(0);
+    /// Reserves capacity for at least roughly `additional` more items to be added to the
+    /// tree without it needing to reallocate.
+    ///
+    /// When a leaf is split, each half gets about `B / 2` of its items, so up to
+    /// `2 * additional / B` new leaves and stems are reserved. This is an estimate: if many
+    /// of the tree's leaves are already full, or the points are added in an order that
+    /// leaves the new leaves less than half full, more may be needed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.reserve(100_000);
+    ///
+    /// let leaf_size = KdTree::<f64, u32, 3, 32, u32>::leaf_node_memory_usage();
+    ///
+    /// assert!(tree.memory_usage() >= 100_000 / 32 * leaf_size);
+    /// ```
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let new_leaves = DivCeil::div_ceil(additional * 2, B.max(2));
+        self.leaves.reserve(new_leaves);
+        self.stems.reserve(new_leaves);
+    }
 
-        assert_eq!(tree.size(), 0);
+    /// Releases any memory allocated for stems and leaves beyond what the tree is
+    /// currently using.
+    ///
+    /// Leaves that have been emptied by removals are mostly kept for reuse, as are stems,
+    /// so this does not help a tree that has had most of its items removed as much as it
+    /// could. Call
+    /// [`rebalance`](KdTree::rebalance) first to rebuild the tree with every leaf filled,
+    /// and then `shrink_to_fit` to release the space that is no longer needed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::with_capacity(100_000);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.1, 2.1, 5.1], 101);
+    ///
+    /// let before = tree.memory_usage();
+    /// tree.shrink_to_fit();
+    ///
+    /// assert!(tree.memory_usage() < before);
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.stems.shrink_to_fit();
+        self.leaves.shrink_to_fit();
+        self.free_stems.shrink_to_fit();
+        self.free_leaves.shrink_to_fit();
     }
-    // #[cfg(feature = "serialize")]
+
+    /// Returns an iterator over every point stored in the tree, alongside its item.
+    ///
+    /// Every entry is visited exactly once, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.1, 2.1, 5.1], 101);
+    ///
+    /// let mut items: Vec<_> = tree.iter().map(|(_, &item)| item).collect();
+    /// items.sort();
+    ///
+    /// assert_eq!(items, vec![100, 101]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&[A; K], &T)> + '_ {
+        self.leaves.iter().flat_map(|leaf_node| {
+            let size = leaf_node.size.az::<usize>();
+            leaf_node.content_points[..size]
+                .iter()
+                .zip(leaf_node.content_items[..size].iter())
+        })
+    }
+
+    /// Returns a copy of every point stored in the tree, alongside its item, in no
+    /// particular order.
+    ///
+    /// Unlike serializing the tree itself, this does not depend on how the tree is laid
+    /// out internally, which can change between versions. The points can be stored in any
+    /// format, and a balanced tree rebuilt from them with
+    /// [`load_points`](KdTree::load_points).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.1, 2.1, 5.1], 101);
+    ///
+    /// let points = tree.dump_points();
+    /// let restored: KdTree<f64, u32, 3, 32, u32> = KdTree::load_points(points);
+    ///
+    /// assert_eq!(restored.size(), 2);
+    /// assert!(restored.contains(&[1.1, 2.1, 5.1], 101));
+    /// ```
+    pub fn dump_points(&self) -> Vec<([A; K], T)> {
+        self.iter().map(|(&point, &item)| (point, item)).collect()
+    }
+
+    /// Removes every entry from the tree, returning them as an iterator of `(point, item)`
+    /// pairs in no particular order.
+    ///
+    /// The tree is emptied as soon as this is called, even if the iterator is dropped
+    /// before all of the entries have been consumed. As with [`clear`](Self::clear), the
+    /// memory allocated for the tree is kept so that it can be reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.1, 2.1, 5.1], 101);
+    ///
+    /// let mut items: Vec<_> = tree.drain().map(|(_, item)| item).collect();
+    /// items.sort();
+    ///
+    /// assert_eq!(items, vec![100, 101]);
+    /// assert_eq!(tree.size(), 0);
+    /// ```
+    pub fn drain(&mut self) -> impl Iterator<Item = ([A; K], T)> {
+        let entries: Vec<([A; K], T)> = self.iter().map(|(&point, &item)| (point, item)).collect();
+        self.clear();
+
+        entries.into_iter()
+    }
+
+    /// Returns the smallest axis-aligned box that contains every point in the tree, as
+    /// `(min, max)` corners, or `None` if the tree is empty.
+    ///
+    /// This visits every stored point, and so is O(n). NaN co-ordinates are ignored
+    /// unless every point has a NaN for that axis.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.bounds(), None);
+    ///
+    /// tree.add(&[1.0, 3.0, 5.0], 100);
+    /// tree.add(&[2.0, 2.0, 6.0], 101);
+    ///
+    /// assert_eq!(tree.bounds(), Some(([1.0, 2.0, 5.0], [2.0, 3.0, 6.0])));
+    /// ```
+    pub fn bounds(&self) -> Option<([A; K], [A; K])> {
+        let mut points = self.iter().map(|(point, _)| point);
+        let first = *points.next()?;
+
+        Some(points.fold((first, first), |(mut min, mut max), point| {
+            for dim in 0..K {
+                min[dim] = min[dim].min(point[dim]);
+                max[dim] = max[dim].max(point[dim]);
+            }
+            (min, max)
+        }))
+    }
+
+    /// Returns the height of the tree: the number of stems on the longest path from
+    /// the root to a leaf.
+    ///
+    /// A tree whose items all fit into a single leaf has a height of zero. Trees built by
+    /// adding points in sorted order can end up much taller than the `log2` of their leaf
+    /// count, which makes queries slower.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// assert_eq!(tree.height(), 0);
+    /// ```
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height_from(self.root_index)
+    }
+
+    /// Returns the number of leaf nodes in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// assert_eq!(tree.leaf_count(), 1);
+    /// ```
+    #[inline]
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len() - self.free_leaves.len()
+    }
+
+    /// Returns the number of stem nodes in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// assert_eq!(tree.stem_count(), 0);
+    /// ```
+    #[inline]
+    pub fn stem_count(&self) -> usize {
+        self.stems.len() - self.free_stems.len()
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by the tree's nodes.
+    ///
+    /// This reflects the capacity that has been allocated for stems and leaves rather than
+    /// the number of items stored, so it includes space that is reserved but not yet in
+    /// use. Leaves are usually the dominant cost, as each one reserves space for `B`
+    /// points and items: see [`leaf_node_memory_usage`](KdTree::leaf_node_memory_usage).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let tree: KdTree<f64, u32, 3, 32, u32> = KdTree::with_capacity(1_000_000);
+    ///
+    /// let leaf_size = KdTree::<f64, u32, 3, 32, u32>::leaf_node_memory_usage();
+    ///
+    /// assert!(tree.memory_usage() >= 1_000_000 / 32 * leaf_size);
+    /// ```
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        self.stems.capacity() * core::mem::size_of::<StemNode<A, K, IDX>>()
+            + self.leaves.capacity() * Self::leaf_node_memory_usage()
+    }
+
+    /// Returns the number of bytes of memory used by each leaf node.
+    ///
+    /// Every leaf uses this much memory no matter how many items it holds, so this can
+    /// be used to gauge the effect of changing the bucket size `B`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let small_leaf = KdTree::<f64, u16, 3, 16, u16>::leaf_node_memory_usage();
+    /// let large_leaf = KdTree::<f64, u32, 3, 32, u32>::leaf_node_memory_usage();
+    ///
+    /// assert!(small_leaf < large_leaf);
+    /// ```
+    #[inline]
+    pub fn leaf_node_memory_usage() -> usize {
+        core::mem::size_of::<LeafNode<A, T, K, B, IDX>>()
+    }
+
+    fn height_from(&self, node_idx: IDX) -> usize {
+        if !Self::is_stem_index(node_idx) {
+            return 0;
+        }
+
+        let node = &self.stems[node_idx.az::<usize>()];
+        1 + self
+            .height_from(node.left)
+            .max(self.height_from(node.right))
+    }
+
+    pub(crate) fn is_stem_index(x: IDX) -> bool {
+        x < <IDX as Index>::leaf_offset()
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> From<&Vec<[A; K]>>
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+    usize: Cast<T>,
+{
+    fn from(vec: &Vec<[A; K]>) -> Self {
+        let mut tree: KdTree<A, T, K, B, IDX> = KdTree::with_capacity(vec.len());
+
+        vec.iter().enumerate().for_each(|(idx, pos)| {
+            tree.add(pos, idx.az::<T>());
+        });
+
+        tree
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    FromIterator<([A; K], T)> for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+    usize: Cast<T>,
+{
+    /// Creates a balanced tree from the `(point, item)` pairs yielded by `iter`,
+    /// in the same way as [`from_slice`](KdTree::from_slice).
+    fn from_iter<I: IntoIterator<Item = ([A; K], T)>>(iter: I) -> Self {
+        let points: Vec<([A; K], T)> = iter.into_iter().collect();
+
+        KdTree::from_slice(&points)
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Extend<([A; K], T)>
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Adds each of the `(point, item)` pairs yielded by `iter` to the tree.
+    fn extend<I: IntoIterator<Item = ([A; K], T)>>(&mut self, iter: I) {
+        iter.into_iter()
+            .for_each(|(point, item)| self.add(&point, item));
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> IntoIterator
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    type Item = ([A; K], T);
+    type IntoIter = IntoIter<A, T, K, B, IDX>;
+
+    /// Consumes the tree, returning an iterator that moves every `(point, item)` pair
+    /// out of it, in no particular order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            leaves: self.leaves.into_iter(),
+            leaf: None,
+            idx: 0,
+        }
+    }
+}
+
+/// An iterator that moves every entry out of a [`KdTree`], in no particular order.
+///
+/// Returned by the [`into_iter`](IntoIterator::into_iter) method of `KdTree`, which
+/// allows a tree to be consumed with a `for` loop.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::float::kdtree::KdTree;
+///
+/// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+///
+/// tree.add(&[1.0, 2.0, 5.0], 100);
+/// tree.add(&[1.1, 2.1, 5.1], 101);
+///
+/// let mut entries = Vec::new();
+/// for (point, item) in tree {
+///     entries.push((item, point));
+/// }
+/// entries.sort_by_key(|&(item, _)| item);
+///
+/// assert_eq!(entries, vec![(100, [1.0, 2.0, 5.0]), (101, [1.1, 2.1, 5.1])]);
+/// ```
+pub struct IntoIter<A: Copy + Default, T: Copy + Default, const K: usize, const B: usize, IDX> {
+    leaves: alloc::vec::IntoIter<LeafNode<A, T, K, B, IDX>>,
+    leaf: Option<LeafNode<A, T, K, B, IDX>>,
+    idx: usize,
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Iterator
+    for IntoIter<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    type Item = ([A; K], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf) = &self.leaf {
+                if self.idx < leaf.size.az::<usize>() {
+                    let entry = (leaf.content_points[self.idx], leaf.content_items[self.idx]);
+                    self.idx += 1;
+
+                    return Some(entry);
+                }
+            }
+
+            self.leaf = Some(self.leaves.next()?);
+            self.idx = 0;
+        }
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>> Debug
+    for KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Summarises the shape of the tree, rather than listing every node, which would
+    /// produce an unmanageable amount of output for large trees.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KdTree")
+            .field("size", &self.size)
+            .field("leaf_count", &self.leaf_count())
+            .field("stem_count", &self.stem_count())
+            .field("height", &self.height())
+            .field("K", &K)
+            .field("B", &B)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::kdtree::KdTree;
+    type AX = f64;
+
+    #[test]
+    fn it_can_be_constructed_with_new() {
+        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::new();
+
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn it_can_be_constructed_with_a_defined_capacity() {
+        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(10);
+
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn it_can_be_constructed_with_a_capacity_of_zero() {
+        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(0);
+
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn it_reports_its_height_and_node_counts() {
+        let mut tree: KdTree<AX, u32, 1, 4, u32> = KdTree::new();
+
+        assert_eq!(
+            (tree.height(), tree.leaf_count(), tree.stem_count()),
+            (0, 1, 0)
+        );
+
+        for i in 0..4 {
+            tree.add(&[i as AX], i);
+        }
+        assert_eq!(
+            (tree.height(), tree.leaf_count(), tree.stem_count()),
+            (0, 1, 0)
+        );
+
+        tree.add(&[4.0], 4);
+        assert_eq!(
+            (tree.height(), tree.leaf_count(), tree.stem_count()),
+            (1, 2, 1)
+        );
+    }
+
+    #[test]
+    fn it_is_taller_when_built_from_sorted_input() {
+        let points: Vec<([AX; 1], u32)> = (0..1000).map(|i| ([i as AX], i)).collect();
+
+        let mut sorted: KdTree<AX, u32, 1, 4, u32> = KdTree::new();
+        points
+            .iter()
+            .for_each(|(point, item)| sorted.add(point, *item));
+        let balanced: KdTree<AX, u32, 1, 4, u32> = KdTree::from_slice(&points);
+
+        assert!(sorted.height() > balanced.height());
+        assert!(balanced.height() <= (balanced.leaf_count() as f64).log2().ceil() as usize);
+    }
+
+    #[test]
+    fn it_estimates_its_memory_usage_from_its_capacity() {
+        type Tree = KdTree<AX, u32, 4, 32, u32>;
+
+        let small = Tree::with_capacity(100);
+        let large = Tree::with_capacity(100_000);
+
+        assert!(Tree::leaf_node_memory_usage() >= 32 * 4 * core::mem::size_of::<AX>());
+        assert!(small.memory_usage() >= small.leaves.capacity() * Tree::leaf_node_memory_usage());
+        assert!(large.memory_usage() > small.memory_usage());
+    }
+
+    #[test]
+    fn with_capacity_avoids_reallocation_when_adding_that_many_points() {
+        const TREE_SIZE: usize = 10_000;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+
+        let stems_capacity = tree.stems.capacity();
+        let leaves_capacity = tree.leaves.capacity();
+
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 4]>(), item as u32);
+        }
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert_eq!(tree.stems.capacity(), stems_capacity);
+        assert_eq!(tree.leaves.capacity(), leaves_capacity);
+    }
+
+    #[test]
+    fn reserving_space_avoids_reallocation_when_adding() {
+        const TREE_SIZE: usize = 10_000;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::new();
+        tree.reserve(TREE_SIZE);
+
+        let stems_capacity = tree.stems.capacity();
+        let leaves_capacity = tree.leaves.capacity();
+
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 4]>(), item as u32);
+        }
+
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+        assert_eq!(tree.stems.capacity(), stems_capacity);
+        assert_eq!(tree.leaves.capacity(), leaves_capacity);
+    }
+
+    #[test]
+    fn can_be_queried_from_many_threads_at_once() {
+        use crate::float::distance::squared_euclidean;
+        use std::sync::Arc;
+        use std::thread;
+
+        const TREE_SIZE: usize = 10_000;
+        const NUM_THREADS: usize = 4;
+        const NUM_QUERIES: usize = 1000;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<KdTree<AX, u32, 4, 32, u32>>();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 4]>(), item as u32);
+        }
+
+        let query_points: Arc<Vec<[AX; 4]>> =
+            Arc::new((0..NUM_QUERIES).map(|_| rand::random()).collect());
+
+        let expected: Vec<(AX, u32)> = query_points
+            .iter()
+            .map(|query_point| tree.nearest_one(query_point, &squared_euclidean))
+            .collect();
+
+        let tree = Arc::new(tree);
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let tree = Arc::clone(&tree);
+                let query_points = Arc::clone(&query_points);
+                thread::spawn(move || {
+                    query_points
+                        .iter()
+                        .map(|query_point| tree.nearest_one(query_point, &squared_euclidean))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn a_clone_is_unaffected_by_changes_to_the_original() {
+        use crate::float::distance::squared_euclidean;
+
+        let content_to_add: Vec<([AX; 2], u32)> =
+            (0..1000).map(|i| (rand::random::<[AX; 2]>(), i)).collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let snapshot = tree.clone();
+        assert_eq!(snapshot, tree);
+
+        content_to_add[..500].iter().for_each(|(point, item)| {
+            tree.remove(point, *item);
+        });
+
+        assert!(tree.size() < 1000);
+        assert_eq!(snapshot.size(), 1000);
+
+        for (point, item) in &content_to_add {
+            assert_eq!(
+                snapshot.nearest_one(point, &squared_euclidean),
+                (0.0, *item)
+            );
+        }
+    }
+
+    #[test]
+    fn it_can_be_collected_from_an_iterator_and_extended() {
+        let content_to_add: Vec<([AX; 4], u32)> =
+            (0..1000).map(|i| (rand::random::<[AX; 4]>(), i)).collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = content_to_add[..500].iter().copied().collect();
+        assert_eq!(tree.size(), 500);
+
+        tree.extend(content_to_add[500..].iter().copied());
+        assert_eq!(tree.size(), 1000);
+
+        let mut items: Vec<u32> = tree.iter().map(|(_, &item)| item).collect();
+        items.sort();
+        assert_eq!(items, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn can_iterate_over_every_stored_item() {
+        let content_to_add: Vec<([AX; 4], u32)> = (0..1000)
+            .map(|i| (rand::random::<[AX; 4]>(), i))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let mut result: Vec<_> = tree.iter().map(|(&point, &item)| (point, item)).collect();
+        result.sort_by_key(|&(_, item)| item);
+
+        assert_eq!(result, content_to_add);
+    }
+
+    #[test]
+    fn can_be_consumed_into_an_iterator() {
+        let content_to_add: Vec<([AX; 4], u32)> =
+            (0..1000).map(|i| (rand::random::<[AX; 4]>(), i)).collect();
+
+        let mut tree: KdTree<AX, u32, 4, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+        content_to_add[..500].iter().for_each(|(point, content)| {
+            tree.remove(point, *content);
+        });
+
+        let mut result: Vec<([AX; 4], u32)> = Vec::new();
+        for (point, item) in tree {
+            result.push((point, item));
+        }
+        result.sort_by_key(|&(_, item)| item);
+
+        assert_eq!(result, content_to_add[500..]);
+    }
+
+    #[test]
+    fn can_be_cleared_and_reused() {
+        use crate::float::distance::squared_euclidean;
+
+        let mut tree: KdTree<AX, u32, 4, 8, u32> = KdTree::new();
+        for i in 0..1000 {
+            tree.add(&rand::random::<[AX; 4]>(), i);
+        }
+        let leaves_capacity = tree.leaves.capacity();
+
+        tree.clear();
+
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.leaves.capacity(), leaves_capacity);
+        assert_eq!(tree.iter().count(), 0);
+
+        tree.add(&[0.1, 0.1, 0.1, 0.1], 1);
+        tree.add(&[0.9, 0.9, 0.9, 0.9], 2);
+
+        assert_eq!(tree.size(), 2);
+        assert_eq!(
+            tree.nearest_one(&[0.8, 0.8, 0.8, 0.8], &squared_euclidean).1,
+            2
+        );
+    }
+
+    // #[cfg(feature = "serialize")]
     // #[test]
     // fn can_serde() {
     //     let mut tree: KdTree<u16, u32, 4, 32, u32> = KdTree::new();
@@ -250,4 +1540,138 @@ mod tests {
     //     let deserialized: KdTree = serde_json::from_str(&serialized).unwrap();
     //     assert_eq!(tree, deserialized);
     // }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn can_deserialize_trees_serialized_without_split_dims() {
+        let content_to_add: Vec<([AX; 3], u32)> =
+            (0..1000).map(|i| (rand::random::<[AX; 3]>(), i)).collect();
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        // the layout of trees serialized by kiddo 2.0.1, whose stems each split along
+        // the axis after the one that their parent splits along
+        let mut serialized = serde_json::to_value(&tree).unwrap();
+        let fields = serialized.as_object_mut().unwrap();
+        for field in [
+            "free_leaves",
+            "free_stems",
+            "split_strategy",
+            "split_dim_strategy",
+        ] {
+            fields.remove(field);
+        }
+        for stem in fields["stems"].as_array_mut().unwrap() {
+            stem.as_object_mut().unwrap().remove("split_dim");
+        }
+
+        let deserialized: KdTree<AX, u32, 3, 8, u32> = serde_json::from_value(serialized).unwrap();
+
+        assert!(deserialized == tree);
+    }
+
+    #[test]
+    fn drain_returns_every_entry_and_empties_the_tree() {
+        let content_to_add: Vec<([AX; 2], u32)> =
+            (0..1000).map(|i| (rand::random::<[AX; 2]>(), i)).collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let mut drained: Vec<([AX; 2], u32)> = tree.drain().collect();
+        drained.sort_by_key(|&(_, item)| item);
+
+        assert_eq!(drained, content_to_add);
+        assert_eq!(tree.size(), 0);
+        assert_eq!((tree.leaf_count(), tree.stem_count()), (1, 0));
+
+        tree.add(&content_to_add[0].0, 0);
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn bounds_are_the_min_and_max_of_every_point() {
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        assert_eq!(tree.bounds(), None);
+
+        let content_to_add: Vec<([AX; 3], u32)> =
+            (0..1000).map(|i| (rand::random::<[AX; 3]>(), i)).collect();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let mut expected_min = content_to_add[0].0;
+        let mut expected_max = content_to_add[0].0;
+        for (point, _) in &content_to_add {
+            for dim in 0..3 {
+                if point[dim] < expected_min[dim] {
+                    expected_min[dim] = point[dim];
+                }
+                if point[dim] > expected_max[dim] {
+                    expected_max[dim] = point[dim];
+                }
+            }
+        }
+
+        assert_eq!(tree.bounds(), Some((expected_min, expected_max)));
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket size B must be at least 2")]
+    fn it_cannot_be_constructed_with_a_bucket_size_of_one() {
+        let _tree: KdTree<AX, u32, 2, 1, u32> = KdTree::new();
+    }
+
+    #[test]
+    fn it_can_hold_many_collinear_points_with_a_bucket_size_of_two() {
+        let mut tree: KdTree<AX, u32, 2, 2, u32> = KdTree::new();
+
+        for i in 0..1000 {
+            tree.add(&[i as AX, i as AX], i);
+        }
+        for i in 1000..1010 {
+            tree.add(&[0.5; 2], i);
+        }
+
+        assert_eq!(tree.size(), 1010);
+        assert_eq!(tree.iter().count(), 1010);
+    }
+
+    #[test]
+    fn shrinking_after_removals_and_a_rebalance_releases_memory() {
+        let content_to_add: Vec<([AX; 2], u32)> = (0..10_000)
+            .map(|i| (rand::random::<[AX; 2]>(), i))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+        tree.retain(|_, item| item < 100);
+
+        let before = tree.memory_usage();
+        tree.rebalance();
+        tree.shrink_to_fit();
+
+        assert!(tree.memory_usage() < before / 10);
+        assert_eq!(tree.size(), 100);
+        assert_eq!(tree.iter().count(), 100);
+    }
+
+    #[test]
+    fn debug_output_summarises_the_tree() {
+        let mut tree: KdTree<AX, u32, 1, 4, u32> = KdTree::new();
+        for i in 0..5 {
+            tree.add(&[i as AX], i);
+        }
+
+        assert_eq!(
+            format!("{tree:?}"),
+            "KdTree { size: 5, leaf_count: 2, stem_count: 1, height: 1, K: 1, B: 4 }"
+        );
+    }
 }
@@ -1,10 +1,19 @@
 //! Floating point k-d tree, for use when the co-ordinates of the points being stored in the tree
 //! are floats. [`f64`] or [`f32`] are supported currently.
 
+#[cfg(feature = "serialize_rkyv")]
+pub mod archived;
+pub mod builder;
+pub mod bulk_loader;
+pub mod bytes;
 #[doc(hidden)]
 pub mod construction;
 pub mod distance;
+pub mod dyn_bucket_kdtree;
+pub mod dyn_kdtree;
+pub mod immutable;
 pub mod kdtree;
 pub mod neighbour;
+pub mod node;
 #[doc(hidden)]
 pub mod query;
@@ -0,0 +1,254 @@
+//! Read-only access to the structure of a float [`KdTree`], for writing custom traversals.
+
+use az::{Az, Cast};
+use core::ops::Rem;
+
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+/// A reference to a single node of a float [`KdTree`], obtained from
+/// [`KdTree::root_node`].
+///
+/// A node is either a stem, which splits the space in two along one axis and has two
+/// children, or a leaf, which holds up to `B` points and their items. Unless the tree uses
+/// [`SplitDimStrategy::MaxVariance`](crate::float::kdtree::SplitDimStrategy::MaxVariance),
+/// stems split along each axis in turn, starting with axis 0 at the root. A point whose
+/// co-ordinate along the split axis is less than the stem's split value is stored under
+/// its left child, and one whose co-ordinate is greater is stored under its right child.
+/// Points whose co-ordinate is equal to the split value can be stored under either child.
+///
+/// # Examples
+///
+/// Counting the points whose first co-ordinate is at least `min`:
+///
+/// ```rust
+/// use kiddo::float::kdtree::KdTree;
+/// use kiddo::float::node::NodeRef;
+///
+/// fn count_from(node: NodeRef<f64, u32, 2, 4, u32>, min: f64) -> usize {
+///     match node.children() {
+///         Some((left, right)) => {
+///             let left_count = if node.split_dim() != 0 || node.split_val().unwrap() >= min {
+///                 count_from(left, min)
+///             } else {
+///                 0
+///             };
+///             left_count + count_from(right, min)
+///         }
+///         None => node.points().iter().filter(|point| point[0] >= min).count(),
+///     }
+/// }
+///
+/// let mut tree: KdTree<f64, u32, 2, 4, u32> = KdTree::new();
+/// for i in 0..100 {
+///     tree.add(&[i as f64, 0.0], i);
+/// }
+///
+/// assert_eq!(count_from(tree.root_node(), 90.0), 10);
+/// ```
+#[derive(Clone, Copy)]
+pub struct NodeRef<'a, A: Copy + Default, T: Copy + Default, const K: usize, const B: usize, IDX> {
+    tree: &'a KdTree<A, T, K, B, IDX>,
+    node_idx: IDX,
+    split_dim: usize,
+}
+
+impl<A, T, const K: usize, const B: usize, IDX> KdTree<A, T, K, B, IDX>
+where
+    A: Axis,
+    T: Content,
+    IDX: Index<T = IDX>,
+    usize: Cast<IDX>,
+{
+    /// Returns the root node of the tree, from which the rest of the tree can be walked.
+    ///
+    /// An empty tree consists of a single empty leaf.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// let root = tree.root_node();
+    ///
+    /// assert!(root.is_leaf());
+    /// assert_eq!(root.items(), &[100]);
+    /// ```
+    #[inline]
+    pub fn root_node(&self) -> NodeRef<'_, A, T, K, B, IDX> {
+        NodeRef {
+            tree: self,
+            node_idx: self.root_index,
+            split_dim: 0,
+        }
+    }
+}
+
+impl<'a, A, T, const K: usize, const B: usize, IDX> NodeRef<'a, A, T, K, B, IDX>
+where
+    A: Axis,
+    T: Content,
+    IDX: Index<T = IDX>,
+    usize: Cast<IDX>,
+{
+    /// Returns `true` if this node is a leaf, or `false` if it is a stem.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        !KdTree::<A, T, K, B, IDX>::is_stem_index(self.node_idx)
+    }
+
+    /// Returns the axis that this node splits along if it is a stem. If it is a leaf,
+    /// returns the axis that it would be split along by
+    /// [`SplitDimStrategy::RoundRobin`](crate::float::kdtree::SplitDimStrategy::RoundRobin)
+    /// if it became full.
+    #[inline]
+    pub fn split_dim(&self) -> usize {
+        self.stem()
+            .map_or(self.split_dim, |(_, _, _, split_dim)| split_dim)
+    }
+
+    /// Returns the value along [`split_dim`](NodeRef::split_dim) that this node splits
+    /// at, or `None` if it is a leaf.
+    #[inline]
+    pub fn split_val(&self) -> Option<A> {
+        self.stem().map(|(_, _, split_val, _)| split_val)
+    }
+
+    /// Returns the left and right children of this node, or `None` if it is a leaf.
+    #[inline]
+    pub fn children(&self) -> Option<(Self, Self)> {
+        self.stem().map(|(left, right, _, split_dim)| {
+            let split_dim = (split_dim + 1).rem(K);
+            (
+                NodeRef {
+                    tree: self.tree,
+                    node_idx: left,
+                    split_dim,
+                },
+                NodeRef {
+                    tree: self.tree,
+                    node_idx: right,
+                    split_dim,
+                },
+            )
+        })
+    }
+
+    /// Returns the points stored in this node if it is a leaf, or an empty slice if it is
+    /// a stem. The item for each point is at the same position in [`items`](NodeRef::items).
+    #[inline]
+    pub fn points(&self) -> &'a [[A; K]] {
+        match self.leaf_idx() {
+            Some(leaf_idx) => {
+                let leaf = &self.tree.leaves[leaf_idx];
+                &leaf.content_points[..leaf.size.az::<usize>()]
+            }
+            None => &[],
+        }
+    }
+
+    /// Returns the items stored in this node if it is a leaf, or an empty slice if it is
+    /// a stem.
+    #[inline]
+    pub fn items(&self) -> &'a [T] {
+        match self.leaf_idx() {
+            Some(leaf_idx) => {
+                let leaf = &self.tree.leaves[leaf_idx];
+                &leaf.content_items[..leaf.size.az::<usize>()]
+            }
+            None => &[],
+        }
+    }
+
+    fn stem(&self) -> Option<(IDX, IDX, A, usize)> {
+        if self.is_leaf() {
+            return None;
+        }
+
+        let stem = &self.tree.stems[self.node_idx.az::<usize>()];
+        Some((stem.left, stem.right, stem.split_val, stem.split_dim))
+    }
+
+    fn leaf_idx(&self) -> Option<usize> {
+        self.is_leaf()
+            .then(|| (self.node_idx - IDX::leaf_offset()).az::<usize>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::kdtree::KdTree;
+    use crate::float::node::NodeRef;
+
+    type AX = f64;
+
+    fn collect_items(node: NodeRef<AX, u32, 3, 8, u32>, items: &mut Vec<u32>) {
+        match node.children() {
+            Some((left, right)) => {
+                assert!(node.points().is_empty());
+                collect_items(left, items);
+                collect_items(right, items);
+            }
+            None => {
+                assert_eq!(node.points().len(), node.items().len());
+                items.extend_from_slice(node.items());
+            }
+        }
+    }
+
+    fn check_split_vals(node: NodeRef<AX, u32, 3, 8, u32>, min: [AX; 3], max: [AX; 3]) {
+        match node.children() {
+            Some((left, right)) => {
+                let split_dim = node.split_dim();
+                let split_val = node.split_val().unwrap();
+                assert_eq!(left.split_dim(), (split_dim + 1) % 3);
+
+                let mut left_max = max;
+                left_max[split_dim] = split_val;
+                let mut right_min = min;
+                right_min[split_dim] = split_val;
+
+                check_split_vals(left, min, left_max);
+                check_split_vals(right, right_min, max);
+            }
+            None => {
+                for point in node.points() {
+                    assert!((0..3).all(|dim| point[dim] >= min[dim] && point[dim] <= max[dim]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn can_walk_every_node_of_the_tree() {
+        const TREE_SIZE: usize = 1000;
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 3]>(), item as u32);
+        }
+
+        let mut items = Vec::new();
+        collect_items(tree.root_node(), &mut items);
+        items.sort();
+
+        assert_eq!(items, (0..TREE_SIZE as u32).collect::<Vec<_>>());
+
+        check_split_vals(tree.root_node(), [AX::NEG_INFINITY; 3], [AX::INFINITY; 3]);
+    }
+
+    #[test]
+    fn the_root_of_an_empty_tree_is_an_empty_leaf() {
+        let tree: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        let root = tree.root_node();
+
+        assert!(root.is_leaf());
+        assert!(root.children().is_none());
+        assert!(root.split_val().is_none());
+        assert!(root.points().is_empty());
+        assert!(root.items().is_empty());
+    }
+}
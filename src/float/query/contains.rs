@@ -0,0 +1,101 @@
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+use az::{Az, Cast};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Returns `true` if `item` is stored in the tree at exactly the point `query`.
+    ///
+    /// Points are compared using floating point equality.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.0, 2.0, 5.0], 101);
+    ///
+    /// assert!(tree.contains(&[1.0, 2.0, 5.0], 100));
+    /// assert!(!tree.contains(&[1.0, 2.0, 5.0], 102));
+    /// assert!(!tree.contains(&[1.0, 2.0, 5.1], 100));
+    /// ```
+    #[inline]
+    pub fn contains(&self, query: &[A; K], item: T) -> bool {
+        unsafe { self.contains_recurse(query, item, self.root_index) }
+    }
+
+    unsafe fn contains_recurse(&self, query: &[A; K], item: T, curr_node_idx: IDX) -> bool {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let query_val = *query.index_unchecked(node.split_dim);
+
+            // points equal to the split value can end up on either side of it
+            // when a leaf gets split, so both sides need checking in that case.
+            if query_val == node.split_val {
+                self.contains_recurse(query, item, node.left)
+                    || self.contains_recurse(query, item, node.right)
+            } else if query_val < node.split_val {
+                self.contains_recurse(query, item, node.left)
+            } else {
+                self.contains_recurse(query, item, node.right)
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+            let size = leaf_node.size.az::<usize>();
+
+            leaf_node.content_points[..size]
+                .iter()
+                .zip(leaf_node.content_items[..size].iter())
+                .any(|(point, &stored_item)| point == query && stored_item == item)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+
+    type AX = f32;
+
+    #[test]
+    fn can_check_whether_an_item_is_contained() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([AX; 2], u32)> =
+            (0..100).map(|i| (rng.gen::<[AX; 2]>(), i)).collect();
+        for (point, item) in content_to_add.iter() {
+            tree.add(point, *item);
+        }
+
+        for (point, item) in content_to_add.iter() {
+            assert!(tree.contains(point, *item));
+            assert!(!tree.contains(point, *item + 1000));
+        }
+        assert!(!tree.contains(&[2.0, 2.0], 1));
+    }
+
+    #[test]
+    fn can_check_whether_one_of_several_items_sharing_a_point_is_contained() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        for item in 0..20 {
+            tree.add(&[0.5, 0.5], item);
+        }
+        tree.remove_by_item(7);
+
+        for item in 0..20 {
+            assert_eq!(tree.contains(&[0.5, 0.5], item), item != 7);
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use alloc::{vec, vec::Vec};
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Counts the elements within `dist` of `query`, using the specified
+    /// distance metric function.
+    ///
+    /// The radius is inclusive, as with `within`. Equivalent to
+    /// `within_unsorted(query, dist, distance_fn).len()`, but faster as the
+    /// matching elements are never collected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[200.0, 300.0, 600.0], 102);
+    ///
+    /// let count = tree.count_within(&[1.0, 2.0, 5.0], 10f64, &squared_euclidean);
+    ///
+    /// assert_eq!(count, 2);
+    /// ```
+    #[inline]
+    pub fn count_within<D>(&self, query: &[A; K], dist: A, distance_fn: &D) -> usize
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+
+        unsafe {
+            self.count_within_recurse(
+                query,
+                dist,
+                distance_fn,
+                self.root_index,
+                &mut off,
+                A::zero(),
+            )
+        }
+    }
+
+    /// Counts the elements within `radius` of `query`, using the specified distance metric
+    /// function, grouped into bands of distance `bucket_width` wide.
+    ///
+    /// An element at distance `dist` is counted in the bucket at index
+    /// `floor(dist / bucket_width)`, so bucket `i` holds the elements at least
+    /// `i * bucket_width` but less than `(i + 1) * bucket_width` from `query`. The radius is
+    /// inclusive, as with `count_within`, so elements at exactly `radius` are counted too,
+    /// in the last bucket. The returned `Vec` has one bucket for each band up to and
+    /// including the one that `radius` falls in, so when `radius` is a multiple of
+    /// `bucket_width`, the last bucket only holds elements at exactly `radius`. It is empty
+    /// if `radius` is negative.
+    ///
+    /// This takes a single pass over the tree, and so is much faster than calling
+    /// [`count_within`](KdTree::count_within) at increasing radii.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 0.0], 100);
+    /// tree.add(&[0.0, 1.5], 101);
+    /// tree.add(&[2.0, 0.0], 102);
+    /// tree.add(&[3.0, 0.0], 103);
+    ///
+    /// let histogram = tree.histogram_within(&[0.0, 0.0], 4.0, 2.0, &squared_euclidean);
+    ///
+    /// assert_eq!(histogram, vec![1, 1, 1]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_width` is not greater than zero, or if the number of buckets,
+    /// `floor(radius / bucket_width) + 1`, is not finite or does not fit in a `usize`, such
+    /// as when `radius` is infinite or NaN. All of the buckets are allocated up front, so a
+    /// `bucket_width` that is tiny compared to `radius` can also exhaust memory.
+    #[inline]
+    pub fn histogram_within<D>(
+        &self,
+        query: &[A; K],
+        radius: A,
+        bucket_width: A,
+        distance_fn: &D,
+    ) -> Vec<usize>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        assert!(
+            bucket_width > A::zero(),
+            "bucket_width must be greater than zero"
+        );
+
+        if radius < A::zero() {
+            return Vec::new();
+        }
+
+        let num_buckets = (radius / bucket_width)
+            .floor()
+            .to_usize()
+            .and_then(|last_bucket| last_bucket.checked_add(1))
+            .expect(
+                "radius / bucket_width must be a finite number of buckets that fits in a usize",
+            );
+        let mut histogram = vec![0; num_buckets];
+        let mut off = [A::zero(); K];
+
+        unsafe {
+            self.histogram_within_recurse(
+                query,
+                radius,
+                bucket_width,
+                distance_fn,
+                self.root_index,
+                &mut histogram,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        histogram
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn count_within_recurse<D>(
+        &self,
+        query: &[A; K],
+        radius: A,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        off: &mut [A; K],
+        rd: A,
+    ) -> usize
+    where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            let mut count =
+                self.count_within_recurse(query, radius, distance_fn, closer_node_idx, off, rd);
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+
+            if rd <= radius {
+                off[split_dim] = new_off;
+                count += self.count_within_recurse(
+                    query,
+                    radius,
+                    distance_fn,
+                    further_node_idx,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+
+            count
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .take(leaf_node.size.az::<usize>())
+                .filter(|entry| distance_fn.dist(query, entry) <= radius)
+                .count()
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn histogram_within_recurse<D>(
+        &self,
+        query: &[A; K],
+        radius: A,
+        bucket_width: A,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        histogram: &mut [usize],
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.histogram_within_recurse(
+                query,
+                radius,
+                bucket_width,
+                distance_fn,
+                closer_node_idx,
+                histogram,
+                off,
+                rd,
+            );
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+
+            if rd <= radius {
+                off[split_dim] = new_off;
+                self.histogram_within_recurse(
+                    query,
+                    radius,
+                    bucket_width,
+                    distance_fn,
+                    further_node_idx,
+                    histogram,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .take(leaf_node.size.az::<usize>())
+                .map(|entry| distance_fn.dist(query, entry))
+                .filter(|&distance| distance <= radius)
+                .for_each(|distance| {
+                    // dividing by the same positive width preserves order, so no element
+                    // within the radius can land beyond the last bucket
+                    let bucket = (distance / bucket_width).floor().to_usize().unwrap_or(0);
+                    histogram[bucket] += 1;
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{squared_euclidean, Manhattan};
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+
+    type AX = f32;
+
+    #[test]
+    fn count_within_matches_within() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+            let radius = rng.gen_range(0.0..0.2);
+
+            assert_eq!(
+                tree.count_within(&query_point, radius, &squared_euclidean),
+                tree.within(&query_point, radius, &squared_euclidean).len()
+            );
+            assert_eq!(
+                tree.count_within(&query_point, radius, &Manhattan),
+                tree.within(&query_point, radius, &Manhattan).len()
+            );
+        }
+    }
+
+    #[test]
+    fn histogram_within_matches_count_within_per_bucket() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        // points on an integer grid, so that distances fall exactly on bucket edges
+        let mut rng = rand::thread_rng();
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            let point = [
+                rng.gen_range(0..20) as AX,
+                rng.gen_range(0..20) as AX,
+                rng.gen_range(0..20) as AX,
+            ];
+            tree.add(&point, item as u32);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = [
+                rng.gen_range(0..20) as AX,
+                rng.gen_range(0..20) as AX,
+                rng.gen_range(0..20) as AX,
+            ];
+            let radius = rng.gen_range(0..60) as AX;
+            let bucket_width = rng.gen_range(1..8) as AX;
+
+            let histogram =
+                tree.histogram_within(&query_point, radius, bucket_width, &squared_euclidean);
+            assert_eq!(histogram.len(), (radius / bucket_width) as usize + 1);
+
+            // as distances are whole numbers, the elements in [lower, upper) are the
+            // ones within upper - 1, less those within lower - 1
+            let count_below = |bound: AX| {
+                if bound < 0.0 {
+                    0
+                } else {
+                    tree.count_within(&query_point, bound.min(radius), &squared_euclidean)
+                }
+            };
+            for (bucket, &count) in histogram.iter().enumerate() {
+                let lower = bucket as AX * bucket_width;
+                let upper = lower + bucket_width;
+                assert_eq!(count, count_below(upper - 1.0) - count_below(lower - 1.0));
+            }
+
+            assert_eq!(
+                histogram.iter().sum::<usize>(),
+                tree.count_within(&query_point, radius, &squared_euclidean)
+            );
+        }
+    }
+
+    #[test]
+    fn histogram_within_counts_everything_within_the_radius() {
+        const TREE_SIZE: usize = 10_000;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        let query_point = rand::random::<[AX; 4]>();
+        let histogram = tree.histogram_within(&query_point, 0.3, 0.07, &Manhattan);
+
+        assert_eq!(histogram.len(), 5);
+        assert_eq!(
+            histogram.iter().sum::<usize>(),
+            tree.count_within(&query_point, 0.3, &Manhattan)
+        );
+        assert!(tree
+            .histogram_within(&query_point, -1.0, 0.1, &Manhattan)
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "radius / bucket_width must be a finite number of buckets")]
+    fn histogram_within_an_infinite_radius_panics() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+        tree.add(&[0.5, 0.5], 0);
+
+        tree.histogram_within(&[0.5, 0.5], AX::INFINITY, 0.1, &squared_euclidean);
+    }
+
+    #[test]
+    #[should_panic(expected = "radius / bucket_width must be a finite number of buckets")]
+    fn histogram_within_more_buckets_than_fit_in_a_usize_panics() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+        tree.add(&[0.5, 0.5], 0);
+
+        tree.histogram_within(&[0.5, 0.5], 1.0, 1e-30, &squared_euclidean);
+    }
+
+    #[test]
+    fn count_within_on_empty_tree_is_zero() {
+        let tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        assert_eq!(tree.count_within(&[0.5, 0.5], 1.0, &squared_euclidean), 0);
+    }
+}
@@ -0,0 +1,104 @@
+use alloc::vec::Vec;
+use az::{Az, Cast};
+
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Returns every item stored in the tree at exactly the point `query`, in no
+    /// particular order.
+    ///
+    /// Points are compared using floating point equality, so this is mainly useful when
+    /// the points are known to be exactly representable, such as integer-valued
+    /// co-ordinates. To find items near to a point, use a query such as
+    /// [`within`](KdTree::within) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[1.0, 2.0, 5.0], 101);
+    /// tree.add(&[2.0, 3.0, 6.0], 102);
+    ///
+    /// let mut items = tree.get(&[1.0, 2.0, 5.0]);
+    /// items.sort();
+    ///
+    /// assert_eq!(items, vec![100, 101]);
+    /// assert!(tree.get(&[1.0, 2.0, 5.1]).is_empty());
+    /// ```
+    #[inline]
+    pub fn get(&self, query: &[A; K]) -> Vec<T> {
+        let mut items = Vec::new();
+
+        unsafe { self.get_recurse(query, self.root_index, &mut items) }
+
+        items
+    }
+
+    unsafe fn get_recurse(&self, query: &[A; K], curr_node_idx: IDX, items: &mut Vec<T>) {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let query_val = *query.index_unchecked(node.split_dim);
+
+            // points equal to the split value can end up on either side of it
+            // when a leaf gets split, so both sides need checking in that case.
+            if query_val <= node.split_val {
+                self.get_recurse(query, node.left, items);
+            }
+            if query_val >= node.split_val {
+                self.get_recurse(query, node.right, items);
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+            let size = leaf_node.size.az::<usize>();
+
+            items.extend(
+                leaf_node.content_points[..size]
+                    .iter()
+                    .zip(leaf_node.content_items[..size].iter())
+                    .filter(|(point, _)| *point == query)
+                    .map(|(_, &item)| item),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn can_get_every_item_sharing_a_point() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        for item in 0..100 {
+            tree.add(&[(item % 10) as AX, 0.0], item);
+        }
+        tree.remove_by_item(13);
+
+        for x in 0..10 {
+            let mut items = tree.get(&[x as AX, 0.0]);
+            items.sort();
+
+            let expected: Vec<u32> = (0..10)
+                .map(|i| i * 10 + x)
+                .filter(|&item| item != 13)
+                .collect();
+            assert_eq!(items, expected);
+        }
+        assert!(tree.get(&[0.5, 0.0]).is_empty());
+    }
+}
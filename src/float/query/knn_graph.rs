@@ -0,0 +1,197 @@
+use alloc::{vec, vec::Vec};
+use az::{Az, Cast};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Builds the `k` nearest neighbour graph of the elements stored in the tree, using
+    /// the specified distance metric function.
+    ///
+    /// Returns one row per item, at the position given by the item itself, holding the
+    /// `k` elements nearest to that item's point other than itself, as `(distance, item)`
+    /// pairs sorted nearest-first, in the same way as
+    /// [`nearest_n_vec`](KdTree::nearest_n_vec). There are fewer than `k` in a row if
+    /// the tree holds `k` or fewer elements. The items are expected to be distinct
+    /// indices, such as the position of each point in the `Vec` that a tree was built
+    /// from with `From`, and there is a row for every index up to the largest item, with
+    /// those that are not in the tree left empty.
+    ///
+    /// Each row takes a [`nearest_n`](KdTree::nearest_n) query of `k + 1` elements, so
+    /// building the graph for `n` elements takes roughly `O(n * (log n + k log k))` time,
+    /// and the result holds `n * k` pairs. With the `rayon` crate feature enabled,
+    /// `par_knn_graph` builds the rows in parallel instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let points = vec![[0.0, 0.0], [1.0, 0.0], [3.0, 0.0]];
+    /// let tree: KdTree<f64, usize, 2, 32, u32> = (&points).into();
+    ///
+    /// let graph = tree.knn_graph(1, &squared_euclidean);
+    ///
+    /// assert_eq!(graph, vec![vec![(1.0, 1)], vec![(1.0, 0)], vec![(4.0, 1)]]);
+    /// ```
+    pub fn knn_graph<D>(&self, k: usize, distance_fn: &D) -> Vec<Vec<(A, T)>>
+    where
+        D: DistanceMetric<A, K>,
+        T: Cast<usize>,
+    {
+        Self::rows_by_item(
+            self.iter()
+                .map(|(point, &item)| (item, self.knn_row(point, item, k, distance_fn))),
+        )
+    }
+
+    /// Builds the `k` nearest neighbour graph of the elements stored in the tree, using
+    /// the specified distance metric function, in parallel.
+    ///
+    /// The rows are found on the threads of rayon's global thread pool, and the result
+    /// is identical to that of [`knn_graph`](KdTree::knn_graph).
+    ///
+    /// Requires the `rayon` crate feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let points = vec![[0.0, 0.0], [1.0, 0.0], [3.0, 0.0]];
+    /// let tree: KdTree<f64, usize, 2, 32, u32> = (&points).into();
+    ///
+    /// let graph = tree.par_knn_graph(1, &squared_euclidean);
+    ///
+    /// assert_eq!(graph, vec![vec![(1.0, 1)], vec![(1.0, 0)], vec![(4.0, 1)]]);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_knn_graph<D>(&self, k: usize, distance_fn: &D) -> Vec<Vec<(A, T)>>
+    where
+        A: Send,
+        T: Send + Cast<usize>,
+        D: DistanceMetric<A, K> + Sync,
+    {
+        let elements: Vec<(&[A; K], &T)> = self.iter().collect();
+
+        let rows: Vec<(T, Vec<(A, T)>)> = elements
+            .par_iter()
+            .map(|&(point, &item)| (item, self.knn_row(point, item, k, distance_fn)))
+            .collect();
+
+        Self::rows_by_item(rows)
+    }
+
+    fn knn_row<D>(&self, point: &[A; K], item: T, k: usize, distance_fn: &D) -> Vec<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        // the element itself is usually the first of the k + 1 found, but if other
+        // elements share its point, it may be tied with them or not be found at all
+        let mut row = self.nearest_n_vec(point, k + 1, distance_fn);
+        if let Some(own_idx) = row.iter().position(|&(_, other)| other == item) {
+            row.remove(own_idx);
+        }
+        row.truncate(k);
+
+        row
+    }
+
+    fn rows_by_item<I>(rows: I) -> Vec<Vec<(A, T)>>
+    where
+        I: IntoIterator<Item = (T, Vec<(A, T)>)>,
+        T: Cast<usize>,
+    {
+        let mut graph: Vec<Vec<(A, T)>> = vec![];
+        for (item, row) in rows {
+            let idx = item.az::<usize>();
+            if idx >= graph.len() {
+                graph.resize(idx + 1, Vec::new());
+            }
+            graph[idx] = row;
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn knn_graph_of_a_grid_links_each_point_to_its_neighbours() {
+        // five points spaced 1 apart along x, in rows spaced 10 apart along y, so that
+        // each point's two nearest neighbours are in its own row
+        const WIDTH: usize = 5;
+        const HEIGHT: usize = 3;
+
+        let points: Vec<[AX; 2]> = (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| [x as AX, 10.0 * y as AX]))
+            .collect();
+        let tree: KdTree<AX, usize, 2, 4, u32> = (&points).into();
+
+        let graph = tree.knn_graph(2, &squared_euclidean);
+
+        assert_eq!(graph.len(), WIDTH * HEIGHT);
+        for (item, row) in graph.iter().enumerate() {
+            let x = item % WIDTH;
+            let expected = match x {
+                0 => vec![(1.0, item + 1), (4.0, item + 2)],
+                x if x == WIDTH - 1 => vec![(1.0, item - 1), (4.0, item - 2)],
+                _ => vec![(1.0, item - 1), (1.0, item + 1)],
+            };
+
+            assert_eq!(row, &expected);
+        }
+    }
+
+    #[test]
+    fn knn_graph_rows_hold_every_other_element_when_k_is_large() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+        tree.add(&[0.0, 0.0], 0);
+        tree.add(&[0.0, 0.0], 2);
+        tree.add(&[1.0, 0.0], 3);
+
+        let graph = tree.knn_graph(5, &squared_euclidean);
+
+        assert_eq!(
+            graph,
+            vec![
+                vec![(0.0, 2), (1.0, 3)],
+                vec![],
+                vec![(0.0, 0), (1.0, 3)],
+                vec![(1.0, 0), (1.0, 2)],
+            ]
+        );
+        assert!(KdTree::<AX, u32, 2, 4, u32>::new()
+            .knn_graph(5, &squared_euclidean)
+            .is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_knn_graph_matches_knn_graph() {
+        const TREE_SIZE: usize = 10_000;
+
+        let points: Vec<[AX; 3]> = (0..TREE_SIZE).map(|_| rand::random()).collect();
+        let tree: KdTree<AX, usize, 3, 32, u32> = (&points).into();
+
+        assert_eq!(
+            tree.par_knn_graph(5, &squared_euclidean),
+            tree.knn_graph(5, &squared_euclidean)
+        );
+    }
+}
@@ -1,5 +1,25 @@
 pub mod best_n_within;
+pub mod contains;
+pub mod count_within;
+pub mod get;
+pub mod knn_graph;
 pub mod nearest_n;
+#[cfg(feature = "rayon")]
+pub mod nearest_n_batch;
+pub mod nearest_n_lazy;
+pub mod nearest_n_within;
 pub mod nearest_one;
+pub mod nearest_one_approx;
+#[cfg(feature = "rayon")]
+pub mod nearest_one_batch;
+pub mod nearest_one_bruteforce;
+pub mod nearest_one_filtered;
+pub mod nearest_one_iterative;
+pub mod nearest_one_prefix;
+pub mod nearest_one_within;
+pub mod reduce_nearest;
 pub mod within;
+pub mod within_bbox;
+pub mod within_haversine;
+pub mod within_segment;
 pub mod within_unsorted;
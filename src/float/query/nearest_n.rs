@@ -1,9 +1,12 @@
+use crate::float::distance::{DistanceMetric, SquaredEuclidean};
 use crate::float::kdtree::{Axis, KdTree};
 use crate::float::neighbour::Neighbour;
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
 use az::{Az, Cast};
-use std::collections::BinaryHeap;
-use std::ops::Rem;
+use core::cmp::Ordering;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -31,75 +34,447 @@ where
     /// assert_eq!(nearest[0].item, 100);
     /// ```
     #[inline]
-    pub fn nearest_n<F>(&self, query: &[A; K], qty: usize, distance_fn: &F) -> Vec<Neighbour<A, T>>
+    pub fn nearest_n<D>(&self, query: &[A; K], qty: usize, distance_fn: &D) -> Vec<Neighbour<A, T>>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::zero(); K];
         let mut result: BinaryHeap<Neighbour<A, T>> = BinaryHeap::with_capacity(qty);
 
+        if qty > 0 {
+            unsafe {
+                self.nearest_n_recurse(
+                    query,
+                    qty,
+                    distance_fn,
+                    self.root_index,
+                    &mut result,
+                    &mut off,
+                    A::zero(),
+                )
+            }
+        }
+
+        result.into_sorted_vec()
+    }
+
+    /// Finds the nearest `qty` elements to `query`, using the specified distance metric
+    /// function, returning them as `(distance, item)` pairs sorted nearest-first.
+    ///
+    /// Elements at the same distance from `query` are ordered by item, so the order of the
+    /// results does not depend on the shape of the tree. Otherwise this returns the same
+    /// results as [`nearest_n`](KdTree::nearest_n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_n_vec(&[1.0, 2.0, 5.0], 2, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest, vec![(0.0, 100), (3.0, 101)]);
+    /// ```
+    #[inline]
+    pub fn nearest_n_vec<D>(&self, query: &[A; K], qty: usize, distance_fn: &D) -> Vec<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut result: Vec<(A, T)> = self
+            .nearest_n(query, qty, distance_fn)
+            .into_iter()
+            .map(|neighbour| (neighbour.distance, neighbour.item))
+            .collect();
+
+        result.sort_by(|(a_dist, a_item), (b_dist, b_item)| {
+            a_dist
+                .partial_cmp(b_dist)
+                .unwrap_or(core::cmp::Ordering::Equal)
+                .then(a_item.cmp(b_item))
+        });
+
+        result
+    }
+
+    /// Finds the nearest `qty` elements to each of the points in `queries`, using the
+    /// specified distance metric function.
+    ///
+    /// Returns one row per query, in the same order as `queries`, each of which is what
+    /// [`nearest_n_vec`](KdTree::nearest_n_vec) would return for that query: up to `qty`
+    /// `(distance, item)` pairs, sorted nearest-first.
+    ///
+    /// Every row is held in memory until the whole batch has been queried, so the result
+    /// takes up roughly `queries.len() * qty * size_of::<(A, T)>()` bytes, plus a
+    /// separate allocation for each row. For very large batches, consider querying
+    /// `queries.chunks(..)` one at a time and consuming each chunk's results before
+    /// moving on to the next. With the `rayon` crate feature enabled, `nearest_n_batch`
+    /// queries the batch in parallel instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_n_many(&[[1.0, 2.0, 5.0], [2.0, 3.0, 6.0]], 2, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest, vec![vec![(0.0, 100), (3.0, 101)], vec![(0.0, 101), (3.0, 100)]]);
+    /// ```
+    #[inline]
+    pub fn nearest_n_many<D>(
+        &self,
+        queries: &[[A; K]],
+        qty: usize,
+        distance_fn: &D,
+    ) -> Vec<Vec<(A, T)>>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        queries
+            .iter()
+            .map(|query| self.nearest_n_vec(query, qty, distance_fn))
+            .collect()
+    }
+
+    /// Finds the nearest `qty` elements to `query` by euclidean distance, returning the
+    /// true (rather than squared) distance to each of them.
+    ///
+    /// The search is carried out using [`SquaredEuclidean`], which is faster and finds
+    /// the same elements, and the square root of each distance is taken once at the end.
+    /// Results are returned sorted nearest-first, as with [`nearest_n`](KdTree::nearest_n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[3.0, 4.0], 100);
+    /// tree.add(&[6.0, 8.0], 101);
+    ///
+    /// let nearest = tree.nearest_n_euclidean(&[0.0, 0.0], 2);
+    ///
+    /// assert_eq!(nearest[0].distance, 5.0);
+    /// assert_eq!(nearest[1].distance, 10.0);
+    /// ```
+    #[inline]
+    pub fn nearest_n_euclidean(&self, query: &[A; K], qty: usize) -> Vec<Neighbour<A, T>> {
+        let mut result = self.nearest_n(query, qty, &SquaredEuclidean);
+        result
+            .iter_mut()
+            .for_each(|neighbour| neighbour.distance = neighbour.distance.sqrt());
+
+        result
+    }
+
+    /// Finds the distance from `query` to its `k`th nearest element, using the specified
+    /// distance metric function.
+    ///
+    /// Equivalent to the distance of the last element returned by
+    /// [`nearest_n`](KdTree::nearest_n) with `qty` of `k`, but without building and
+    /// sorting the full result set. This is useful for algorithms that only need a
+    /// neighbourhood radius, such as local outlier factor. Returns `None` if `k` is
+    /// zero or the tree holds fewer than `k` elements.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// assert_eq!(tree.kth_nearest_distance(&[1.0, 2.0, 5.0], 2, &squared_euclidean), Some(3.0));
+    /// assert_eq!(tree.kth_nearest_distance(&[1.0, 2.0, 5.0], 3, &squared_euclidean), None);
+    /// ```
+    #[inline]
+    pub fn kth_nearest_distance<D>(&self, query: &[A; K], k: usize, distance_fn: &D) -> Option<A>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        if k == 0 {
+            return None;
+        }
+
+        let mut off = [A::zero(); K];
+        let mut result: BinaryHeap<Neighbour<A, T>> = BinaryHeap::with_capacity(k);
+
         unsafe {
             self.nearest_n_recurse(
                 query,
+                k,
                 distance_fn,
                 self.root_index,
-                0,
                 &mut result,
                 &mut off,
                 A::zero(),
             )
         }
 
-        result.into_sorted_vec()
+        if result.len() < k {
+            return None;
+        }
+
+        result.peek().map(|neighbour| neighbour.distance)
     }
 
-    unsafe fn nearest_n_recurse<F>(
+    /// Finds the nearest elements to `query`, using the specified distance metric
+    /// function, and places them into `heap`, which is cleared first.
+    ///
+    /// The number of elements found is determined by the capacity of `heap`, as reported
+    /// by [`BinaryHeap::capacity`], so it should be created with
+    /// [`BinaryHeap::with_capacity`]. The heap never grows beyond that capacity, so it can
+    /// be reused for further queries without allocating. Its contents are the same as the
+    /// results of [`nearest_n`](KdTree::nearest_n) with `qty` equal to the capacity, with the
+    /// furthest element at the top of the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::BinaryHeap;
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let mut heap = BinaryHeap::with_capacity(1);
+    /// tree.nearest_n_into(&[1.0, 2.0, 5.1], &squared_euclidean, &mut heap);
+    ///
+    /// assert_eq!(heap.len(), 1);
+    /// assert_eq!(heap.peek().unwrap().item, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_n_into<D>(
         &self,
         query: &[A; K],
-        distance_fn: &F,
+        distance_fn: &D,
+        heap: &mut BinaryHeap<Neighbour<A, T>>,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        let qty = heap.capacity();
+        let mut off = [A::zero(); K];
+
+        heap.clear();
+
+        if qty > 0 {
+            unsafe {
+                self.nearest_n_recurse(
+                    query,
+                    qty,
+                    distance_fn,
+                    self.root_index,
+                    heap,
+                    &mut off,
+                    A::zero(),
+                )
+            }
+        }
+    }
+
+    /// Finds the nearest `qty` elements to `query`, using the specified distance metric
+    /// function, with `tie` deciding the order of elements at the same distance.
+    ///
+    /// Elements at different distances are still ordered purely by distance, and `tie` is
+    /// only called to compare the items of elements that are equally far from `query`.
+    /// Results are returned sorted nearest-first, with equidistant elements in ascending
+    /// order according to `tie`. Where the elements that tie for the last place in the
+    /// results do not all fit, the ones that `tie` orders first are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 0.0], 100);
+    /// tree.add(&[0.0, 1.0], 101);
+    /// tree.add(&[-1.0, 0.0], 102);
+    /// tree.add(&[5.0, 5.0], 103);
+    ///
+    /// // prefer the highest item amongst equally near elements
+    /// let nearest = tree.nearest_n_by(&[0.0, 0.0], 2, &squared_euclidean, |a, b| b.cmp(&a));
+    ///
+    /// assert_eq!(nearest[0].item, 102);
+    /// assert_eq!(nearest[1].item, 101);
+    /// ```
+    #[inline]
+    pub fn nearest_n_by<D, C>(
+        &self,
+        query: &[A; K],
+        qty: usize,
+        distance_fn: &D,
+        tie: C,
+    ) -> Vec<Neighbour<A, T>>
+    where
+        D: DistanceMetric<A, K>,
+        C: Fn(T, T) -> Ordering,
+    {
+        let mut off = [A::zero(); K];
+        let mut result: BinaryHeap<TieBrokenNeighbour<A, T, C>> = BinaryHeap::with_capacity(qty);
+
+        if qty > 0 {
+            unsafe {
+                self.nearest_n_by_recurse(
+                    query,
+                    qty,
+                    distance_fn,
+                    &tie,
+                    self.root_index,
+                    &mut result,
+                    &mut off,
+                    A::zero(),
+                )
+            }
+        }
+
+        result
+            .into_sorted_vec()
+            .into_iter()
+            .map(|entry| entry.neighbour)
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_n_recurse<D>(
+        &self,
+        query: &[A; K],
+        qty: usize,
+        distance_fn: &D,
         curr_node_idx: IDX,
-        split_dim: usize,
         results: &mut BinaryHeap<Neighbour<A, T>>,
         off: &mut [A; K],
         rd: A,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = &self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim] - node.split_val;
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
                 };
-            let next_split_dim = (split_dim + 1).rem(K);
 
-            self.nearest_n_recurse(
+            self.nearest_n_recurse(query, qty, distance_fn, closer_node_idx, results, off, rd);
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if Self::dist_belongs_in_heap(rd, qty, results) {
+                off[split_dim] = new_off;
+                self.nearest_n_recurse(query, qty, distance_fn, further_node_idx, results, off, rd);
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .take(leaf_node.size.az::<usize>())
+                .enumerate()
+                .for_each(|(idx, entry)| {
+                    let distance: A = distance_fn.dist(query, entry);
+                    if Self::dist_belongs_in_heap(distance, qty, results) {
+                        let item = unsafe { *leaf_node.content_items.index_unchecked(idx) };
+                        let element = Neighbour { distance, item };
+                        if results.len() < qty {
+                            results.push(element)
+                        } else {
+                            let mut top = results.peek_mut().unwrap();
+                            if element.distance < top.distance {
+                                *top = element;
+                            }
+                        }
+                    }
+                });
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_n_by_recurse<'a, D, C>(
+        &self,
+        query: &[A; K],
+        qty: usize,
+        distance_fn: &D,
+        tie: &'a C,
+        curr_node_idx: IDX,
+        results: &mut BinaryHeap<TieBrokenNeighbour<'a, A, T, C>>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+        C: Fn(T, T) -> Ordering,
+    {
+        // elements at the same distance as the furthest one kept so far can still displace
+        // it, so unlike in nearest_n_recurse, ties are not pruned
+        let dist_may_belong_in_heap =
+            |dist: A, results: &BinaryHeap<TieBrokenNeighbour<A, T, C>>| {
+                results.len() < qty || dist <= results.peek().unwrap().neighbour.distance
+            };
+
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.nearest_n_by_recurse(
                 query,
+                qty,
                 distance_fn,
+                tie,
                 closer_node_idx,
-                next_split_dim,
                 results,
                 off,
                 rd,
             );
 
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd + new_off * new_off - old_off * old_off;
-            if Self::dist_belongs_in_heap(rd, results) {
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if dist_may_belong_in_heap(rd, results) {
                 off[split_dim] = new_off;
-                self.nearest_n_recurse(
+                self.nearest_n_by_recurse(
                     query,
+                    qty,
                     distance_fn,
+                    tie,
                     further_node_idx,
-                    next_split_dim,
                     results,
                     off,
                     rd,
@@ -109,23 +484,25 @@ where
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
 
             leaf_node
                 .content_points
                 .iter()
+                .zip(leaf_node.content_items.iter())
                 .take(leaf_node.size.az::<usize>())
-                .enumerate()
-                .for_each(|(idx, entry)| {
-                    let distance: A = distance_fn(query, entry);
-                    if Self::dist_belongs_in_heap(distance, results) {
-                        let item = unsafe { *leaf_node.content_items.get_unchecked(idx) };
-                        let element = Neighbour { distance, item };
-                        if results.len() < results.capacity() {
+                .for_each(|(entry, &item)| {
+                    let distance: A = distance_fn.dist(query, entry);
+                    if dist_may_belong_in_heap(distance, results) {
+                        let element = TieBrokenNeighbour {
+                            neighbour: Neighbour { distance, item },
+                            tie,
+                        };
+                        if results.len() < qty {
                             results.push(element)
                         } else {
                             let mut top = results.peek_mut().unwrap();
-                            if element.distance < top.distance {
+                            if element < *top {
                                 *top = element;
                             }
                         }
@@ -134,16 +511,49 @@ where
         }
     }
 
-    fn dist_belongs_in_heap(dist: A, heap: &BinaryHeap<Neighbour<A, T>>) -> bool {
-        heap.is_empty() || dist < heap.peek().unwrap().distance || heap.len() < heap.capacity()
+    fn dist_belongs_in_heap(dist: A, qty: usize, heap: &BinaryHeap<Neighbour<A, T>>) -> bool {
+        heap.len() < qty || dist < heap.peek().unwrap().distance
+    }
+}
+
+/// An entry in the heap of [`nearest_n_by`](KdTree::nearest_n_by), ordered by distance and
+/// then by the caller's tie-breaking comparator, so that the greatest entry is the one to
+/// be displaced next.
+struct TieBrokenNeighbour<'a, A, T, C> {
+    neighbour: Neighbour<A, T>,
+    tie: &'a C,
+}
+
+impl<A: Axis, T: Content, C: Fn(T, T) -> Ordering> Ord for TieBrokenNeighbour<'_, A, T, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.neighbour
+            .distance
+            .partial_cmp(&other.neighbour.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| (self.tie)(self.neighbour.item, other.neighbour.item))
+    }
+}
+
+impl<A: Axis, T: Content, C: Fn(T, T) -> Ordering> PartialOrd for TieBrokenNeighbour<'_, A, T, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Axis, T: Content, C: Fn(T, T) -> Ordering> Eq for TieBrokenNeighbour<'_, A, T, C> {}
+
+impl<A: Axis, T: Content, C: Fn(T, T) -> Ordering> PartialEq for TieBrokenNeighbour<'_, A, T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::float::distance::squared_euclidean;
+    use crate::float::distance::{euclidean, manhattan, squared_euclidean, Manhattan};
     use crate::float::kdtree::{Axis, KdTree};
     use rand::Rng;
+    use std::collections::BinaryHeap;
 
     type AX = f32;
 
@@ -196,7 +606,7 @@ mod tests {
                 rng.gen_range(0f32..1f32),
                 rng.gen_range(0f32..1f32),
             ];
-            let expected = linear_search(&content_to_add, qty, &query_point);
+            let expected = linear_search(&content_to_add, qty, &query_point, &squared_euclidean);
 
             let result: Vec<_> = tree
                 .nearest_n(&query_point, qty, &squared_euclidean)
@@ -211,6 +621,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn can_query_nearest_n_items_with_qty_outside_tree_size() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        let query_point = [0.5f32, 0.5f32];
+
+        assert!(tree
+            .nearest_n(&query_point, 3, &squared_euclidean)
+            .is_empty());
+
+        for i in 0..10 {
+            tree.add(&[i as f32 / 10f32, i as f32 / 10f32], i);
+        }
+
+        assert!(tree
+            .nearest_n(&query_point, 0, &squared_euclidean)
+            .is_empty());
+
+        let result: Vec<_> = tree
+            .nearest_n(&query_point, 20, &squared_euclidean)
+            .into_iter()
+            .map(|n| n.distance)
+            .collect();
+        let mut expected: Vec<_> = (0..10)
+            .map(|i| squared_euclidean(&query_point, &[i as f32 / 10f32, i as f32 / 10f32]))
+            .collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn can_query_nearest_10_items_large_scale() {
         const TREE_SIZE: usize = 100_000;
@@ -232,7 +673,7 @@ mod tests {
             .collect();
 
         for query_point in query_points {
-            let expected = linear_search(&content_to_add, N, &query_point);
+            let expected = linear_search(&content_to_add, N, &query_point, &squared_euclidean);
 
             let result: Vec<_> = tree
                 .nearest_n(&query_point, N, &squared_euclidean)
@@ -247,15 +688,309 @@ mod tests {
         }
     }
 
-    fn linear_search<A: Axis, const K: usize>(
+    #[test]
+    fn can_query_nearest_n_items_with_manhattan_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const N: usize = 10;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([f32; 4], u32)> = (0..TREE_SIZE)
+            .map(|item| {
+                (
+                    rng.gen::<[f32; 4]>().map(|x| x * 200f32 - 100f32),
+                    item as u32,
+                )
+            })
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rng.gen::<[f32; 4]>().map(|x| x * 200f32 - 100f32);
+            let expected = linear_search(&content_to_add, N, &query_point, &manhattan);
+
+            let result_dists: Vec<_> = tree
+                .nearest_n(&query_point, N, &Manhattan)
+                .into_iter()
+                .map(|n| n.distance)
+                .collect();
+            let expected_dists: Vec<_> = expected.iter().map(|(d, _)| *d).collect();
+
+            assert_eq!(result_dists, expected_dists);
+        }
+    }
+
+    #[test]
+    fn nearest_n_into_matches_nearest_n_when_reusing_a_heap() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const N: usize = 10;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        let mut heap = BinaryHeap::with_capacity(N);
+        let capacity = heap.capacity();
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            tree.nearest_n_into(&query_point, &squared_euclidean, &mut heap);
+
+            assert_eq!(heap.capacity(), capacity);
+
+            let mut result: Vec<_> = heap.drain().collect();
+            result.sort();
+
+            assert_eq!(
+                result,
+                tree.nearest_n(&query_point, capacity, &squared_euclidean)
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_n_vec_matches_a_sorted_linear_search() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const N: usize = 10;
+
+        let content_to_add: Vec<([AX; 4], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 4]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            let mut expected: Vec<(AX, u32)> = content_to_add
+                .iter()
+                .map(|(p, item)| (squared_euclidean(&query_point, p), *item))
+                .collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            expected.truncate(N);
+
+            assert_eq!(
+                tree.nearest_n_vec(&query_point, N, &squared_euclidean),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_n_many_matches_nearest_n_for_each_query() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const N: usize = 10;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 4]>(), item as u32);
+        }
+
+        let queries: Vec<[AX; 4]> = (0..NUM_QUERIES)
+            .map(|_| rand::random::<[AX; 4]>())
+            .collect();
+
+        let result = tree.nearest_n_many(&queries, N, &squared_euclidean);
+
+        assert_eq!(result.len(), NUM_QUERIES);
+        for (query, row) in queries.iter().zip(result) {
+            let expected: Vec<(AX, u32)> = tree
+                .nearest_n(query, N, &squared_euclidean)
+                .into_iter()
+                .map(Into::into)
+                .collect();
+
+            assert_eq!(row, expected);
+        }
+
+        assert!(tree.nearest_n_many(&[], N, &squared_euclidean).is_empty());
+    }
+
+    #[test]
+    fn nearest_n_vec_orders_equidistant_items_by_item() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+        for (idx, point) in [[1.0, 0.0], [0.0, 1.0], [-1.0, 0.0], [0.0, -1.0], [3.0, 3.0]]
+            .iter()
+            .enumerate()
+        {
+            tree.add(point, 10 - idx as u32);
+        }
+
+        assert_eq!(
+            tree.nearest_n_vec(&[0.0, 0.0], 5, &manhattan),
+            vec![(1.0, 7), (1.0, 8), (1.0, 9), (1.0, 10), (6.0, 6)]
+        );
+    }
+
+    #[test]
+    fn nearest_n_by_orders_equidistant_items_with_the_comparator() {
+        const TREE_SIZE: usize = 1000;
+
+        // points on a coarse grid, so that many are tied
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([AX; 2], u32)> = (0..TREE_SIZE)
+            .map(|item| {
+                let point = [rng.gen_range(0..10) as AX, rng.gen_range(0..10) as AX];
+                (point, item as u32)
+            })
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        for (point, item) in &content_to_add {
+            tree.add(point, *item);
+        }
+
+        for qty in [1, 5, 20, 100, TREE_SIZE] {
+            let query_point = [rng.gen_range(0..10) as AX, rng.gen_range(0..10) as AX];
+
+            // odd items first, then even items, each in descending order
+            let tie = |a: u32, b: u32| (b % 2).cmp(&(a % 2)).then(b.cmp(&a));
+
+            let mut expected: Vec<(AX, u32)> = content_to_add
+                .iter()
+                .map(|(p, item)| (squared_euclidean(&query_point, p), *item))
+                .collect();
+            expected.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(tie(a.1, b.1)));
+            expected.truncate(qty);
+
+            let result: Vec<(AX, u32)> = tree
+                .nearest_n_by(&query_point, qty, &squared_euclidean, tie)
+                .into_iter()
+                .map(Into::into)
+                .collect();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn nearest_n_euclidean_returns_the_true_distances() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const N: usize = 10;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            let mut expected: Vec<AX> = content_to_add
+                .iter()
+                .map(|(p, _)| euclidean(&query_point, p))
+                .collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            expected.truncate(N);
+
+            let result: Vec<AX> = tree
+                .nearest_n_euclidean(&query_point, N)
+                .into_iter()
+                .map(|neighbour| neighbour.distance)
+                .collect();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn nearest_n_euclidean_matches_square_rooted_nearest_n() {
+        const TREE_SIZE: usize = 1000;
+        const NUM_QUERIES: usize = 100;
+        const N: usize = 10;
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 3]>(), item as u32);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            let expected: Vec<(AX, u32)> = tree
+                .nearest_n(&query_point, N, &squared_euclidean)
+                .into_iter()
+                .map(|neighbour| (neighbour.distance.sqrt(), neighbour.item))
+                .collect();
+
+            let result: Vec<(AX, u32)> = tree
+                .nearest_n_euclidean(&query_point, N)
+                .into_iter()
+                .map(Into::into)
+                .collect();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn kth_nearest_distance_matches_the_last_of_nearest_n() {
+        const TREE_SIZE: usize = 1000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 3]>(), item as u32);
+        }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+            let k = rng.gen_range(1..50);
+
+            assert_eq!(
+                tree.kth_nearest_distance(&query_point, k, &squared_euclidean),
+                tree.nearest_n(&query_point, k, &squared_euclidean)
+                    .last()
+                    .map(|neighbour| neighbour.distance)
+            );
+        }
+
+        let query_point = rand::random::<[AX; 3]>();
+        assert!(tree
+            .kth_nearest_distance(&query_point, TREE_SIZE, &squared_euclidean)
+            .is_some());
+        assert_eq!(
+            tree.kth_nearest_distance(&query_point, TREE_SIZE + 1, &squared_euclidean),
+            None
+        );
+        assert_eq!(
+            tree.kth_nearest_distance(&query_point, 0, &squared_euclidean),
+            None
+        );
+    }
+
+    fn linear_search<A: Axis, const K: usize, F>(
         content: &[([A; K], u32)],
         qty: usize,
         query_point: &[A; K],
-    ) -> Vec<(A, u32)> {
+        distance_fn: &F,
+    ) -> Vec<(A, u32)>
+    where
+        F: Fn(&[A; K], &[A; K]) -> A,
+    {
         let mut results = vec![];
 
         for &(p, item) in content {
-            let dist = squared_euclidean(query_point, &p);
+            let dist = distance_fn(query_point, &p);
             if results.len() < qty {
                 results.push((dist, item));
                 results.sort_by(|(a_dist, _), (b_dist, _)| a_dist.partial_cmp(&b_dist).unwrap());
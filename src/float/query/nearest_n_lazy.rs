@@ -0,0 +1,248 @@
+use alloc::collections::BinaryHeap;
+use az::{Az, Cast};
+use core::cmp::Ordering;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::float::neighbour::Neighbour;
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Returns an iterator over the elements of the tree in order of increasing distance
+    /// from `query`, using the specified distance metric function.
+    ///
+    /// Rather than finding a fixed number of elements up front, the tree is searched
+    /// best-first: a priority queue holds the nodes that have not been visited yet, keyed
+    /// by the smallest distance that anything inside them could be from `query`, along
+    /// with the elements of the leaves that have been visited. Each call to `next`
+    /// visits nodes only until the nearest remaining element is known, so the work
+    /// done is proportional to the number of elements that are actually taken.
+    /// Elements at the same distance from `query` are returned in order of item.
+    ///
+    /// Prefer this over [`nearest_n`](KdTree::nearest_n) when the number of elements
+    /// needed is not known in advance, for example when taking elements until one
+    /// matches a condition, or when a large `qty` would be requested but usually only
+    /// the first few results are used. When exactly `qty` elements are needed,
+    /// `nearest_n` is faster, as it prunes the tree more tightly and does not need to
+    /// keep the visited leaves' elements in a queue.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[3.0, 4.0, 7.0], 102);
+    ///
+    /// let first_odd = tree
+    ///     .nearest_n_lazy(&[1.0, 2.0, 5.1], &squared_euclidean)
+    ///     .find(|neighbour| neighbour.item % 2 == 1)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(first_odd.item, 101);
+    /// ```
+    #[inline]
+    pub fn nearest_n_lazy<'a, D>(
+        &'a self,
+        query: &[A; K],
+        distance_fn: &'a D,
+    ) -> impl Iterator<Item = Neighbour<A, T>> + 'a
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let query = *query;
+        let mut queue: BinaryHeap<QueueEntry<A, T, K, IDX>> = BinaryHeap::new();
+
+        queue.push(QueueEntry {
+            distance: A::zero(),
+            candidate: Candidate::Node {
+                node_idx: self.root_index,
+                off: [A::zero(); K],
+            },
+        });
+
+        core::iter::from_fn(move || {
+            while let Some(QueueEntry {
+                distance,
+                candidate,
+            }) = queue.pop()
+            {
+                let (node_idx, mut off) = match candidate {
+                    Candidate::Item(item) => return Some(Neighbour { distance, item }),
+                    Candidate::Node { node_idx, off } => (node_idx, off),
+                };
+
+                if KdTree::<A, T, K, B, IDX>::is_stem_index(node_idx) {
+                    let node = &self.stems[node_idx.az::<usize>()];
+                    let split_dim = node.split_dim;
+
+                    let old_off = off[split_dim];
+                    let new_off =
+                        distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+                    let [closer_node_idx, further_node_idx] = if query[split_dim] < node.split_val {
+                        [node.left, node.right]
+                    } else {
+                        [node.right, node.left]
+                    };
+
+                    queue.push(QueueEntry {
+                        distance,
+                        candidate: Candidate::Node {
+                            node_idx: closer_node_idx,
+                            off,
+                        },
+                    });
+
+                    off[split_dim] = new_off;
+                    queue.push(QueueEntry {
+                        distance: distance_fn
+                            .combine(distance_fn.uncombine(distance, old_off), new_off),
+                        candidate: Candidate::Node {
+                            node_idx: further_node_idx,
+                            off,
+                        },
+                    });
+                } else {
+                    let leaf_node = &self.leaves[(node_idx - IDX::leaf_offset()).az::<usize>()];
+
+                    leaf_node
+                        .content_points
+                        .iter()
+                        .zip(leaf_node.content_items.iter())
+                        .take(leaf_node.size.az::<usize>())
+                        .for_each(|(entry, &item)| {
+                            queue.push(QueueEntry {
+                                distance: distance_fn.dist(&query, entry),
+                                candidate: Candidate::Item(item),
+                            })
+                        });
+                }
+            }
+
+            None
+        })
+    }
+}
+
+enum Candidate<A, T, const K: usize, IDX> {
+    Node { node_idx: IDX, off: [A; K] },
+    Item(T),
+}
+
+/// An entry in the priority queue of [`nearest_n_lazy`](KdTree::nearest_n_lazy).
+///
+/// The greatest entry is the one to be popped next: the nearest, with nodes before items
+/// at the same distance, so that every item at that distance is in the queue before any
+/// of them is returned, and then items in ascending order.
+struct QueueEntry<A, T, const K: usize, IDX> {
+    distance: A,
+    candidate: Candidate<A, T, K, IDX>,
+}
+
+impl<A: Axis, T: Content, const K: usize, IDX> Ord for QueueEntry<A, T, K, IDX> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .partial_cmp(&self.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| match (&self.candidate, &other.candidate) {
+                (Candidate::Node { .. }, Candidate::Node { .. }) => Ordering::Equal,
+                (Candidate::Node { .. }, Candidate::Item(_)) => Ordering::Greater,
+                (Candidate::Item(_), Candidate::Node { .. }) => Ordering::Less,
+                (Candidate::Item(a), Candidate::Item(b)) => b.cmp(a),
+            })
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, IDX> PartialOrd for QueueEntry<A, T, K, IDX> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Axis, T: Content, const K: usize, IDX> Eq for QueueEntry<A, T, K, IDX> {}
+
+impl<A: Axis, T: Content, const K: usize, IDX> PartialEq for QueueEntry<A, T, K, IDX> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{manhattan, squared_euclidean};
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+
+    type AX = f32;
+    type DistanceFn = fn(&[AX; 4], &[AX; 4]) -> AX;
+
+    #[test]
+    fn nearest_n_lazy_matches_nearest_n() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const QTY: usize = 20;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            let distance_fns: [DistanceFn; 2] = [squared_euclidean, manhattan];
+            for distance_fn in &distance_fns {
+                let expected: Vec<AX> = tree
+                    .nearest_n(&query_point, QTY, distance_fn)
+                    .into_iter()
+                    .map(|neighbour| neighbour.distance)
+                    .collect();
+
+                let result: Vec<AX> = tree
+                    .nearest_n_lazy(&query_point, distance_fn)
+                    .take(QTY)
+                    .map(|neighbour| neighbour.distance)
+                    .collect();
+
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_n_lazy_returns_every_item_in_order() {
+        const TREE_SIZE: usize = 1000;
+
+        // points on a coarse grid, so that many are tied
+        let mut rng = rand::thread_rng();
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        for item in 0..TREE_SIZE {
+            let point = [rng.gen_range(0..10) as AX, rng.gen_range(0..10) as AX];
+            tree.add(&point, item as u32);
+        }
+
+        let result: Vec<(AX, u32)> = tree
+            .nearest_n_lazy(&[4.5, 5.0], &squared_euclidean)
+            .map(Into::into)
+            .collect();
+
+        let mut expected: Vec<(AX, u32)> = tree
+            .iter()
+            .map(|(point, &item)| (squared_euclidean(&[4.5, 5.0], point), item))
+            .collect();
+        expected.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+
+        assert_eq!(result, expected);
+    }
+}
@@ -1,7 +1,8 @@
+use crate::float::distance::{DistanceMetric, SquaredEuclidean};
 use crate::float::kdtree::{Axis, KdTree, LeafNode};
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
 use az::{Az, Cast};
-use std::ops::Rem;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -11,9 +12,20 @@ where
     /// Queries the tree to find the nearest element to `query`, using the specified
     /// distance metric function.
     ///
+    /// `distance_fn` can be a plain distance function such as `squared_euclidean`, or any
+    /// other [`DistanceMetric`], such as [`Chebyshev`](crate::float::distance::Chebyshev).
+    ///
     /// Faster than querying for nearest_n(point, 1, ...) due
     /// to not needing to allocate memory or maintain sorted results.
     ///
+    /// If more than one item is at exactly the nearest distance to `query`, the smallest
+    /// of those items is returned, regardless of the order that they were added in.
+    /// With a low-precision axis type such as `half::f16`, distances that would differ
+    /// at a higher precision can round to the same value and so also count as ties.
+    ///
+    /// If the tree is empty, `(A::max_value(), T::zero())` is returned. Use
+    /// [`try_nearest_one`](Self::try_nearest_one) to get `None` in that case instead.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -31,118 +43,209 @@ where
     /// assert_eq!(nearest.1, 100);
     /// ```
     #[inline]
-    pub fn nearest_one<F>(&self, query: &[A; K], distance_fn: &F) -> (A, T)
+    pub fn nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best_point = [A::zero(); K];
+        let best = unsafe {
+            self.nearest_one_recurse(
+                query,
+                distance_fn,
+                self.root_index,
+                None,
+                &mut best_point,
+                &mut off,
+                A::zero(),
+            )
+        };
+
+        best.unwrap_or((A::max_value(), T::zero()))
+    }
+
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function, also returning the co-ordinates that it is stored at.
+    ///
+    /// Returns `(distance, item, point)`. Behaves the same as
+    /// [`nearest_one`](Self::nearest_one) otherwise, including for ties. If the tree
+    /// is empty, the returned point is all zeroes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let (_, item, point) = tree.nearest_one_point(&[1.0, 2.0, 5.1], &squared_euclidean);
+    ///
+    /// assert_eq!(item, 100);
+    /// assert_eq!(point, [1.0, 2.0, 5.0]);
+    /// ```
+    #[inline]
+    pub fn nearest_one_point<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T, [A; K])
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         let mut off = [A::zero(); K];
-        unsafe {
+        let mut best_point = [A::zero(); K];
+        let best = unsafe {
             self.nearest_one_recurse(
                 query,
                 distance_fn,
                 self.root_index,
-                0,
-                T::zero(),
-                A::max_value(),
+                None,
+                &mut best_point,
                 &mut off,
                 A::zero(),
             )
+        };
+        let (best_dist, best_item) = best.unwrap_or((A::max_value(), T::zero()));
+
+        (best_dist, best_item, best_point)
+    }
+
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function, returning `None` if the tree is empty.
+    ///
+    /// Behaves the same as [`nearest_one`](Self::nearest_one) otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// assert_eq!(tree.try_nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean), None);
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    ///
+    /// let nearest = tree.try_nearest_one(&[1.0, 2.0, 5.1], &squared_euclidean);
+    ///
+    /// assert_eq!(nearest.map(|(_, item)| item), Some(100));
+    /// ```
+    #[inline]
+    pub fn try_nearest_one<D>(&self, query: &[A; K], distance_fn: &D) -> Option<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        if self.size == T::zero() {
+            return None;
         }
+
+        Some(self.nearest_one(query, distance_fn))
+    }
+
+    /// Queries the tree to find the nearest element to `query` by euclidean distance,
+    /// returning the true (rather than squared) distance to it.
+    ///
+    /// The search is carried out using [`SquaredEuclidean`], which is faster and finds
+    /// the same nearest element, and the square root of the distance is taken once at
+    /// the end. This gives the same result as taking the square root of the distance
+    /// returned by `nearest_one(query, &squared_euclidean)` yourself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[3.0, 4.0], 100);
+    /// tree.add(&[6.0, 8.0], 101);
+    ///
+    /// assert_eq!(tree.nearest_one_euclidean(&[0.0, 0.0]), (5.0, 100));
+    /// ```
+    #[inline]
+    pub fn nearest_one_euclidean(&self, query: &[A; K]) -> (A, T) {
+        let (dist, item) = self.nearest_one(query, &SquaredEuclidean);
+
+        (dist.sqrt(), item)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[inline]
-    unsafe fn nearest_one_recurse<F>(
+    unsafe fn nearest_one_recurse<D>(
         &self,
         query: &[A; K],
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
-        split_dim: usize,
-        mut best_item: T,
-        mut best_dist: A,
+        mut best: Option<(A, T)>,
+        best_point: &mut [A; K],
         off: &mut [A; K],
         rd: A,
-    ) -> (A, T)
+    ) -> Option<(A, T)>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = &self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim] - node.split_val;
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
                 };
-            let next_split_dim = (split_dim + 1).rem(K);
 
-            let (dist, item) = self.nearest_one_recurse(
+            best = self.nearest_one_recurse(
                 query,
                 distance_fn,
                 closer_node_idx,
-                next_split_dim,
-                best_item,
-                best_dist,
+                best,
+                best_point,
                 off,
                 rd,
             );
 
-            if dist < best_dist {
-                best_dist = dist;
-                best_item = item;
-            }
-
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd + new_off * new_off - old_off * old_off;
-            if rd <= best_dist {
+            // once an exact match has been found, the best distance is zero and this only
+            // visits nodes that the query lies on the boundary of. Returning early instead
+            // would be wrong: those nodes can hold copies of the query point with smaller items.
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if Self::may_hold_nearer(rd, best) {
                 off[split_dim] = new_off;
-                let (dist, item) = self.nearest_one_recurse(
+                best = self.nearest_one_recurse(
                     query,
                     distance_fn,
                     further_node_idx,
-                    next_split_dim,
-                    best_item,
-                    best_dist,
+                    best,
+                    best_point,
                     off,
                     rd,
                 );
                 off[split_dim] = old_off;
-
-                if dist < best_dist {
-                    best_dist = dist;
-                    best_item = item;
-                }
             }
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
 
-            Self::search_content_for_best(
-                query,
-                distance_fn,
-                &mut best_item,
-                &mut best_dist,
-                leaf_node,
-            );
+            Self::search_content_for_best(query, distance_fn, &mut best, best_point, leaf_node);
         }
 
-        (best_dist, best_item)
+        best
     }
 
-    fn search_content_for_best<F>(
+    pub(crate) fn search_content_for_best<D>(
         query: &[A; K],
-        distance_fn: &F,
-        best_item: &mut T,
-        best_dist: &mut A,
+        distance_fn: &D,
+        best: &mut Option<(A, T)>,
+        best_point: &mut [A; K],
         leaf_node: &LeafNode<A, T, K, B, IDX>,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
         leaf_node
             .content_points
@@ -150,20 +253,52 @@ where
             .enumerate()
             .take(leaf_node.size.az::<usize>())
             .for_each(|(idx, entry)| {
-                let dist = distance_fn(query, entry);
-                if dist < *best_dist {
-                    *best_dist = dist;
-                    *best_item = unsafe { *leaf_node.content_items.get_unchecked(idx) };
+                let dist = distance_fn.dist(query, entry);
+                let item = unsafe { *leaf_node.content_items.index_unchecked(idx) };
+                if Self::is_nearer(dist, item, *best) {
+                    *best = Some((dist, item));
+                    *best_point = *entry;
                 }
             });
     }
+
+    /// Returns `true` if an element at `dist` from the query, with item `item`, should
+    /// replace `best`, the nearest element found so far: if it is nearer, or is equally
+    /// near and has a smaller item.
+    ///
+    /// `None` stands for nothing having been found yet, rather than a sentinel distance
+    /// such as `A::max_value()`, so that an element at any distance can be found.
+    #[inline]
+    pub(crate) fn is_nearer(dist: A, item: T, best: Option<(A, T)>) -> bool {
+        match best {
+            Some((best_dist, best_item)) => {
+                dist < best_dist || (dist == best_dist && item < best_item)
+            }
+            // a NaN distance is never nearer than anything, including nothing
+            None => !dist.is_nan(),
+        }
+    }
+
+    /// Returns `true` if a node whose contents are at least `rd` from the query could
+    /// hold an element that should replace `best`, the nearest element found so far.
+    #[inline]
+    pub(crate) fn may_hold_nearer(rd: A, best: Option<(A, T)>) -> bool {
+        match best {
+            Some((best_dist, _)) => rd <= best_dist,
+            None => true,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::float::distance::manhattan;
+    use crate::float::distance::{
+        chebyshev, euclidean, haversine, manhattan, minkowski, weighted_squared_euclidean,
+        Chebyshev, DistanceMetric, Haversine, Manhattan,
+    };
     use crate::float::kdtree::{Axis, KdTree};
     use rand::Rng;
+    use std::f32::consts::{FRAC_PI_2, PI};
 
     type AX = f32;
 
@@ -248,6 +383,375 @@ mod tests {
         }
     }
 
+    #[test]
+    fn can_query_nearest_one_item_with_manhattan_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        // axis differences are mostly greater than one here, where bounding each
+        // axis by its squared difference would skip parts of the tree incorrectly
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([f32; 3], u32)> = (0..TREE_SIZE)
+            .map(|i| (rng.gen::<[f32; 3]>().map(|x| x * 200f32 - 100f32), i as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rng.gen::<[f32; 3]>().map(|x| x * 240f32 - 120f32);
+
+            let expected = linear_search(&content_to_add, &query_point);
+
+            let result = tree.nearest_one(&query_point, &Manhattan);
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn can_query_nearest_one_item_with_chebyshev_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([f32; 3], u32)> = (0..TREE_SIZE)
+            .map(|i| {
+                (
+                    [
+                        rng.gen_range(-100f32..100f32),
+                        rng.gen_range(-100f32..100f32),
+                        rng.gen_range(-100f32..100f32),
+                    ],
+                    i as u32,
+                )
+            })
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = [
+                rng.gen_range(-120f32..120f32),
+                rng.gen_range(-120f32..120f32),
+                rng.gen_range(-120f32..120f32),
+            ];
+
+            let expected = content_to_add
+                .iter()
+                .map(|(p, _)| chebyshev(&query_point, p))
+                .fold(f32::INFINITY, f32::min);
+
+            let result = tree.nearest_one(&query_point, &Chebyshev);
+
+            assert_eq!(result.0, expected);
+        }
+    }
+
+    #[test]
+    fn can_query_nearest_one_item_with_weighted_squared_euclidean_distance() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        tree.add(&[1.0, 0.0], 1);
+        tree.add(&[0.0, 0.8], 2);
+        for item in 3..20u32 {
+            tree.add(&[item as f32, item as f32], item);
+        }
+
+        let query_point = [0.0, 0.0];
+
+        let unweighted = weighted_squared_euclidean([1.0, 1.0]);
+        assert_eq!(tree.nearest_one(&query_point, &unweighted).1, 2);
+
+        let weighted = weighted_squared_euclidean([1.0, 2.0]);
+        let result = tree.nearest_one(&query_point, &weighted);
+        assert_eq!(result, (1.0, 1));
+    }
+
+    #[test]
+    fn can_query_nearest_one_item_with_weighted_squared_euclidean_distance_large_scale() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1_000;
+
+        let content_to_add: Vec<([f32; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand::random::<([f32; 4], u32)>())
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let metric = weighted_squared_euclidean([1.0, 10.0, 0.1, 100.0]);
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[f32; 4]>();
+            let expected = content_to_add
+                .iter()
+                .map(|(p, _)| metric.dist(&query_point, p))
+                .fold(f32::INFINITY, f32::min);
+
+            let result = tree.nearest_one(&query_point, &metric);
+
+            assert_eq!(result.0, expected);
+        }
+    }
+
+    #[test]
+    fn can_query_nearest_one_item_with_haversine_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+        let mut random_lat_lng = || [rng.gen_range(-FRAC_PI_2..FRAC_PI_2), rng.gen_range(-PI..PI)];
+
+        let content_to_add: Vec<([f32; 2], u32)> = (0..TREE_SIZE)
+            .map(|i| (random_lat_lng(), i as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = random_lat_lng();
+
+            let expected = content_to_add
+                .iter()
+                .map(|(p, _)| haversine(&query_point, p))
+                .fold(f32::INFINITY, f32::min);
+
+            let result = tree.nearest_one(&query_point, &Haversine);
+
+            assert_eq!(result.0, expected);
+        }
+    }
+
+    #[test]
+    fn can_query_nearest_one_item_with_minkowski_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([f32; 3], u32)> = (0..TREE_SIZE)
+            .map(|i| (rng.gen::<[f32; 3]>().map(|x| x * 200f32 - 100f32), i as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let metric = minkowski(3f32);
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rng.gen::<[f32; 3]>().map(|x| x * 240f32 - 120f32);
+
+            let expected = content_to_add
+                .iter()
+                .map(|(p, _)| metric.dist(&query_point, p))
+                .fold(f32::INFINITY, f32::min);
+
+            let result = tree.nearest_one(&query_point, &metric);
+
+            assert_eq!(result.0, expected);
+        }
+    }
+
+    #[test]
+    fn querying_an_empty_tree_finds_nothing() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        assert_eq!(tree.try_nearest_one(&[0.5, 0.5], &manhattan), None);
+        assert!(tree.nearest_n(&[0.5, 0.5], 3, &manhattan).is_empty());
+
+        tree.add(&[0.0, 0.0], 0);
+
+        assert_eq!(
+            tree.try_nearest_one(&[0.5, 0.5], &manhattan),
+            Some((1.0, 0))
+        );
+        assert_eq!(tree.nearest_n(&[0.5, 0.5], 3, &manhattan).len(), 1);
+
+        tree.remove(&[0.0, 0.0], 0);
+
+        assert_eq!(tree.try_nearest_one(&[0.5, 0.5], &manhattan), None);
+    }
+
+    #[test]
+    fn nearest_one_breaks_ties_in_favour_of_the_smallest_item() {
+        let equidistant: [([AX; 2], u32); 2] = [([0.0, 0.5], 7), ([1.0, 0.5], 3)];
+        let others: Vec<([AX; 2], u32)> = (0..20)
+            .map(|i| ([i as AX / 20.0, 2.0 + i as AX / 20.0], 100 + i))
+            .collect();
+
+        for order in [[0, 1], [1, 0]] {
+            let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+            for idx in order {
+                tree.add(&equidistant[idx].0, equidistant[idx].1);
+            }
+            others
+                .iter()
+                .for_each(|(point, item)| tree.add(point, *item));
+
+            assert_eq!(tree.nearest_one(&[0.5, 0.5], &manhattan), (0.5, 3));
+        }
+    }
+
+    #[test]
+    fn finds_elements_as_far_away_as_the_largest_distance() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+        tree.add(&[AX::MAX, 0.0], 7);
+        tree.add(&[0.0, AX::MAX], 5);
+
+        assert_eq!(tree.nearest_one(&[0.0, 0.0], &Chebyshev), (AX::MAX, 5));
+        assert_eq!(
+            tree.nearest_one_iterative(&[0.0, 0.0], &Chebyshev),
+            (AX::MAX, 5)
+        );
+        assert_eq!(
+            tree.nearest_one_approx(&[0.0, 0.0], 0.0, &Chebyshev),
+            (AX::MAX, 5)
+        );
+
+        // the distance to every element overflows to infinity
+        assert_eq!(
+            tree.nearest_one(&[-AX::MAX, -AX::MAX], &Manhattan),
+            (AX::INFINITY, 5)
+        );
+    }
+
+    #[test]
+    fn exact_matches_are_tie_broken_across_leaves() {
+        const COPIES: u32 = 50;
+
+        let mut rng = rand::thread_rng();
+        let point: [AX; 2] = [0.25, 0.75];
+
+        // enough copies of the same point that they get split across many leaves,
+        // added in descending order of item so the smallest item is added last
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+        for i in 0..200 {
+            tree.add(&[rng.gen(), rng.gen()], 1000 + i);
+        }
+        for item in (0..COPIES).rev() {
+            tree.add(&point, 10 + item);
+        }
+
+        assert_eq!(tree.nearest_one(&point, &manhattan), (0.0, 10));
+        assert_eq!(tree.nearest_one_iterative(&point, &manhattan), (0.0, 10));
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn can_query_nearest_one_item_with_f16_axis() {
+        use half::f16;
+
+        const TREE_SIZE: usize = 1_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([f16; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| {
+                let point = rand::random::<[f32; 3]>().map(f16::from_f32);
+                (point, item as u32)
+            })
+            .collect();
+
+        let mut tree: KdTree<f16, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[f32; 3]>().map(f16::from_f32);
+
+            let expected = linear_search(&content_to_add, &query_point);
+
+            let result = tree.nearest_one(&query_point, &manhattan);
+
+            assert_eq!(result.0, expected.0);
+        }
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn f16_points_that_round_together_are_tied() {
+        use half::f16;
+
+        // 1.0001 and 1.0002 are both rounded to 1.0 in f16
+        let mut tree: KdTree<f16, u32, 1, 4, u32> = KdTree::new();
+        tree.add(&[f16::from_f32(1.0002)], 9);
+        tree.add(&[f16::from_f32(1.0001)], 5);
+
+        assert_eq!(
+            tree.nearest_one(&[f16::from_f32(1.0)], &manhattan),
+            (f16::ZERO, 5)
+        );
+    }
+
+    #[test]
+    fn nearest_one_point_returns_the_stored_point_of_the_nearest_item() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 4], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 4]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            let (dist, item, point) = tree.nearest_one_point(&query_point, &manhattan);
+
+            assert_eq!((dist, item), tree.nearest_one(&query_point, &manhattan));
+            assert_eq!(point, content_to_add[item as usize].0);
+            assert_eq!(dist, manhattan(&query_point, &point));
+        }
+    }
+
+    #[test]
+    fn nearest_one_euclidean_returns_the_true_distance() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            let (dist, item) = tree.nearest_one_euclidean(&query_point);
+
+            let expected = content_to_add
+                .iter()
+                .map(|(p, _)| euclidean(&query_point, p))
+                .fold(f32::INFINITY, f32::min);
+            assert_eq!(dist, expected);
+            assert_eq!(
+                dist,
+                euclidean(&query_point, &content_to_add[item as usize].0)
+            );
+        }
+    }
+
     fn linear_search<A: Axis, const K: usize>(
         content: &[([A; K], u32)],
         query_point: &[A; K],
@@ -0,0 +1,200 @@
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Queries the tree to find an approximate nearest element to `query`, using the
+    /// specified distance metric function.
+    ///
+    /// A subtree is skipped when the closest it could possibly be to `query`, multiplied
+    /// by `1 + epsilon`, is still further than the best distance found so far. This
+    /// prunes more aggressively than `nearest_one`, so fewer leaves are visited, at the
+    /// cost of the result possibly not being the true nearest. The distance returned is
+    /// guaranteed to be no more than `(1 + epsilon)` times the distance to the true
+    /// nearest item, in the units returned by `distance_fn` (i.e. for `squared_euclidean`,
+    /// the bound applies to the squared distance).
+    ///
+    /// The larger `epsilon` is, the more of the tree gets skipped, and so the faster
+    /// the query - but the larger the potential error. The gains are most noticeable
+    /// on large trees with higher numbers of dimensions. With an `epsilon` of zero,
+    /// this behaves identically to `nearest_one`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_approx(&[1.0, 2.0, 5.1], 0.5, &squared_euclidean);
+    ///
+    /// assert!((nearest.0 - 0.01f64).abs() < f64::EPSILON);
+    /// assert_eq!(nearest.1, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_one_approx<D>(&self, query: &[A; K], epsilon: A, distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best_point = [A::zero(); K];
+        let best = unsafe {
+            self.nearest_one_approx_recurse(
+                query,
+                A::one() + epsilon,
+                distance_fn,
+                self.root_index,
+                None,
+                &mut best_point,
+                &mut off,
+                A::zero(),
+            )
+        };
+
+        best.unwrap_or((A::max_value(), T::zero()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    unsafe fn nearest_one_approx_recurse<D>(
+        &self,
+        query: &[A; K],
+        scale: A,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        mut best: Option<(A, T)>,
+        best_point: &mut [A; K],
+        off: &mut [A; K],
+        rd: A,
+    ) -> Option<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            best = self.nearest_one_approx_recurse(
+                query,
+                scale,
+                distance_fn,
+                closer_node_idx,
+                best,
+                best_point,
+                off,
+                rd,
+            );
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if Self::may_hold_nearer(rd * scale, best) {
+                off[split_dim] = new_off;
+                best = self.nearest_one_approx_recurse(
+                    query,
+                    scale,
+                    distance_fn,
+                    further_node_idx,
+                    best,
+                    best_point,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            Self::search_content_for_best(query, distance_fn, &mut best, best_point, leaf_node);
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn nearest_one_approx_with_zero_epsilon_matches_nearest_one() {
+        const TREE_SIZE: usize = 100_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([f32; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand::random::<([f32; 4], u32)>())
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[f32; 4]>();
+
+            let expected = tree.nearest_one(&query_point, &squared_euclidean);
+            let result = tree.nearest_one_approx(&query_point, 0f32, &squared_euclidean);
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn nearest_one_approx_is_within_epsilon_of_nearest() {
+        const TREE_SIZE: usize = 100_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([f32; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand::random::<([f32; 4], u32)>())
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        for epsilon in [0.1f32, 0.5f32, 2f32] {
+            for _ in 0..NUM_QUERIES {
+                let query_point = rand::random::<[f32; 4]>();
+
+                let exact = content_to_add
+                    .iter()
+                    .map(|(p, _)| squared_euclidean(&query_point, p))
+                    .fold(f32::INFINITY, f32::min);
+                let (dist, item) =
+                    tree.nearest_one_approx(&query_point, epsilon, &squared_euclidean);
+
+                assert!(dist <= exact * (1f32 + epsilon));
+                assert!(content_to_add
+                    .iter()
+                    .any(|(p, i)| *i == item && squared_euclidean(&query_point, p) == dist));
+            }
+        }
+    }
+}
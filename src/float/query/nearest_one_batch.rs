@@ -0,0 +1,88 @@
+use alloc::vec::Vec;
+use az::Cast;
+use rayon::prelude::*;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Queries the tree to find the nearest element to each of the points in `queries`,
+    /// using the specified distance metric function.
+    ///
+    /// The queries are spread across the threads of rayon's global thread pool, and the
+    /// results are returned in the same order as `queries`. Each result is identical to
+    /// what [`nearest_one`](KdTree::nearest_one) would return for that query.
+    ///
+    /// Requires the `rayon` crate feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_batch(&[[1.0, 2.0, 5.1], [2.0, 3.0, 5.9]], &squared_euclidean);
+    ///
+    /// assert_eq!(nearest[0].1, 100);
+    /// assert_eq!(nearest[1].1, 101);
+    /// ```
+    #[inline]
+    pub fn nearest_one_batch<D>(&self, queries: &[[A; K]], distance_fn: &D) -> Vec<(A, T)>
+    where
+        A: Send,
+        T: Send,
+        D: DistanceMetric<A, K> + Sync,
+    {
+        queries
+            .par_iter()
+            .map(|query| self.nearest_one(query, distance_fn))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn can_query_nearest_one_for_a_batch_of_points() {
+        const TREE_SIZE: usize = 100_000;
+        const NUM_QUERIES: usize = 1_000;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|_| rand::random::<([AX; 3], u32)>())
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let queries: Vec<[AX; 3]> = (0..NUM_QUERIES)
+            .map(|_| rand::random::<[AX; 3]>())
+            .collect();
+
+        let expected: Vec<(AX, u32)> = queries
+            .iter()
+            .map(|query| tree.nearest_one(query, &squared_euclidean))
+            .collect();
+
+        assert_eq!(
+            tree.nearest_one_batch(&queries, &squared_euclidean),
+            expected
+        );
+    }
+}
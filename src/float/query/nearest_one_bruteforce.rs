@@ -0,0 +1,104 @@
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Finds the nearest element to `query` by checking the distance to every element in
+    /// the tree, using the specified distance metric function.
+    ///
+    /// Unlike [`nearest_one`](KdTree::nearest_one), no parts of the tree are skipped, so
+    /// this gives correct results for distance functions that cannot be bounded one axis
+    /// at a time, such as [`cosine`](crate::float::distance::cosine). It takes time
+    /// proportional to the size of the tree, so prefer `nearest_one` for any metric that
+    /// supports it. As with `nearest_one`, ties are won by the smallest item, and an
+    /// empty tree returns `(A::max_value(), T::zero())`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::float::distance::cosine;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 0.0], 100);
+    /// tree.add(&[10.0, 9.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_bruteforce(&[1.0, 1.0], &cosine);
+    ///
+    /// assert_eq!(nearest.1, 101);
+    /// ```
+    #[inline]
+    pub fn nearest_one_bruteforce<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut best_dist = A::max_value();
+        let mut best_item = T::zero();
+
+        self.leaves.iter().for_each(|leaf_node| {
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .for_each(|(entry, &item)| {
+                    let dist = distance_fn.dist(query, entry);
+                    if dist < best_dist || (dist == best_dist && item < best_item) {
+                        best_dist = dist;
+                        best_item = item;
+                    }
+                });
+        });
+
+        (best_dist, best_item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{cosine, squared_euclidean};
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+
+    type AX = f32;
+
+    #[test]
+    fn nearest_one_bruteforce_matches_a_linear_search() {
+        const TREE_SIZE: usize = 1000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rng.gen::<[AX; 3]>().map(|x| x * 2.0 - 1.0), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rng.gen::<[AX; 3]>().map(|x| x * 2.0 - 1.0);
+
+            let expected = content_to_add
+                .iter()
+                .map(|(p, item)| (cosine(&query_point, p), *item))
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap();
+
+            assert_eq!(tree.nearest_one_bruteforce(&query_point, &cosine), expected);
+            assert_eq!(
+                tree.nearest_one_bruteforce(&query_point, &squared_euclidean)
+                    .0,
+                tree.nearest_one(&query_point, &squared_euclidean).0
+            );
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Queries the tree to find the nearest element to `query` for which `pred` returns
+    /// `true`, using the specified distance metric function.
+    ///
+    /// Elements for which `pred` returns `false` are skipped over, as if they were not in
+    /// the tree. Returns `None` if `pred` returns `false` for every element.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_filtered(&[1.0, 2.0, 5.1], &squared_euclidean, |item| item != 100);
+    ///
+    /// assert_eq!(nearest.map(|(_, item)| item), Some(101));
+    /// ```
+    #[inline]
+    pub fn nearest_one_filtered<D, P>(
+        &self,
+        query: &[A; K],
+        distance_fn: &D,
+        pred: P,
+    ) -> Option<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+        P: Fn(T) -> bool,
+    {
+        let mut off = [A::zero(); K];
+        let mut best = None;
+
+        unsafe {
+            self.nearest_one_filtered_recurse(
+                query,
+                distance_fn,
+                &pred,
+                self.root_index,
+                &mut best,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        best
+    }
+
+    /// Queries the tree to find the nearest element to `query` whose item is not
+    /// `exclude`, using the specified distance metric function.
+    ///
+    /// This is useful for finding the nearest neighbour of a point that is itself stored
+    /// in the tree, which would otherwise be found at a distance of zero, and is the
+    /// building block for constructing a nearest neighbour graph of the tree's contents.
+    /// Returns `None` if every element in the tree has the item `exclude`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_excluding(&[1.0, 2.0, 5.0], 100, &squared_euclidean);
+    ///
+    /// assert_eq!(nearest, Some((3.0, 101)));
+    /// ```
+    #[inline]
+    pub fn nearest_one_excluding<D>(
+        &self,
+        query: &[A; K],
+        exclude: T,
+        distance_fn: &D,
+    ) -> Option<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        self.nearest_one_filtered(query, distance_fn, |item| item != exclude)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_one_filtered_recurse<D, P>(
+        &self,
+        query: &[A; K],
+        distance_fn: &D,
+        pred: &P,
+        curr_node_idx: IDX,
+        best: &mut Option<(A, T)>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+        P: Fn(T) -> bool,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.nearest_one_filtered_recurse(
+                query,
+                distance_fn,
+                pred,
+                closer_node_idx,
+                best,
+                off,
+                rd,
+            );
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if Self::may_hold_nearer(rd, *best) {
+                off[split_dim] = new_off;
+                self.nearest_one_filtered_recurse(
+                    query,
+                    distance_fn,
+                    pred,
+                    further_node_idx,
+                    best,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .filter(|(_, &item)| pred(item))
+                .for_each(|(entry, &item)| {
+                    let dist = distance_fn.dist(query, entry);
+                    if Self::is_nearer(dist, item, *best) {
+                        *best = Some((dist, item));
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn can_query_nearest_one_filtered() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let is_odd = |item: u32| item % 2 == 1;
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            let expected = content_to_add
+                .iter()
+                .filter(|(_, item)| is_odd(*item))
+                .map(|(point, item)| (squared_euclidean(&query_point, point), *item))
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            assert_eq!(
+                tree.nearest_one_filtered(&query_point, &squared_euclidean, is_odd),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_one_filtered_returns_none_when_nothing_passes() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        assert!(tree
+            .nearest_one_filtered(&[0.5, 0.5], &squared_euclidean, |_| true)
+            .is_none());
+
+        for i in 0..20 {
+            tree.add(&[i as AX / 20.0, i as AX / 20.0], i);
+        }
+
+        assert!(tree
+            .nearest_one_filtered(&[0.5, 0.5], &squared_euclidean, |_| false)
+            .is_none());
+    }
+
+    #[test]
+    fn nearest_one_excluding_finds_each_points_nearest_other_point() {
+        const TREE_SIZE: usize = 1000;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for (query_point, query_item) in &content_to_add {
+            let expected = content_to_add
+                .iter()
+                .filter(|(_, item)| item != query_item)
+                .map(|(point, item)| (squared_euclidean(query_point, point), *item))
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            assert_eq!(
+                tree.nearest_one_excluding(query_point, *query_item, &squared_euclidean),
+                expected
+            );
+        }
+
+        let mut single: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        single.add(&[0.5, 0.5, 0.5], 7);
+        assert_eq!(
+            single.nearest_one_excluding(&[0.5, 0.5, 0.5], 7, &squared_euclidean),
+            None
+        );
+    }
+
+    #[test]
+    fn nearest_one_filtered_returns_smallest_item_of_equidistant_elements() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        let unit_points = [[1.0, 0.0], [0.0, 1.0], [-1.0, 0.0], [0.0, -1.0]];
+        for item in (0..40).rev() {
+            tree.add(&unit_points[item as usize % 4], item);
+            tree.add(&[0.0, 0.0], 100 + item);
+        }
+
+        assert_eq!(
+            tree.nearest_one_filtered(&[0.0, 0.0], &squared_euclidean, |item| item < 100)
+                .map(|(_, item)| item),
+            Some(0)
+        );
+        assert_eq!(
+            tree.nearest_one_excluding(&[0.0, 0.0], 100, &squared_euclidean),
+            Some((0.0, 101))
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::vec;
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Queries the tree to find the nearest element to `query`, using the specified
+    /// distance metric function, without recursing.
+    ///
+    /// Returns the same result as [`nearest_one`](Self::nearest_one), including for ties,
+    /// but keeps the nodes still to be visited on an explicit stack on the heap rather
+    /// than on the call stack. Use this on trees that may be extremely unbalanced, such
+    /// as those built by adding sorted points one at a time, where the depth of the tree
+    /// could otherwise overflow the call stack. On balanced trees, `nearest_one` is faster.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_iterative(&[1.0, 2.0, 5.1], &squared_euclidean);
+    ///
+    /// assert!((nearest.0 - 0.01f64).abs() < f64::EPSILON);
+    /// assert_eq!(nearest.1, 100);
+    /// ```
+    #[inline]
+    pub fn nearest_one_iterative<D>(&self, query: &[A; K], distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut best = None;
+        let mut best_point = [A::zero(); K];
+
+        let mut to_visit = vec![(self.root_index, A::zero(), [A::zero(); K])];
+        while let Some((curr_node_idx, rd, mut off)) = to_visit.pop() {
+            // the best distance may have improved since this node was pushed
+            if !Self::may_hold_nearer(rd, best) {
+                continue;
+            }
+
+            if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+                let node = &self.stems[curr_node_idx.az::<usize>()];
+                let split_dim = node.split_dim;
+
+                let old_off = off[split_dim];
+                let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+                let [closer_node_idx, further_node_idx] = if query[split_dim] < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+                // pushed first so that the closer node gets visited first
+                let further_rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+                if Self::may_hold_nearer(further_rd, best) {
+                    let mut further_off = off;
+                    further_off[split_dim] = new_off;
+                    to_visit.push((further_node_idx, further_rd, further_off));
+                }
+
+                off[split_dim] = old_off;
+                to_visit.push((closer_node_idx, rd, off));
+            } else {
+                let leaf_node = &self.leaves[(curr_node_idx - IDX::leaf_offset()).az::<usize>()];
+
+                Self::search_content_for_best(
+                    query,
+                    distance_fn,
+                    &mut best,
+                    &mut best_point,
+                    leaf_node,
+                );
+            }
+        }
+
+        best.unwrap_or((A::max_value(), T::zero()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{manhattan, squared_euclidean};
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn nearest_one_iterative_matches_nearest_one() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            assert_eq!(
+                tree.nearest_one_iterative(&query_point, &manhattan),
+                tree.nearest_one(&query_point, &manhattan)
+            );
+            assert_eq!(
+                tree.nearest_one_iterative(&query_point, &squared_euclidean),
+                tree.nearest_one(&query_point, &squared_euclidean)
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_one_iterative_can_query_a_very_deep_tree() {
+        const TREE_SIZE: usize = 20_000;
+
+        // adding sorted points one at a time always splits the last leaf,
+        // giving a tree that is thousands of stems deep
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::with_capacity(TREE_SIZE);
+        for i in 0..TREE_SIZE {
+            tree.add(&[i as AX, i as AX], i as u32);
+        }
+
+        assert!(tree.height() > 1_000);
+
+        for i in [0, 1, TREE_SIZE / 2, TREE_SIZE - 1] {
+            let query_point = [i as AX, i as AX + 0.5];
+
+            assert_eq!(
+                tree.nearest_one_iterative(&query_point, &manhattan),
+                (0.5, i as u32)
+            );
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Queries the tree to find the nearest element to `query` considering only its first
+    /// `dims` axes, using the specified distance metric function.
+    ///
+    /// The remaining axes are ignored, as if every element had the same co-ordinates as
+    /// `query` along them, so the distance returned is measured over the first `dims` axes
+    /// alone. A `dims` of `K` or more considers every axis, giving the same result as
+    /// [`nearest_one`](KdTree::nearest_one), including for ties, which are broken in favour
+    /// of the smallest item in the same way.
+    ///
+    /// Note that this can be much slower than `nearest_one`. A stem that splits along one
+    /// of the ignored axes says nothing about the distance to the elements on either side
+    /// of it, so both of its children always have to be searched. The fewer axes that are
+    /// considered, the more of the tree is visited, up to all of it when `dims` is zero.
+    ///
+    /// If the tree is empty, `(A::max_value(), T::zero())` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 50.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let query = [1.0, 2.0, 5.0];
+    ///
+    /// assert_eq!(tree.nearest_one(&query, &squared_euclidean), (3.0, 101));
+    /// assert_eq!(tree.nearest_one_prefix(&query, 2, &squared_euclidean), (0.0, 100));
+    /// ```
+    #[inline]
+    pub fn nearest_one_prefix<D>(&self, query: &[A; K], dims: usize, distance_fn: &D) -> (A, T)
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best = None;
+
+        unsafe {
+            self.nearest_one_prefix_recurse(
+                query,
+                dims.min(K),
+                distance_fn,
+                self.root_index,
+                &mut best,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        best.unwrap_or((A::max_value(), T::zero()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_one_prefix_recurse<D>(
+        &self,
+        query: &[A; K],
+        dims: usize,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        best: &mut Option<(A, T)>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.nearest_one_prefix_recurse(
+                query,
+                dims,
+                distance_fn,
+                closer_node_idx,
+                best,
+                off,
+                rd,
+            );
+
+            // a split along an ignored axis puts no bound on the distance to the further
+            // side, which is then searched with the same bound as the closer side
+            if split_dim >= dims {
+                self.nearest_one_prefix_recurse(
+                    query,
+                    dims,
+                    distance_fn,
+                    further_node_idx,
+                    best,
+                    off,
+                    rd,
+                );
+                return;
+            }
+
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if Self::may_hold_nearer(rd, *best) {
+                off[split_dim] = new_off;
+                self.nearest_one_prefix_recurse(
+                    query,
+                    dims,
+                    distance_fn,
+                    further_node_idx,
+                    best,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .for_each(|(entry, &item)| {
+                    // taking the ignored axes from the query makes them contribute nothing
+                    let mut point = *entry;
+                    point[dims..].copy_from_slice(&query[dims..]);
+
+                    let dist = distance_fn.dist(query, &point);
+                    if Self::is_nearer(dist, item, *best) {
+                        *best = Some((dist, item));
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{squared_euclidean, Manhattan};
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn nearest_one_prefix_over_every_axis_matches_nearest_one() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 4], u32)>();
+            tree.add(&point, item);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            assert_eq!(
+                tree.nearest_one_prefix(&query_point, 4, &squared_euclidean),
+                tree.nearest_one(&query_point, &squared_euclidean)
+            );
+            assert_eq!(
+                tree.nearest_one_prefix(&query_point, 10, &Manhattan),
+                tree.nearest_one(&query_point, &Manhattan)
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_one_prefix_matches_a_linear_search_over_the_first_axes() {
+        const TREE_SIZE: usize = 1000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 4], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 4]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 8, u32> = KdTree::new();
+        for (point, item) in &content_to_add {
+            tree.add(point, *item);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+
+            for dims in 0..4 {
+                let expected = content_to_add
+                    .iter()
+                    .map(|(point, item)| {
+                        let dist: AX = (0..dims)
+                            .map(|dim| (query_point[dim] - point[dim]).powi(2))
+                            .sum();
+                        (dist, *item)
+                    })
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)))
+                    .unwrap();
+
+                let result = tree.nearest_one_prefix(&query_point, dims, &squared_euclidean);
+
+                assert_eq!(result.1, expected.1);
+                assert!((result.0 - expected.0).abs() < 1e-6);
+            }
+        }
+    }
+}
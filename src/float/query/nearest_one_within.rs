@@ -0,0 +1,228 @@
+use az::{Az, Cast};
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Queries the tree to find the nearest element to `query` that is no further than
+    /// `max_dist` from it, using the specified distance metric function.
+    ///
+    /// Returns `None` if there are no elements within `max_dist`. As with `within`, the
+    /// distance is inclusive. Since anything further than `max_dist` is never considered,
+    /// more of the tree can be skipped than with [`nearest_one`](KdTree::nearest_one).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    ///
+    /// let nearest = tree.nearest_one_within(&[1.0, 2.0, 5.1], 1.0, &squared_euclidean);
+    /// assert_eq!(nearest.map(|(_, item)| item), Some(100));
+    ///
+    /// let nearest = tree.nearest_one_within(&[9.0, 9.0, 9.0], 1.0, &squared_euclidean);
+    /// assert!(nearest.is_none());
+    /// ```
+    #[inline]
+    pub fn nearest_one_within<D>(
+        &self,
+        query: &[A; K],
+        max_dist: A,
+        distance_fn: &D,
+    ) -> Option<(A, T)>
+    where
+        D: DistanceMetric<A, K>,
+    {
+        let mut off = [A::zero(); K];
+        let mut best = None;
+
+        unsafe {
+            self.nearest_one_within_recurse(
+                query,
+                max_dist,
+                distance_fn,
+                self.root_index,
+                &mut best,
+                &mut off,
+                A::zero(),
+            )
+        }
+
+        best
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn nearest_one_within_recurse<D>(
+        &self,
+        query: &[A; K],
+        max_dist: A,
+        distance_fn: &D,
+        curr_node_idx: IDX,
+        best: &mut Option<(A, T)>,
+        off: &mut [A; K],
+        rd: A,
+    ) where
+        D: DistanceMetric<A, K>,
+    {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            let mut rd = rd;
+            let old_off = off[split_dim];
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
+
+            let [closer_node_idx, further_node_idx] =
+                if *query.index_unchecked(split_dim) < node.split_val {
+                    [node.left, node.right]
+                } else {
+                    [node.right, node.left]
+                };
+
+            self.nearest_one_within_recurse(
+                query,
+                max_dist,
+                distance_fn,
+                closer_node_idx,
+                best,
+                off,
+                rd,
+            );
+
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
+            if rd <= best.map_or(max_dist, |(best_dist, _)| best_dist) {
+                off[split_dim] = new_off;
+                self.nearest_one_within_recurse(
+                    query,
+                    max_dist,
+                    distance_fn,
+                    further_node_idx,
+                    best,
+                    off,
+                    rd,
+                );
+                off[split_dim] = old_off;
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .for_each(|(entry, &item)| {
+                    let dist = distance_fn.dist(query, entry);
+                    let is_better = match best {
+                        Some((best_dist, best_item)) => {
+                            dist < *best_dist || (dist == *best_dist && item < *best_item)
+                        }
+                        None => dist <= max_dist,
+                    };
+                    if is_better {
+                        *best = Some((dist, item));
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::{manhattan, squared_euclidean};
+    use crate::float::kdtree::KdTree;
+
+    type AX = f64;
+
+    #[test]
+    fn can_query_nearest_one_within_a_sparse_tree() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..10 {
+            tree.add(&[i as AX * 10.0, i as AX * 10.0], i);
+        }
+
+        assert_eq!(
+            tree.nearest_one_within(&[21.0, 20.0], 4.0, &manhattan),
+            Some((1.0, 2))
+        );
+        assert_eq!(
+            tree.nearest_one_within(&[50.0, 200.0], 10.0, &manhattan),
+            None
+        );
+    }
+
+    #[test]
+    fn nearest_one_within_includes_points_at_exactly_max_dist() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        tree.add(&[1.0, 0.0], 7);
+
+        assert_eq!(
+            tree.nearest_one_within(&[0.0, 0.0], 1.0, &squared_euclidean),
+            Some((1.0, 7))
+        );
+        assert_eq!(
+            tree.nearest_one_within(&[0.0, 0.0], 0.5, &squared_euclidean),
+            None
+        );
+    }
+
+    #[test]
+    fn nearest_one_within_returns_smallest_item_of_equidistant_elements() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        let unit_points = [[1.0, 0.0], [0.0, 1.0], [-1.0, 0.0], [0.0, -1.0]];
+        for item in (0..40).rev() {
+            tree.add(&unit_points[item as usize % 4], item);
+        }
+
+        assert_eq!(
+            tree.nearest_one_within(&[0.0, 0.0], 1.0, &squared_euclidean),
+            Some((1.0, 0))
+        );
+    }
+
+    #[test]
+    fn nearest_one_within_matches_nearest_one_when_in_range() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1_000;
+        const MAX_DIST: AX = 0.001;
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for _ in 0..TREE_SIZE {
+            let (point, item) = rand::random::<([AX; 3], u32)>();
+            tree.add(&point, item);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 3]>();
+
+            let nearest = tree.nearest_one(&query_point, &squared_euclidean);
+            let expected = if nearest.0 <= MAX_DIST {
+                Some(nearest.0)
+            } else {
+                None
+            };
+
+            assert_eq!(
+                tree.nearest_one_within(&query_point, MAX_DIST, &squared_euclidean)
+                    .map(|(dist, _)| dist),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use az::Cast;
+use core::ops::ControlFlow;
+
+use crate::float::distance::DistanceMetric;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Folds `f` over the elements of the tree in order of increasing distance from
+    /// `query`, using the specified distance metric function, starting from `init`.
+    ///
+    /// `f` is passed the accumulator along with the distance and item of the next nearest
+    /// element. It returns [`ControlFlow::Continue`] with the new accumulator to move on to
+    /// the following element, or [`ControlFlow::Break`] with a final value to stop the
+    /// search there. Either way, the last value returned is the result, or `init` if the
+    /// tree is empty. Elements at the same distance from `query` are passed in order of item.
+    ///
+    /// The tree is searched lazily, as with [`nearest_n_lazy`](KdTree::nearest_n_lazy),
+    /// so stopping early avoids visiting the rest of it. This allows queries with
+    /// stopping criteria of their own, such as
+    /// [`nearest_one_filtered`](KdTree::nearest_one_filtered), to be written in terms of
+    /// it, although the dedicated queries are faster where one exists.
+    ///
+    /// # Examples
+    ///
+    /// Finding the nearest elements whose items add up to more than 200:
+    ///
+    /// ```rust
+    /// use core::ops::ControlFlow;
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[3.0, 4.0, 7.0], 102);
+    ///
+    /// let nearest = tree.reduce_nearest(
+    ///     &[1.0, 2.0, 5.1],
+    ///     &squared_euclidean,
+    ///     (0, Vec::new()),
+    ///     |(sum, mut items), _distance, item| {
+    ///         items.push(item);
+    ///         if sum + item > 200 {
+    ///             ControlFlow::Break((sum + item, items))
+    ///         } else {
+    ///             ControlFlow::Continue((sum + item, items))
+    ///         }
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(nearest, (201, vec![100, 101]));
+    /// ```
+    #[inline]
+    pub fn reduce_nearest<D, S, F>(&self, query: &[A; K], distance_fn: &D, init: S, mut f: F) -> S
+    where
+        D: DistanceMetric<A, K>,
+        F: FnMut(S, A, T) -> ControlFlow<S, S>,
+    {
+        let result = self
+            .nearest_n_lazy(query, distance_fn)
+            .try_fold(init, |acc, neighbour| {
+                f(acc, neighbour.distance, neighbour.item)
+            });
+
+        match result {
+            ControlFlow::Continue(acc) | ControlFlow::Break(acc) => acc,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::ControlFlow;
+
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+
+    type AX = f32;
+
+    #[test]
+    fn reduce_nearest_can_implement_nearest_one_filtered() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 4]>(), item as u32);
+        }
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+            let modulus = rand::random::<u32>() % 20 + 1;
+
+            let expected = tree
+                .nearest_one_filtered(&query_point, &squared_euclidean, |item| item % modulus == 0);
+
+            let mut visited = 0;
+            let result = tree.reduce_nearest(
+                &query_point,
+                &squared_euclidean,
+                None,
+                |_, distance, item| {
+                    visited += 1;
+                    if item % modulus == 0 {
+                        ControlFlow::Break(Some((distance, item)))
+                    } else {
+                        ControlFlow::Continue(None)
+                    }
+                },
+            );
+
+            assert_eq!(
+                result.map(|(distance, _)| distance),
+                expected.map(|(d, _)| d)
+            );
+            assert!(visited < TREE_SIZE);
+        }
+    }
+
+    #[test]
+    fn reduce_nearest_folds_over_every_element_unless_stopped() {
+        const TREE_SIZE: usize = 1000;
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 2]>(), item as u32);
+        }
+
+        let query_point = [0.5, 0.5];
+
+        let all = tree.reduce_nearest(
+            &query_point,
+            &squared_euclidean,
+            Vec::new(),
+            |mut acc, distance, item| {
+                acc.push((distance, item));
+                ControlFlow::Continue(acc)
+            },
+        );
+        let expected: Vec<(AX, u32)> = tree
+            .nearest_n_lazy(&query_point, &squared_euclidean)
+            .map(Into::into)
+            .collect();
+        assert_eq!(all, expected);
+
+        let count = tree.reduce_nearest(&query_point, &squared_euclidean, 0, |count, _, _| {
+            if count == 10 {
+                ControlFlow::Break(count)
+            } else {
+                ControlFlow::Continue(count + 1)
+            }
+        });
+        assert_eq!(count, 10);
+
+        let empty: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        let untouched = empty.reduce_nearest(&query_point, &squared_euclidean, 42, |_, _, _| {
+            ControlFlow::Break(0)
+        });
+        assert_eq!(untouched, 42);
+    }
+}
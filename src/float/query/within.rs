@@ -1,8 +1,9 @@
-use az::{Az, Cast};
-use std::collections::BinaryHeap;
-use std::ops::Rem;
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use az::Cast;
 
 use crate::float::{
+    distance::DistanceMetric,
     kdtree::{Axis, KdTree},
     neighbour::Neighbour,
 };
@@ -16,6 +17,9 @@ where
     /// Finds all elements within `dist` of `query`, using the specified
     /// distance metric function.
     ///
+    /// The radius is inclusive, so a `dist` of zero returns only the items
+    /// stored at exactly the same position as `query`.
+    ///
     /// Results are returned sorted nearest-first
     ///
     /// # Examples
@@ -35,108 +39,18 @@ where
     /// assert_eq!(within.len(), 2);
     /// ```
     #[inline]
-    pub fn within<F>(&self, query: &[A; K], dist: A, distance_fn: &F) -> Vec<Neighbour<A, T>>
+    pub fn within<D>(&self, query: &[A; K], dist: A, distance_fn: &D) -> Vec<Neighbour<A, T>>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
-        let mut off = [A::zero(); K];
         let mut matching_items: BinaryHeap<Neighbour<A, T>> = BinaryHeap::new();
 
-        unsafe {
-            self.within_recurse(
-                query,
-                dist,
-                distance_fn,
-                self.root_index,
-                0,
-                &mut matching_items,
-                &mut off,
-                A::zero(),
-            );
-        }
+        self.for_each_within(query, dist, distance_fn, |distance, item| {
+            matching_items.push(Neighbour { distance, item })
+        });
 
         matching_items.into_sorted_vec()
     }
-
-    unsafe fn within_recurse<F>(
-        &self,
-        query: &[A; K],
-        radius: A,
-        distance_fn: &F,
-        curr_node_idx: IDX,
-        split_dim: usize,
-        matching_items: &mut BinaryHeap<Neighbour<A, T>>,
-        off: &mut [A; K],
-        rd: A,
-    ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
-    {
-        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = self.stems.get_unchecked(curr_node_idx.az::<usize>());
-
-            let mut rd = rd;
-            let old_off = off[split_dim];
-            let new_off = query[split_dim] - node.split_val;
-
-            let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
-                    [node.left, node.right]
-                } else {
-                    [node.right, node.left]
-                };
-            let next_split_dim = (split_dim + 1).rem(K);
-
-            self.within_recurse(
-                query,
-                radius,
-                distance_fn,
-                closer_node_idx,
-                next_split_dim,
-                matching_items,
-                off,
-                rd,
-            );
-
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd + new_off * new_off - old_off * old_off;
-
-            if rd <= radius {
-                off[split_dim] = new_off;
-                self.within_recurse(
-                    query,
-                    radius,
-                    distance_fn,
-                    further_node_idx,
-                    next_split_dim,
-                    matching_items,
-                    off,
-                    rd,
-                );
-                off[split_dim] = old_off;
-            }
-        } else {
-            let leaf_node = self
-                .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
-
-            leaf_node
-                .content_points
-                .iter()
-                .enumerate()
-                .take(leaf_node.size.az::<usize>())
-                .for_each(|(idx, entry)| {
-                    let distance = distance_fn(query, entry);
-
-                    if distance < radius {
-                        matching_items.push(Neighbour {
-                            distance,
-                            item: *leaf_node.content_items.get_unchecked(idx.az::<usize>()),
-                        })
-                    }
-                });
-        }
-    }
 }
 
 #[cfg(test)]
@@ -212,6 +126,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn can_query_items_within_zero_radius() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        let content_to_add: [([AX; 2], u32); 6] = [
+            ([0.1f32, 0.2f32], 1),
+            ([0.5f32, 0.5f32], 2),
+            ([0.5f32, 0.5f32], 3),
+            ([0.5f32, 0.6f32], 4),
+            ([0.9f32, 0.1f32], 5),
+            ([0.3f32, 0.5f32], 6),
+        ];
+
+        for (point, item) in content_to_add {
+            tree.add(&point, item);
+        }
+
+        let mut result: Vec<_> = tree
+            .within(&[0.5f32, 0.5f32], 0f32, &manhattan)
+            .into_iter()
+            .map(|n| (n.distance, n.item))
+            .collect();
+        stabilize_sort(&mut result);
+
+        assert_eq!(result, vec![(0f32, 2), (0f32, 3)]);
+    }
+
+    #[test]
+    fn can_query_items_within_radius_on_empty_tree() {
+        let tree: KdTree<AX, u32, 4, 32, u32> = KdTree::new();
+
+        let result = tree.within(&[0.5f32, 0.5f32, 0.5f32, 0.5f32], 1f32, &manhattan);
+
+        assert!(result.is_empty());
+    }
+
     #[test]
     fn can_query_items_within_radius_large_scale() {
         const TREE_SIZE: usize = 100_000;
@@ -235,11 +185,12 @@ mod tests {
         for query_point in query_points {
             let expected = linear_search(&content_to_add, &query_point, RADIUS);
 
-            let result: Vec<_> = tree
+            let mut result: Vec<_> = tree
                 .within(&query_point, RADIUS, &manhattan)
                 .into_iter()
                 .map(|n| (n.distance, n.item))
                 .collect();
+            stabilize_sort(&mut result);
 
             assert_eq!(result, expected);
         }
@@ -254,7 +205,7 @@ mod tests {
 
         for &(p, item) in content {
             let dist = manhattan(query_point, &p);
-            if dist < radius {
+            if dist <= radius {
                 matching_items.push((dist, item));
             }
         }
@@ -0,0 +1,310 @@
+use alloc::{vec, vec::Vec};
+use az::{Az, Cast};
+
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Finds all elements inside the axis-aligned box with corners `min` and `max`.
+    ///
+    /// An element is inside the box if each of its coordinates is between the
+    /// corresponding coordinates of `min` and `max`, inclusive. Returns the point and
+    /// item of each such element, in arbitrary order. If `min` is greater than `max`
+    /// along any axis, the box is empty and so are the results.
+    ///
+    /// With the `bbox-cache` crate feature enabled, each stem of the tree keeps a bounding
+    /// box of everything stored under it, and any subtree whose box does not overlap
+    /// the query box is skipped without being descended into. This makes queries much
+    /// faster when the points are clustered, leaving large parts of the space empty,
+    /// but a little slower when they are spread evenly, and it costs two extra points of
+    /// memory per stem.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[20.0, 30.0, 60.0], 102);
+    ///
+    /// let within = tree.within_bbox(&[0.0, 0.0, 0.0], &[2.0, 3.0, 6.0]);
+    ///
+    /// assert_eq!(within.len(), 2);
+    /// ```
+    #[inline]
+    pub fn within_bbox(&self, min: &[A; K], max: &[A; K]) -> Vec<([A; K], T)> {
+        let mut matching_items = Vec::new();
+
+        if min.iter().zip(max.iter()).all(|(lo, hi)| lo <= hi) {
+            unsafe { self.within_bbox_recurse(min, max, self.root_index, &mut matching_items) }
+        }
+
+        matching_items
+    }
+
+    unsafe fn within_bbox_recurse(
+        &self,
+        min: &[A; K],
+        max: &[A; K],
+        curr_node_idx: IDX,
+        matching_items: &mut Vec<([A; K], T)>,
+    ) {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = &self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            #[cfg(feature = "bbox-cache")]
+            if !node.bbox_overlaps(min, max) {
+                return;
+            }
+
+            // points equal to the split value can end up on either side of it
+            if *min.index_unchecked(split_dim) <= node.split_val {
+                self.within_bbox_recurse(min, max, node.left, matching_items);
+            }
+            if *max.index_unchecked(split_dim) >= node.split_val {
+                self.within_bbox_recurse(min, max, node.right, matching_items);
+            }
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .filter(|(entry, _)| Self::is_within_bbox(entry, min, max))
+                .for_each(|(&entry, &item)| matching_items.push((entry, item)));
+        }
+    }
+
+    /// Returns the indices of every leaf that could hold a point inside the box with
+    /// corners `min` and `max`, pruning in the same way as [`within_bbox`](Self::within_bbox).
+    pub(crate) fn leaves_within_bbox(&self, min: &[A; K], max: &[A; K]) -> Vec<IDX> {
+        let mut leaf_indices = Vec::new();
+
+        if !min.iter().zip(max.iter()).all(|(lo, hi)| lo <= hi) {
+            return leaf_indices;
+        }
+
+        let mut to_visit = vec![self.root_index];
+        while let Some(curr_node_idx) = to_visit.pop() {
+            if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+                let node = &self.stems[curr_node_idx.az::<usize>()];
+                let split_dim = node.split_dim;
+
+                #[cfg(feature = "bbox-cache")]
+                if !node.bbox_overlaps(min, max) {
+                    continue;
+                }
+
+                if min[split_dim] <= node.split_val {
+                    to_visit.push(node.left);
+                }
+                if max[split_dim] >= node.split_val {
+                    to_visit.push(node.right);
+                }
+            } else {
+                leaf_indices.push(curr_node_idx - IDX::leaf_offset());
+            }
+        }
+
+        leaf_indices
+    }
+
+    pub(crate) fn is_within_bbox(point: &[A; K], min: &[A; K], max: &[A; K]) -> bool {
+        point
+            .iter()
+            .zip(min.iter().zip(max.iter()))
+            .all(|(val, (lo, hi))| lo <= val && val <= hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::kdtree::KdTree;
+    #[cfg(feature = "bbox-cache")]
+    use crate::types::Index;
+    use rand::Rng;
+
+    type AX = f32;
+
+    #[test]
+    fn can_query_items_within_bbox() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let min = rng.gen::<[AX; 3]>().map(|x| x * 0.8);
+            let max = min.map(|x| x + rng.gen_range(0.0..0.2));
+
+            let mut expected: Vec<_> = content_to_add
+                .iter()
+                .filter(|(point, _)| (0..3).all(|d| min[d] <= point[d] && point[d] <= max[d]))
+                .map(|(_, item)| *item)
+                .collect();
+            expected.sort();
+
+            let mut result: Vec<_> = tree
+                .within_bbox(&min, &max)
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect();
+            result.sort();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn within_bbox_is_inclusive_and_handles_duplicate_split_values() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..20 {
+            tree.add(&[0.5, 0.5], i);
+        }
+        tree.add(&[0.25, 0.75], 20);
+
+        assert_eq!(tree.within_bbox(&[0.5, 0.5], &[0.5, 0.5]).len(), 20);
+        assert_eq!(tree.within_bbox(&[0.0, 0.0], &[1.0, 1.0]).len(), 21);
+    }
+
+    #[cfg(feature = "bbox-cache")]
+    fn assert_stem_bboxes_contain_their_points<const B: usize>(tree: &KdTree<AX, u32, 3, B, u32>) {
+        // stems that have been freed by removing points are not checked, as they are
+        // no longer part of the tree
+        let mut reachable_stems = Vec::new();
+        let mut to_visit = vec![tree.root_index];
+        while let Some(node_idx) = to_visit.pop() {
+            if KdTree::<AX, u32, 3, B, u32>::is_stem_index(node_idx) {
+                let node = &tree.stems[node_idx as usize];
+                to_visit.push(node.left);
+                to_visit.push(node.right);
+                reachable_stems.push(node_idx);
+            }
+        }
+
+        for stem_idx in reachable_stems {
+            let stem = &tree.stems[stem_idx as usize];
+            let mut to_visit = vec![stem_idx];
+            while let Some(node_idx) = to_visit.pop() {
+                if KdTree::<AX, u32, 3, B, u32>::is_stem_index(node_idx) {
+                    let node = &tree.stems[node_idx as usize];
+                    to_visit.push(node.left);
+                    to_visit.push(node.right);
+                } else {
+                    let leaf = &tree.leaves[(node_idx - u32::leaf_offset()) as usize];
+                    for point in &leaf.content_points[..leaf.size as usize] {
+                        assert!(KdTree::<AX, u32, 3, B, u32>::is_within_bbox(
+                            point, &stem.bbox.min, &stem.bbox.max
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "bbox-cache")]
+    #[test]
+    fn cached_bboxes_contain_every_point_after_changes_to_the_tree() {
+        const TREE_SIZE: usize = 2_000;
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 3, 8, u32> = KdTree::new();
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+        assert_stem_bboxes_contain_their_points(&tree);
+
+        for (point, item) in content_to_add.iter().step_by(3) {
+            tree.update(point, &point.map(|x| x * 1.5), *item);
+        }
+        for (point, item) in content_to_add.iter().skip(1).step_by(3) {
+            tree.remove(point, *item);
+        }
+        assert_stem_bboxes_contain_their_points(&tree);
+
+        let from_bytes: KdTree<AX, u32, 3, 8, u32> = KdTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_stem_bboxes_contain_their_points(&from_bytes);
+
+        tree.rebalance();
+        assert_stem_bboxes_contain_their_points(&tree);
+        assert_stem_bboxes_contain_their_points(&KdTree::<AX, u32, 3, 8, u32>::from_slice(
+            &content_to_add,
+        ));
+    }
+
+    #[cfg(all(feature = "bbox-cache", feature = "serialize"))]
+    #[test]
+    fn cached_bboxes_are_recomputed_when_deserializing_with_serde() {
+        let content_to_add: Vec<([AX; 3], u32)> = (0..2_000)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+        let tree: KdTree<AX, u32, 3, 8, u32> = KdTree::from_slice(&content_to_add);
+
+        let serialized = serde_json::to_string(&tree).unwrap();
+        assert!(!serialized.contains("bbox"));
+
+        let deserialized: KdTree<AX, u32, 3, 8, u32> = serde_json::from_str(&serialized).unwrap();
+        assert_stem_bboxes_contain_their_points(&deserialized);
+        assert_eq!(deserialized.stems, tree.stems);
+    }
+
+    #[cfg(all(feature = "bbox-cache", feature = "serialize_rkyv"))]
+    #[test]
+    fn trees_deserialized_with_rkyv_find_every_item_within_bbox() {
+        use rkyv::{Deserialize, Infallible};
+
+        let content_to_add: Vec<([AX; 3], u32)> = (0..2_000)
+            .map(|item| (rand::random::<[AX; 3]>(), item as u32))
+            .collect();
+        let tree: KdTree<AX, u32, 3, 8, u32> = KdTree::from_slice(&content_to_add);
+
+        let bytes = rkyv::to_bytes::<_, 1024>(&tree).unwrap();
+        let archived = unsafe { rkyv::archived_root::<KdTree<AX, u32, 3, 8, u32>>(&bytes) };
+        let deserialized: KdTree<AX, u32, 3, 8, u32> =
+            archived.deserialize(&mut Infallible).unwrap();
+
+        let (min, max) = ([0.2, 0.3, 0.4], [0.6, 0.7, 0.8]);
+        let mut expected = tree.within_bbox(&min, &max);
+        let mut result = deserialized.within_bbox(&min, &max);
+        expected.sort_by_key(|&(_, item)| item);
+        result.sort_by_key(|&(_, item)| item);
+        assert!(!expected.is_empty());
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn within_bbox_with_inverted_corners_is_empty() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        for i in 0..20 {
+            tree.add(&[i as AX / 20.0, i as AX / 20.0], i);
+        }
+
+        assert!(tree.within_bbox(&[0.0, 1.0], &[1.0, 0.0]).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use alloc::{vec, vec::Vec};
+use az::{Az, Cast};
+use core::cmp::Ordering;
+
+use crate::float::distance::haversine;
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+
+impl<A: Axis, T: Content, const B: usize, IDX: Index<T = IDX>> KdTree<A, T, 2, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Finds all elements within a great-circle angle of `radius_radians` of `query`,
+    /// for trees of `[latitude, longitude]` points in radians.
+    ///
+    /// Latitudes must be within `-π/2..=π/2` and longitudes within `-π..=π`. Returns
+    /// the [`haversine`] distance and item of each element, sorted nearest-first. The
+    /// radius is inclusive.
+    ///
+    /// The radius is first converted into the latitude and longitude bounds of the
+    /// spherical cap around `query`: latitude can differ by at most `radius_radians`,
+    /// and longitude by at most `asin(sin(radius_radians) / cos(latitude))`, or by any
+    /// amount if the cap contains a pole. Boxes that cross the antimeridian are split in
+    /// two. Only the leaves overlapping these bounds are visited, and their contents are
+    /// then filtered exactly using [`haversine`].
+    ///
+    /// This is both more accurate and prunes more of the tree than storing points on a
+    /// unit sphere and querying with `squared_euclidean`. If you do store unit sphere
+    /// points, the squared euclidean radius that corresponds to an angle `r` is the
+    /// squared chord length, `(2 * sin(r / 2))²`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[0.0, 3.1], 100);
+    /// tree.add(&[0.0, -3.1], 101);
+    /// tree.add(&[0.0, 0.0], 102);
+    ///
+    /// // the first two points are only 0.08 radians apart, across the antimeridian
+    /// let within = tree.within_haversine(&[0.0, 3.1], 0.1);
+    ///
+    /// assert_eq!(within.len(), 2);
+    /// assert_eq!(within[0].1, 100);
+    /// assert_eq!(within[1].1, 101);
+    /// ```
+    #[inline]
+    pub fn within_haversine(&self, query: &[A; 2], radius_radians: A) -> Vec<(A, T)> {
+        let mut matching_items: Vec<(A, T)> = Vec::new();
+
+        if radius_radians.is_nan() || radius_radians < A::zero() {
+            return matching_items;
+        }
+
+        let mut leaf_indices = Vec::new();
+        for (min, max) in Self::haversine_bboxes(query, radius_radians) {
+            leaf_indices.extend(self.leaves_within_bbox(&min, &max));
+        }
+
+        // a leaf can overlap both halves of a box that crosses the antimeridian
+        leaf_indices.sort_unstable();
+        leaf_indices.dedup();
+
+        for leaf_idx in leaf_indices {
+            let leaf_node = &self.leaves[leaf_idx.az::<usize>()];
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .for_each(|(entry, &item)| {
+                    let distance = haversine(query, entry);
+                    if distance <= radius_radians {
+                        matching_items.push((distance, item));
+                    }
+                });
+        }
+
+        matching_items.sort_unstable_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap_or(Ordering::Equal)
+                .then(a.1.cmp(&b.1))
+        });
+
+        matching_items
+    }
+
+    fn haversine_bboxes(query: &[A; 2], radius: A) -> Vec<([A; 2], [A; 2])> {
+        let pi = A::from(core::f64::consts::PI).unwrap();
+        let frac_pi_2 = A::from(core::f64::consts::FRAC_PI_2).unwrap();
+        let [lat, lng] = *query;
+
+        let min_lat = lat - radius;
+        let max_lat = lat + radius;
+
+        let max_lng_diff = if min_lat <= -frac_pi_2 || max_lat >= frac_pi_2 {
+            None
+        } else {
+            let sin_max_lng_diff = radius.sin() / lat.cos();
+            if sin_max_lng_diff >= A::one() {
+                None
+            } else {
+                Some(sin_max_lng_diff.asin())
+            }
+        };
+
+        let Some(max_lng_diff) = max_lng_diff else {
+            return vec![([min_lat, -pi], [max_lat, pi])];
+        };
+
+        let min_lng = lng - max_lng_diff;
+        let max_lng = lng + max_lng_diff;
+
+        if min_lng < -pi {
+            vec![
+                ([min_lat, -pi], [max_lat, max_lng]),
+                ([min_lat, min_lng + pi + pi], [max_lat, pi]),
+            ]
+        } else if max_lng > pi {
+            vec![
+                ([min_lat, min_lng], [max_lat, pi]),
+                ([min_lat, -pi], [max_lat, max_lng - pi - pi]),
+            ]
+        } else {
+            vec![([min_lat, min_lng], [max_lat, max_lng])]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::haversine;
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    type AX = f64;
+
+    #[test]
+    fn can_query_items_within_haversine_radius() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 1000;
+
+        let mut rng = rand::thread_rng();
+        let content_to_add: Vec<([AX; 2], u32)> = (0..TREE_SIZE)
+            .map(|i| {
+                (
+                    [rng.gen_range(-FRAC_PI_2..FRAC_PI_2), rng.gen_range(-PI..PI)],
+                    i as u32,
+                )
+            })
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = [rng.gen_range(-FRAC_PI_2..FRAC_PI_2), rng.gen_range(-PI..PI)];
+            let radius = rng.gen_range(0.0..0.5);
+
+            let mut expected: Vec<(AX, u32)> = content_to_add
+                .iter()
+                .map(|(p, item)| (haversine(&query_point, p), *item))
+                .filter(|(dist, _)| *dist <= radius)
+                .collect();
+            expected.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+
+            let result = tree.within_haversine(&query_point, radius);
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn within_haversine_finds_items_across_the_antimeridian_and_poles() {
+        let mut tree: KdTree<AX, u32, 2, 4, u32> = KdTree::new();
+
+        tree.add(&[0.0, PI - 0.01], 0);
+        tree.add(&[0.0, -PI + 0.01], 1);
+        tree.add(&[FRAC_PI_2 - 0.01, 0.0], 2);
+        tree.add(&[FRAC_PI_2 - 0.01, PI - 0.5], 3);
+        for i in 0..20 {
+            tree.add(&[0.5, i as AX / 20.0], 10 + i);
+        }
+
+        let items = |query: [AX; 2], radius: AX| -> Vec<u32> {
+            let mut items: Vec<u32> = tree
+                .within_haversine(&query, radius)
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect();
+            items.sort();
+            items
+        };
+
+        assert_eq!(items([0.0, PI], 0.05), vec![0, 1]);
+        assert_eq!(items([0.0, -PI], 0.05), vec![0, 1]);
+        assert_eq!(items([FRAC_PI_2 - 0.01, -PI + 0.5], 0.05), vec![2, 3]);
+        assert!(items([0.0, PI], -1.0).is_empty());
+    }
+}
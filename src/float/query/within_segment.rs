@@ -0,0 +1,349 @@
+use alloc::vec::Vec;
+use az::{Az, Cast};
+use core::cmp::Ordering;
+
+use crate::float::kdtree::{Axis, KdTree};
+use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
+
+impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
+    KdTree<A, T, K, B, IDX>
+where
+    usize: Cast<IDX>,
+{
+    /// Finds all elements within a euclidean distance of `radius` of any point on the
+    /// line segment from `p0` to `p1`.
+    ///
+    /// Returns the distance from the segment to each element, along with its item,
+    /// sorted nearest-first. The distance is the true euclidean distance rather than its
+    /// square, and the radius is inclusive. If `p0` and `p1` are the same, this finds the
+    /// elements within `radius` of that point.
+    ///
+    /// The cell covered by each node is tracked as the tree is walked, and any node whose
+    /// cell is further than `radius` from the segment is skipped along with everything
+    /// under it. This is useful for corridor searches, such as finding everything near
+    /// the path of an object moving from `p0` to `p1`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    ///
+    /// let mut tree: KdTree<f64, u32, 2, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[5.0, 1.0], 100);
+    /// tree.add(&[12.0, 0.0], 101);
+    /// tree.add(&[5.0, 3.0], 102);
+    ///
+    /// let within = tree.within_segment(&[0.0, 0.0], &[10.0, 0.0], 2.0);
+    ///
+    /// assert_eq!(within, vec![(1.0, 100), (2.0, 101)]);
+    /// ```
+    #[inline]
+    pub fn within_segment(&self, p0: &[A; K], p1: &[A; K], radius: A) -> Vec<(A, T)> {
+        let mut matching_items: Vec<(A, T)> = Vec::new();
+
+        if radius.is_nan() || radius < A::zero() {
+            return matching_items;
+        }
+
+        let mut min = [A::neg_infinity(); K];
+        let mut max = [A::infinity(); K];
+        let mut breakpoints = Vec::with_capacity(2 * K + 2);
+
+        unsafe {
+            self.within_segment_recurse(
+                p0,
+                p1,
+                radius,
+                self.root_index,
+                &mut min,
+                &mut max,
+                &mut breakpoints,
+                &mut matching_items,
+            );
+        }
+
+        matching_items.sort_unstable_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap_or(Ordering::Equal)
+                .then(a.1.cmp(&b.1))
+        });
+
+        matching_items
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn within_segment_recurse(
+        &self,
+        p0: &[A; K],
+        p1: &[A; K],
+        radius: A,
+        curr_node_idx: IDX,
+        min: &mut [A; K],
+        max: &mut [A; K],
+        breakpoints: &mut Vec<A>,
+        matching_items: &mut Vec<(A, T)>,
+    ) {
+        if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
+
+            // points equal to the split value can be under either child, so both cells
+            // include it
+            let old_max = max[split_dim];
+            max[split_dim] = node.split_val;
+            if Self::segment_bbox_distance(p0, p1, min, max, breakpoints) <= radius {
+                self.within_segment_recurse(
+                    p0,
+                    p1,
+                    radius,
+                    node.left,
+                    min,
+                    max,
+                    breakpoints,
+                    matching_items,
+                );
+            }
+            max[split_dim] = old_max;
+
+            let old_min = min[split_dim];
+            min[split_dim] = node.split_val;
+            if Self::segment_bbox_distance(p0, p1, min, max, breakpoints) <= radius {
+                self.within_segment_recurse(
+                    p0,
+                    p1,
+                    radius,
+                    node.right,
+                    min,
+                    max,
+                    breakpoints,
+                    matching_items,
+                );
+            }
+            min[split_dim] = old_min;
+        } else {
+            let leaf_node = self
+                .leaves
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+
+            leaf_node
+                .content_points
+                .iter()
+                .zip(leaf_node.content_items.iter())
+                .take(leaf_node.size.az::<usize>())
+                .for_each(|(entry, &item)| {
+                    let distance = Self::segment_point_distance(p0, p1, entry);
+                    if distance <= radius {
+                        matching_items.push((distance, item));
+                    }
+                });
+        }
+    }
+
+    /// Returns the euclidean distance from `point` to the nearest point on the segment
+    /// from `p0` to `p1`.
+    fn segment_point_distance(p0: &[A; K], p1: &[A; K], point: &[A; K]) -> A {
+        let mut along = A::zero();
+        let mut length_squared = A::zero();
+        for dim in 0..K {
+            let dir = p1[dim] - p0[dim];
+            along = along + (point[dim] - p0[dim]) * dir;
+            length_squared = length_squared + dir * dir;
+        }
+
+        let t = if length_squared > A::zero() {
+            (along / length_squared).max(A::zero()).min(A::one())
+        } else {
+            A::zero()
+        };
+
+        (0..K)
+            .map(|dim| {
+                let diff = point[dim] - (p0[dim] + t * (p1[dim] - p0[dim]));
+                diff * diff
+            })
+            .fold(A::zero(), |acc, diff_squared| acc + diff_squared)
+            .sqrt()
+    }
+
+    /// Returns the euclidean distance from the segment from `p0` to `p1` to the nearest
+    /// point in the box with corners `min` and `max`.
+    ///
+    /// The squared distance from the point `p0 + t * (p1 - p0)` to the box is a sum of
+    /// one term per axis, each of which is zero while the point is within the box's
+    /// bounds along that axis, and quadratic in `t` while it is outside them. Between the
+    /// values of `t` at which the segment crosses the planes of the box's faces, the sum
+    /// is therefore a single convex quadratic, whose smallest value can be found exactly.
+    /// `breakpoints` is scratch space, reused to avoid allocating for every node.
+    fn segment_bbox_distance(
+        p0: &[A; K],
+        p1: &[A; K],
+        min: &[A; K],
+        max: &[A; K],
+        breakpoints: &mut Vec<A>,
+    ) -> A {
+        breakpoints.clear();
+        breakpoints.push(A::zero());
+        breakpoints.push(A::one());
+        for dim in 0..K {
+            let dir = p1[dim] - p0[dim];
+            if dir == A::zero() {
+                continue;
+            }
+            for bound in [min[dim], max[dim]] {
+                let t = (bound - p0[dim]) / dir;
+                if t > A::zero() && t < A::one() {
+                    breakpoints.push(t);
+                }
+            }
+        }
+        breakpoints.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let mut nearest_squared = A::infinity();
+        for piece in breakpoints.windows(2) {
+            let (start, end) = (piece[0], piece[1]);
+            let mid = (start + end) / (A::one() + A::one());
+
+            // the quadratic a * t² + b * t + c for the axes that the middle of this piece
+            // is outside the box along; c is not needed to find where it is smallest
+            let mut a = A::zero();
+            let mut b = A::zero();
+            for dim in 0..K {
+                let dir = p1[dim] - p0[dim];
+                let coord = p0[dim] + mid * dir;
+                let bound = if coord < min[dim] {
+                    min[dim]
+                } else if coord > max[dim] {
+                    max[dim]
+                } else {
+                    continue;
+                };
+                a = a + dir * dir;
+                b = b + (dir + dir) * (p0[dim] - bound);
+            }
+
+            let t = if a > A::zero() {
+                (-b / (a + a)).max(start).min(end)
+            } else {
+                start
+            };
+
+            let distance_squared = (0..K)
+                .map(|dim| {
+                    let coord = p0[dim] + t * (p1[dim] - p0[dim]);
+                    let outside = (min[dim] - coord).max(coord - max[dim]).max(A::zero());
+                    outside * outside
+                })
+                .fold(A::zero(), |acc, outside_squared| acc + outside_squared);
+
+            nearest_squared = nearest_squared.min(distance_squared);
+        }
+
+        nearest_squared.sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::float::distance::squared_euclidean;
+    use crate::float::kdtree::KdTree;
+    use rand::Rng;
+
+    type AX = f64;
+
+    fn distance_to_segment(p0: &[AX; 2], p1: &[AX; 2], point: &[AX; 2]) -> AX {
+        let dir = [p1[0] - p0[0], p1[1] - p0[1]];
+        let length_squared = dir[0] * dir[0] + dir[1] * dir[1];
+        let t = if length_squared > 0.0 {
+            (((point[0] - p0[0]) * dir[0] + (point[1] - p0[1]) * dir[1]) / length_squared)
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let nearest = [p0[0] + t * dir[0], p0[1] + t * dir[1]];
+        squared_euclidean(point, &nearest).sqrt()
+    }
+
+    #[test]
+    fn within_segment_matches_a_linear_search() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let content_to_add: Vec<([AX; 2], u32)> = (0..TREE_SIZE)
+            .map(|item| (rand::random::<[AX; 2]>(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let p0 = rand::random::<[AX; 2]>();
+            let p1 = rand::random::<[AX; 2]>();
+            let radius = rng.gen_range(0.0..0.05);
+
+            let mut expected: Vec<(AX, u32)> = content_to_add
+                .iter()
+                .map(|(point, item)| (distance_to_segment(&p0, &p1, point), *item))
+                .filter(|(distance, _)| *distance <= radius)
+                .collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let result = tree.within_segment(&p0, &p1, radius);
+
+            assert_eq!(
+                result.iter().map(|(_, item)| *item).collect::<Vec<_>>(),
+                expected.iter().map(|(_, item)| *item).collect::<Vec<_>>()
+            );
+            for ((distance, _), (expected_distance, _)) in result.iter().zip(expected.iter()) {
+                assert!((distance - expected_distance).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn within_a_degenerate_segment_matches_within() {
+        const TREE_SIZE: usize = 1000;
+
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 2]>(), item as u32);
+        }
+
+        let query = [0.5, 0.5];
+        let radius: AX = 0.1;
+
+        let mut expected: Vec<u32> = tree
+            .within(&query, radius * radius, &squared_euclidean)
+            .into_iter()
+            .map(|neighbour| neighbour.item)
+            .collect();
+        expected.sort();
+
+        let mut result: Vec<u32> = tree
+            .within_segment(&query, &query, radius)
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect();
+        result.sort();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn within_segment_with_a_negative_radius_is_empty() {
+        let mut tree: KdTree<AX, u32, 2, 8, u32> = KdTree::new();
+        tree.add(&[0.5, 0.5], 1);
+
+        assert!(tree
+            .within_segment(&[0.0, 0.0], &[1.0, 1.0], -1.0)
+            .is_empty());
+        assert!(tree
+            .within_segment(&[0.0, 0.0], &[1.0, 1.0], AX::NAN)
+            .is_empty());
+    }
+}
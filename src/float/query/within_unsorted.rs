@@ -1,9 +1,11 @@
 use crate::float::neighbour::Neighbour;
+use alloc::vec::Vec;
 use az::{Az, Cast};
-use std::ops::Rem;
 
+use crate::float::distance::DistanceMetric;
 use crate::float::kdtree::{Axis, KdTree};
 use crate::types::{Content, Index};
+use crate::unchecked::IndexUnchecked;
 
 impl<A: Axis, T: Content, const K: usize, const B: usize, IDX: Index<T = IDX>>
     KdTree<A, T, K, B, IDX>
@@ -13,7 +15,9 @@ where
     /// Finds all elements within `dist` of `query`, using the specified
     /// distance metric function.
     ///
-    /// Results are returned in arbitrary order. Faster than `within`.
+    /// The radius is inclusive, as with `within`. Results are returned in
+    /// arbitrary order, which avoids the cost of sorting them: prefer this
+    /// over `within` when the order of the results is not needed.
     ///
     /// # Examples
     ///
@@ -27,91 +31,118 @@ where
     /// tree.add(&[2.0, 3.0, 6.0], 101);
     /// tree.add(&[200.0, 300.0, 600.0], 102);
     ///
-    /// let within = tree.within(&[1.0, 2.0, 5.0], 10f64, &squared_euclidean);
+    /// let within = tree.within_unsorted(&[1.0, 2.0, 5.0], 10f64, &squared_euclidean);
     ///
     /// assert_eq!(within.len(), 2);
     /// ```
     #[inline]
-    pub fn within_unsorted<F>(
+    pub fn within_unsorted<D>(
         &self,
         query: &[A; K],
         dist: A,
-        distance_fn: &F,
+        distance_fn: &D,
     ) -> Vec<Neighbour<A, T>>
     where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
     {
-        let mut off = [A::zero(); K];
         let mut matching_items = Vec::new();
 
+        self.for_each_within(query, dist, distance_fn, |distance, item| {
+            matching_items.push(Neighbour { distance, item })
+        });
+
+        matching_items
+    }
+
+    /// Calls `f` with the distance and item of each element within `dist` of `query`,
+    /// using the specified distance metric function.
+    ///
+    /// The radius is inclusive, as with `within`. Elements are visited in arbitrary order,
+    /// as the tree is searched. Unlike `within` and `within_unsorted`, nothing is
+    /// allocated, so this suits accumulating results into a structure of your own, or
+    /// reducing them to a single value, particularly when there are many of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use kiddo::float::kdtree::KdTree;
+    /// use kiddo::distance::squared_euclidean;
+    ///
+    /// let mut tree: KdTree<f64, u32, 3, 32, u32> = KdTree::new();
+    ///
+    /// tree.add(&[1.0, 2.0, 5.0], 100);
+    /// tree.add(&[2.0, 3.0, 6.0], 101);
+    /// tree.add(&[200.0, 300.0, 600.0], 102);
+    ///
+    /// let mut total = 0;
+    /// tree.for_each_within(&[1.0, 2.0, 5.0], 10f64, &squared_euclidean, |_, item| {
+    ///     total += item
+    /// });
+    ///
+    /// assert_eq!(total, 201);
+    /// ```
+    #[inline]
+    pub fn for_each_within<D, F>(&self, query: &[A; K], dist: A, distance_fn: &D, mut f: F)
+    where
+        D: DistanceMetric<A, K>,
+        F: FnMut(A, T),
+    {
+        let mut off = [A::zero(); K];
+
         unsafe {
-            self.within_unsorted_recurse(
+            self.for_each_within_recurse(
                 query,
                 dist,
                 distance_fn,
                 self.root_index,
-                0,
-                &mut matching_items,
+                &mut f,
                 &mut off,
                 A::zero(),
             );
         }
-
-        matching_items
     }
 
-    unsafe fn within_unsorted_recurse<F>(
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn for_each_within_recurse<D, F>(
         &self,
         query: &[A; K],
         radius: A,
-        distance_fn: &F,
+        distance_fn: &D,
         curr_node_idx: IDX,
-        split_dim: usize,
-        matching_items: &mut Vec<Neighbour<A, T>>,
+        f: &mut F,
         off: &mut [A; K],
         rd: A,
     ) where
-        F: Fn(&[A; K], &[A; K]) -> A,
+        D: DistanceMetric<A, K>,
+        F: FnMut(A, T),
     {
         if KdTree::<A, T, K, B, IDX>::is_stem_index(curr_node_idx) {
-            let node = self.stems.get_unchecked(curr_node_idx.az::<usize>());
+            let node = self.stems.index_unchecked(curr_node_idx.az::<usize>());
+            let split_dim = node.split_dim;
 
             let mut rd = rd;
             let old_off = off[split_dim];
-            let new_off = query[split_dim] - node.split_val;
+            let new_off = distance_fn.axis_dist(query[split_dim], node.split_val, split_dim);
 
             let [closer_node_idx, further_node_idx] =
-                if *query.get_unchecked(split_dim) < node.split_val {
+                if *query.index_unchecked(split_dim) < node.split_val {
                     [node.left, node.right]
                 } else {
                     [node.right, node.left]
                 };
-            let next_split_dim = (split_dim + 1).rem(K);
 
-            self.within_unsorted_recurse(
-                query,
-                radius,
-                distance_fn,
-                closer_node_idx,
-                next_split_dim,
-                matching_items,
-                off,
-                rd,
-            );
+            self.for_each_within_recurse(query, radius, distance_fn, closer_node_idx, f, off, rd);
 
-            // TODO: switch from dist_fn to a dist trait that can apply to 1D as well as KD
-            //       so that updating rd is not hardcoded to sq euclidean
-            rd = rd + new_off * new_off - old_off * old_off;
+            rd = distance_fn.combine(distance_fn.uncombine(rd, old_off), new_off);
 
             if rd <= radius {
                 off[split_dim] = new_off;
-                self.within_unsorted_recurse(
+                self.for_each_within_recurse(
                     query,
                     radius,
                     distance_fn,
                     further_node_idx,
-                    next_split_dim,
-                    matching_items,
+                    f,
                     off,
                     rd,
                 );
@@ -120,7 +151,7 @@ where
         } else {
             let leaf_node = self
                 .leaves
-                .get_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
+                .index_unchecked((curr_node_idx - IDX::leaf_offset()).az::<usize>());
 
             leaf_node
                 .content_points
@@ -128,13 +159,10 @@ where
                 .enumerate()
                 .take(leaf_node.size.az::<usize>())
                 .for_each(|(idx, entry)| {
-                    let distance = distance_fn(query, entry);
+                    let distance = distance_fn.dist(query, entry);
 
-                    if distance < radius {
-                        matching_items.push(Neighbour {
-                            distance,
-                            item: *leaf_node.content_items.get_unchecked(idx.az::<usize>()),
-                        });
+                    if distance <= radius {
+                        f(distance, *leaf_node.content_items.index_unchecked(idx));
                     }
                 });
         }
@@ -247,6 +275,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn within_unsorted_matches_within_once_sorted() {
+        const TREE_SIZE: usize = 100_000;
+        const NUM_QUERIES: usize = 100;
+        const RADIUS: f32 = 0.5;
+
+        let content_to_add: Vec<([f32; 4], u32)> = (0..TREE_SIZE)
+            .map(|_| rand::random::<([f32; 4], u32)>())
+            .collect();
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, content)| tree.add(point, *content));
+
+        let query_points: Vec<[f32; 4]> = (0..NUM_QUERIES)
+            .map(|_| rand::random::<[f32; 4]>())
+            .collect();
+
+        for query_point in query_points {
+            let mut expected: Vec<_> = tree
+                .within(&query_point, RADIUS, &squared_euclidean)
+                .into_iter()
+                .map(|n| (n.distance, n.item))
+                .collect();
+            stabilize_sort(&mut expected);
+
+            let mut result: Vec<_> = tree
+                .within_unsorted(&query_point, RADIUS, &squared_euclidean)
+                .into_iter()
+                .map(|n| (n.distance, n.item))
+                .collect();
+            stabilize_sort(&mut result);
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn for_each_within_visits_the_same_elements_as_within() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+
+        let mut tree: KdTree<AX, u32, 4, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        for item in 0..TREE_SIZE {
+            tree.add(&rand::random::<[AX; 4]>(), item as u32);
+        }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_QUERIES {
+            let query_point = rand::random::<[AX; 4]>();
+            let radius = rng.gen_range(0.0..0.2);
+
+            let mut total = 0u64;
+            let mut count = 0;
+            tree.for_each_within(
+                &query_point,
+                radius,
+                &squared_euclidean,
+                |distance, item| {
+                    assert!(distance <= radius);
+                    total += item as u64;
+                    count += 1;
+                },
+            );
+
+            let within = tree.within(&query_point, radius, &squared_euclidean);
+            assert_eq!(count, within.len());
+            assert_eq!(
+                total,
+                within
+                    .iter()
+                    .map(|neighbour| neighbour.item as u64)
+                    .sum::<u64>()
+            );
+        }
+    }
+
     fn linear_search<A: Axis, const K: usize>(
         content: &[([A; K], u32)],
         query_point: &[A; K],
@@ -256,7 +362,7 @@ mod tests {
 
         for &(p, item) in content {
             let dist = squared_euclidean(query_point, &p);
-            if dist < radius {
+            if dist <= radius {
                 matching_items.push((dist, item));
             }
         }
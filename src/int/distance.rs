@@ -0,0 +1,206 @@
+//! Distance metrics for integer co-ordinates, which are accumulated in a wider integer type
+//! so that large differences between co-ordinates do not overflow.
+
+use num_traits::{PrimInt, Saturating, Unsigned, Zero};
+
+use crate::fixed::distance::DistanceMetric;
+use crate::fixed::kdtree::Axis;
+
+/// Implemented by the integer types that can be used as co-ordinates, to give the
+/// unsigned type of twice the width that distances between them are accumulated in.
+///
+/// The square of the difference between any two values fits in the wide type, so only
+/// the sum across axes can overflow it, and that saturates.
+pub trait WideAxis: Axis {
+    /// The unsigned type of twice the width that distances are accumulated in.
+    type Wide: PrimInt + Unsigned;
+
+    /// Returns the absolute difference between `self` and `other` in the wide type.
+    fn wide_dist(self, other: Self) -> Self::Wide;
+
+    /// Converts a distance back from the wide type, saturating at [`Axis::MAX`] if it
+    /// does not fit.
+    fn from_wide(wide: Self::Wide) -> Self;
+}
+
+macro_rules! impl_wide_axis {
+    ($($int:ty => $wide:ty)*) => {
+        $(
+            impl WideAxis for $int {
+                type Wide = $wide;
+
+                #[inline]
+                fn wide_dist(self, other: Self) -> Self::Wide {
+                    self.abs_diff(other).into()
+                }
+
+                #[inline]
+                fn from_wide(wide: Self::Wide) -> Self {
+                    Self::try_from(wide).unwrap_or(Self::MAX)
+                }
+            }
+        )*
+    };
+}
+
+impl_wide_axis! {
+    i32 => u64
+    i64 => u128
+}
+
+/// Returns the Manhattan / "taxi cab" distance between two points, saturating at the
+/// largest value of the co-ordinate type.
+///
+/// When querying the tree, pass [`Manhattan`] rather than this function so that
+/// the parts of the tree that get skipped are determined correctly.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::int::distance::manhattan;
+///
+/// assert_eq!(manhattan(&[0i64, 0], &[0, 0]), 0);
+/// assert_eq!(manhattan(&[0i64, 0], &[1, -1]), 2);
+/// assert_eq!(manhattan(&[i64::MIN, 0], &[i64::MAX, 0]), i64::MAX);
+/// ```
+pub fn manhattan<A: WideAxis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    A::from_wide(
+        a.iter()
+            .zip(b.iter())
+            .fold(A::Wide::zero(), |acc, (&a_val, &b_val)| {
+                acc.saturating_add(a_val.wide_dist(b_val))
+            }),
+    )
+}
+
+/// Manhattan / "taxi cab" distance metric, for use in queries. See [`manhattan`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Manhattan;
+
+impl<A: WideAxis, const K: usize> DistanceMetric<A, K> for Manhattan {
+    #[inline]
+    fn dist(&self, a: &[A; K], b: &[A; K]) -> A {
+        manhattan(a, b)
+    }
+
+    #[inline]
+    fn axis_dist(&self, a: A, b: A, _dim: usize) -> A {
+        a.dist(b)
+    }
+}
+
+/// Returns the squared euclidean distance between two points, saturating at the largest
+/// value of the co-ordinate type.
+///
+/// The squares of the differences along each axis are summed in the wider type given by
+/// [`WideAxis`], so that the result is exact whenever it fits in the co-ordinate type.
+///
+/// # Examples
+///
+/// ```rust
+/// use kiddo::int::distance::squared_euclidean;
+///
+/// assert_eq!(squared_euclidean(&[0i64, 0], &[0, 0]), 0);
+/// assert_eq!(squared_euclidean(&[0i64, 0], &[1, 0]), 1);
+/// assert_eq!(squared_euclidean(&[0i64, 0], &[-2, 2]), 8);
+/// assert_eq!(squared_euclidean(&[i64::MIN, 0], &[i64::MAX, 0]), i64::MAX);
+/// ```
+pub fn squared_euclidean<A: WideAxis, const K: usize>(a: &[A; K], b: &[A; K]) -> A {
+    A::from_wide(
+        a.iter()
+            .zip(b.iter())
+            .fold(A::Wide::zero(), |acc, (&a_val, &b_val)| {
+                let diff = a_val.wide_dist(b_val);
+                acc.saturating_add(diff * diff)
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::fixed::kdtree::KdTree;
+    use crate::int::distance::{manhattan, squared_euclidean, Manhattan};
+
+    #[test]
+    fn distances_saturate_rather_than_overflow() {
+        assert_eq!(squared_euclidean(&[i32::MIN; 3], &[i32::MAX; 3]), i32::MAX);
+        assert_eq!(manhattan(&[i32::MIN; 3], &[i32::MAX; 3]), i32::MAX);
+
+        assert_eq!(squared_euclidean(&[i64::MIN; 3], &[i64::MAX; 3]), i64::MAX);
+        assert_eq!(manhattan(&[i64::MIN; 3], &[i64::MAX; 3]), i64::MAX);
+
+        // each square fits, but the sum of them does not
+        let far = 3_037_000_499i64;
+        assert_eq!(squared_euclidean(&[0, 0], &[far, 0]), far * far);
+        assert_eq!(squared_euclidean(&[0, 0], &[far, far]), i64::MAX);
+    }
+
+    #[test]
+    fn can_query_a_tree_of_integer_points() {
+        const TREE_SIZE: usize = 10_000;
+        const NUM_QUERIES: usize = 100;
+        const RANGE: i64 = 1_000_000_000;
+
+        let mut rng = rand::thread_rng();
+        let mut random_point = || -> [i64; 3] {
+            [
+                rng.gen_range(-RANGE..RANGE),
+                rng.gen_range(-RANGE..RANGE),
+                rng.gen_range(-RANGE..RANGE),
+            ]
+        };
+
+        let content_to_add: Vec<([i64; 3], u32)> = (0..TREE_SIZE)
+            .map(|item| (random_point(), item as u32))
+            .collect();
+
+        let mut tree: KdTree<i64, u32, 3, 32, u32> = KdTree::with_capacity(TREE_SIZE);
+        content_to_add
+            .iter()
+            .for_each(|(point, item)| tree.add(point, *item));
+        assert_eq!(tree.size(), TREE_SIZE as u32);
+
+        for _ in 0..NUM_QUERIES {
+            let query_point = random_point();
+
+            let expected = content_to_add
+                .iter()
+                .map(|(p, item)| (squared_euclidean(&query_point, p), *item))
+                .min()
+                .unwrap();
+            assert_eq!(tree.nearest_one(&query_point, &squared_euclidean), expected);
+
+            let mut expected: Vec<(i64, u32)> = content_to_add
+                .iter()
+                .map(|(p, item)| (manhattan(&query_point, p), *item))
+                .collect();
+            expected.sort();
+            expected.truncate(10);
+
+            let result: Vec<(i64, u32)> = tree
+                .nearest_n(&query_point, 10, &Manhattan)
+                .into_iter()
+                .map(Into::into)
+                .collect();
+            assert_eq!(result, expected);
+
+            let radius = RANGE * RANGE / 10;
+            let mut expected: Vec<u32> = content_to_add
+                .iter()
+                .filter(|(p, _)| squared_euclidean(&query_point, p) <= radius)
+                .map(|(_, item)| *item)
+                .collect();
+            expected.sort();
+
+            let mut result: Vec<u32> = tree
+                .within(&query_point, radius, &squared_euclidean)
+                .into_iter()
+                .map(|neighbour| neighbour.item)
+                .collect();
+            result.sort();
+            assert_eq!(result, expected);
+        }
+    }
+}
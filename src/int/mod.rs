@@ -0,0 +1,9 @@
+//! Support for storing integer co-ordinates in a [`fixed::kdtree::KdTree`](crate::fixed::kdtree::KdTree).
+//!
+//! The fixed point k-d tree accepts `i32` and `i64` co-ordinates directly, without needing
+//! to wrap them in a fixed point type, eg `KdTree<i64, u32, 3, 32, u32>`. The distances
+//! between integer points quickly outgrow the co-ordinates themselves, so the distance
+//! metrics in [`distance`] accumulate in an integer type of twice the width, saturating at
+//! the largest value of the co-ordinate type rather than overflowing.
+
+pub mod distance;
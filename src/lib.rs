@@ -3,7 +3,8 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::broken_intra_doc_links)]
 #![warn(rustdoc::private_intra_doc_links)]
-#![doc(html_root_url = "https://docs.rs/kiddo/2.0.1")]
+#![doc(html_root_url = "https://docs.rs/kiddo/3.0.0")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![doc(issue_tracker_base_url = "https://github.com/sdd/kiddo/issues/")]
 
 //! # Kiddo
@@ -23,15 +24,43 @@
 //!  - Find the [nearest_n](`float::kdtree::KdTree::nearest_n`) item(s) to a query point, ordered by distance;
 //!  - Find all items [within](`float::kdtree::KdTree::within`) a specified radius of a query point;
 //!  - Find the ["best" n item(s) within](`float::kdtree::KdTree::best_n_within`) a specified distance of a query point, for some definition of "best"
+//!  - Find the [nearest_n item(s) within](`float::kdtree::KdTree::nearest_n_within`) a specified distance of a query point, ordered by distance;
 //!
 //! ## Installation
 //!
 //! Add `kiddo` to `Cargo.toml`
 //! ```toml
 //! [dependencies]
-//! kiddo = "2.0.1"
+//! kiddo = "3.0.0"
 //! ```
 //!
+//! Kiddo can be used in `no_std` environments that provide an allocator. Disable the
+//! default `std` feature to do so:
+//! ```toml
+//! [dependencies]
+//! kiddo = { version = "3.0.0", default-features = false }
+//! ```
+//! The `serialize` and `serialize_rkyv` features both require `std`.
+//!
+//! Enable the `half` feature to use [`half::f16`](https://docs.rs/half/latest/half/struct.f16.html)
+//! co-ordinates in a float [`KdTree`](`float::kdtree::KdTree`), which halves the memory used
+//! for points compared to `f32` at the cost of precision.
+//!
+//! Enable the `safe` feature to check every index into the tree's internal storage, so
+//! that an out-of-bounds access panics rather than being undefined behaviour. This is
+//! slower, and is intended for fuzzing and for running the tests under
+//! [Miri](https://github.com/rust-lang/miri):
+//! ```sh
+//! cargo +nightly miri test --features safe
+//! ```
+//!
+//! Enable the `bbox-cache` feature to store a bounding box of each subtree in the stems
+//! of a float [`KdTree`](`float::kdtree::KdTree`), which lets `within_bbox` skip
+//! subtrees that lie wholly outside the query box, at the cost of more memory per stem.
+//! The boxes are not serialized, so serialized trees are the same with or without it.
+//! They are recomputed when deserializing with serde or `from_bytes`; a tree deserialized
+//! from rkyv instead has unbounded boxes, and so gives correct results without skipping.
+//!
 //! ## Usage
 //! ```rust
 //! use kiddo::KdTree;
@@ -67,6 +96,8 @@
 //!
 //! See the [examples documentation](https://github.com/sdd/kiddo/tree/master/examples) for some more in-depth examples.
 
+extern crate alloc;
+
 #[cfg(feature = "serialize")]
 extern crate serde;
 #[cfg(feature = "serialize")]
@@ -75,13 +106,18 @@ extern crate serde_derive;
 #[cfg(feature = "serialize")]
 mod custom_serde;
 pub mod distance;
+pub mod error;
 
 pub mod fixed;
 pub mod float;
+pub mod int;
 mod mirror_select_nth_unstable_by;
+mod split_ties;
+#[cfg(any(feature = "std", test))]
 #[doc(hidden)]
 pub mod test_utils;
 pub mod types;
+mod unchecked;
 
 /// A floating-point k-d tree with default parameters.
 ///
@@ -1,7 +1,9 @@
-use std::cmp::Ordering;
-use std::cmp::Ordering::Less;
-use std::mem::MaybeUninit;
-use std::{cmp, mem, ptr};
+use core::cmp::Ordering;
+use core::cmp::Ordering::Less;
+use core::mem::MaybeUninit;
+use core::{cmp, mem, ptr};
+
+use crate::unchecked::IndexUnchecked;
 
 // performs select_nth_unstable_by on target,
 // but all the operations performed in the sort are applied to mirror as well
@@ -123,12 +125,12 @@ where
         //                     From here we know that `r` must be at least `r == l` which was shown to be valid from the first one.
         unsafe {
             // Find the first element greater than the pivot.
-            while l < r && !is_less(pivot, v.get_unchecked(l)) {
+            while l < r && !is_less(pivot, v.index_unchecked(l)) {
                 l += 1;
             }
 
             // Find the last element equal to the pivot.
-            while l < r && is_less(pivot, v.get_unchecked(r - 1)) {
+            while l < r && is_less(pivot, v.index_unchecked(r - 1)) {
                 r -= 1;
             }
 
@@ -202,12 +204,12 @@ where
         //                     From here we know that `r` must be at least `r == l` which was shown to be valid from the first one.
         unsafe {
             // Find the first element greater than or equal to the pivot.
-            while l < r && is_less(v.get_unchecked(l), pivot) {
+            while l < r && is_less(v.index_unchecked(l), pivot) {
                 l += 1;
             }
 
             // Find the last element smaller that the pivot.
-            while l < r && !is_less(v.get_unchecked(r - 1), pivot) {
+            while l < r && !is_less(v.index_unchecked(r - 1), pivot) {
                 r -= 1;
             }
         }
@@ -606,10 +608,10 @@ where
         // `a`, `b` and `c`. This means the three calls to `sort_adjacent` result in
         // corresponding calls to `sort3` with valid 3-item neighborhoods around each
         // pointer, which in turn means the calls to `sort2` are done with valid
-        // references. Thus the `v.get_unchecked` calls are safe, as is the `ptr::swap`
+        // references. Thus the `v.index_unchecked` calls are safe, as is the `ptr::swap`
         // call.
         let mut sort2 = |a: &mut usize, b: &mut usize| unsafe {
-            if is_less(v.get_unchecked(*b), v.get_unchecked(*a)) {
+            if is_less(v.index_unchecked(*b), v.index_unchecked(*a)) {
                 ptr::swap(a, b);
                 swaps += 1;
             }
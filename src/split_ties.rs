@@ -0,0 +1,24 @@
+//! Routing of points that are equal to a stem's split value as they are added to a tree.
+
+/// Returns `true` if a point that is being added, whose co-ordinate is equal to the split
+/// value of a stem at `depth` below the root, should go to the stem's left child.
+///
+/// Such a point can be stored on either side of the split, as queries already check both
+/// sides for points that are equal to the split value. Sending them all to the left, as
+/// is done for points below the split value, would turn many points at the same position
+/// into a chain of stems one level deeper for every `B / 2` of them, as no split can
+/// separate them. Instead, the side is picked by hashing `seed`, which changes every time
+/// a leaf is split, together with `depth`, so that such points are spread over a subtree
+/// whose height only grows with the logarithm of their number.
+#[inline]
+pub(crate) fn tie_goes_left(seed: usize, depth: usize) -> bool {
+    // the finalizer of MurmurHash3, which mixes every input bit into the lowest bit
+    let mut hash = (seed as u64) ^ (depth as u64).rotate_left(32);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+
+    hash & 1 == 0
+}
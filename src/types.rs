@@ -1,8 +1,8 @@
 //! Definitions for some types that are common between the [`fixed`](crate::fixed) and [`float`](crate::float) modules
 use az::Cast;
-use num_traits::{One, PrimInt, Unsigned, Zero};
-use std::fmt::Debug;
+use core::fmt::Debug;
 use divrem::DivCeil;
+use num_traits::{One, PrimInt, Unsigned, Zero};
 
 /// Content trait.
 ///
@@ -14,24 +14,28 @@ use divrem::DivCeil;
 /// a Vec, and try switching tqo a smaller type and benchmarking to see if you get better
 /// performance.
 pub trait Content:
-    Zero + One + PartialEq + Default + Clone + Copy + Ord + Debug + std::ops::SubAssign + Sync
+    Zero + One + PartialEq + Default + Clone + Copy + Ord + Debug + core::ops::SubAssign + Sync
 {
 }
 impl<
-        T: Zero + One + PartialEq + Default + Clone + Copy + Ord + Debug + std::ops::SubAssign + Sync,
+        T: Zero + One + PartialEq + Default + Clone + Copy + Ord + Debug + core::ops::SubAssign + Sync,
     > Content for T
 {
 }
 
-/// Implemented on u16 and u32 so that they can be used internally to index the
+/// Implemented on u8, u16 and u32 so that they can be used internally to index the
 /// `Vec`s of Stem and Leaf nodes.
 ///
-/// Allows `u32` or `u16` to be used as the 5th generic parameter of `float::KdTree`
+/// Allows `u32`, `u16` or `u8` to be used as the 5th generic parameter of `float::KdTree`
 /// and `fixed::KdTree`. If you will be storing fewer than `BUCKET_SIZE` * ~32k items
 /// in the tree, selecting `u16` will slightly reduce the size of the Stem Nodes,
 /// ensuring that more of them can be kept in the CPU cache, which may improve
 /// performance (this may be offset on some architectures if it results in a
-/// misalignment penalty).
+/// misalignment penalty). `u8` only allows for around 127 leaves, and so is only
+/// suitable for very small trees.
+///
+/// Adding items to a tree that has run out of indices is detected by `try_add`, which
+/// returns [`CapacityExceeded`](crate::error::CapacityExceeded).
 pub trait Index: PrimInt + Unsigned + Zero + Cast<usize> + Sync {
     #[doc(hidden)]
     type T: Cast<usize>;
@@ -92,3 +96,25 @@ impl Index for u16 {
         (u16::MAX - u16::MAX.overflowing_shr(1).0) as usize * bucket_size
     }
 }
+
+impl Index for u8 {
+    type T = u8;
+    fn max() -> u8 {
+        u8::MAX
+    }
+    fn min() -> u8 {
+        0u8
+    }
+    fn leaf_offset() -> u8 {
+        u8::MAX.overflowing_shr(1).0
+    }
+    fn ilog2(self) -> u8 {
+        u8::ilog2(self) as u8
+    }
+    fn div_ceil(self, b: u8) -> u8 {
+        DivCeil::div_ceil(self, b)
+    }
+    fn capacity_with_bucket_size(bucket_size: usize) -> usize {
+        (u8::MAX - u8::MAX.overflowing_shr(1).0) as usize * bucket_size
+    }
+}
@@ -0,0 +1,92 @@
+//! Indexing that skips bounds checks unless the `safe` feature is enabled.
+
+use core::slice::SliceIndex;
+
+/// Indexes into a slice without bounds checking, in the same way as
+/// [`slice::get_unchecked`], when the `safe` feature is disabled.
+///
+/// With the `safe` feature enabled, the index is checked and an out-of-bounds access
+/// panics instead of being undefined behaviour, so that the crate can be fuzzed and run
+/// under Miri. This is slower, and only intended for testing.
+pub(crate) trait IndexUnchecked<T> {
+    /// Returns a reference to the element or subslice at `index`.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in bounds, as for [`slice::get_unchecked`].
+    unsafe fn index_unchecked<I>(&self, index: I) -> &I::Output
+    where
+        I: SliceIndex<[T]>;
+
+    /// Returns a mutable reference to the element or subslice at `index`.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in bounds, as for [`slice::get_unchecked_mut`].
+    unsafe fn index_unchecked_mut<I>(&mut self, index: I) -> &mut I::Output
+    where
+        I: SliceIndex<[T]>;
+}
+
+impl<T> IndexUnchecked<T> for [T] {
+    #[inline(always)]
+    unsafe fn index_unchecked<I>(&self, index: I) -> &I::Output
+    where
+        I: SliceIndex<[T]>,
+    {
+        #[cfg(feature = "safe")]
+        {
+            &self[index]
+        }
+        #[cfg(not(feature = "safe"))]
+        {
+            self.get_unchecked(index)
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn index_unchecked_mut<I>(&mut self, index: I) -> &mut I::Output
+    where
+        I: SliceIndex<[T]>,
+    {
+        #[cfg(feature = "safe")]
+        {
+            &mut self[index]
+        }
+        #[cfg(not(feature = "safe"))]
+        {
+            self.get_unchecked_mut(index)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexUnchecked;
+
+    #[test]
+    fn indexes_elements_and_subslices() {
+        let mut values = [1, 2, 3, 4];
+
+        unsafe {
+            assert_eq!(*values.index_unchecked(2), 3);
+            assert_eq!(values.index_unchecked(1..3), &[2, 3]);
+
+            *values.index_unchecked_mut(0) = 5;
+            values.index_unchecked_mut(2..).copy_from_slice(&[6, 7]);
+        }
+
+        assert_eq!(values, [5, 2, 6, 7]);
+    }
+
+    #[cfg(feature = "safe")]
+    #[test]
+    #[should_panic]
+    fn panics_out_of_bounds_with_the_safe_feature() {
+        let values = [1, 2, 3, 4];
+
+        unsafe {
+            values.index_unchecked(4);
+        }
+    }
+}